@@ -3,9 +3,17 @@
 pub mod prelude;
 
 pub mod admin;
+pub mod api_token;
+pub mod audit_log;
 pub mod election;
 pub mod election_vote;
+pub mod fenix_snapshot;
 pub mod nomination;
 pub mod nomination_log;
+pub mod observer;
+pub mod pending_action;
+pub mod session;
+pub mod setting;
+pub mod signing_key;
 pub mod user_degree_override;
 pub mod vote_log;
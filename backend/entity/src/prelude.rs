@@ -1,9 +1,17 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.2
 
 pub use super::admin::Entity as Admin;
+pub use super::api_token::Entity as ApiToken;
+pub use super::audit_log::Entity as AuditLog;
 pub use super::election::Entity as Election;
 pub use super::election_vote::Entity as ElectionVote;
+pub use super::fenix_snapshot::Entity as FenixSnapshot;
 pub use super::nomination::Entity as Nomination;
 pub use super::nomination_log::Entity as NominationLog;
+pub use super::observer::Entity as Observer;
+pub use super::pending_action::Entity as PendingAction;
+pub use super::session::Entity as Session;
+pub use super::setting::Entity as Setting;
+pub use super::signing_key::Entity as SigningKey;
 pub use super::user_degree_override::Entity as UserDegreeOverride;
 pub use super::vote_log::Entity as VoteLog;
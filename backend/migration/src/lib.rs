@@ -4,6 +4,19 @@ mod m20220101_000001_create_table;
 mod m20230917_143144_nomination_log;
 mod m20231001_091623_nomination_valid_null;
 mod m20231008_162240_user_degree_override;
+mod m20231021_103512_session;
+mod m20231024_191207_signing_key;
+mod m20231027_150318_session_metadata;
+mod m20231103_101544_fenix_snapshot;
+mod m20231105_143027_setting;
+mod m20231108_094512_audit_log;
+mod m20231110_161204_admin_role;
+mod m20231112_103045_pending_action;
+mod m20231114_152318_observer;
+mod m20231116_094127_api_token;
+mod sqlite;
+
+pub use sqlite::SqliteMigrator;
 
 pub struct Migrator;
 
@@ -15,6 +28,16 @@ impl MigratorTrait for Migrator {
             Box::new(m20230917_143144_nomination_log::Migration),
             Box::new(m20231001_091623_nomination_valid_null::Migration),
             Box::new(m20231008_162240_user_degree_override::Migration),
+            Box::new(m20231021_103512_session::Migration),
+            Box::new(m20231024_191207_signing_key::Migration),
+            Box::new(m20231027_150318_session_metadata::Migration),
+            Box::new(m20231103_101544_fenix_snapshot::Migration),
+            Box::new(m20231105_143027_setting::Migration),
+            Box::new(m20231108_094512_audit_log::Migration),
+            Box::new(m20231110_161204_admin_role::Migration),
+            Box::new(m20231112_103045_pending_action::Migration),
+            Box::new(m20231114_152318_observer::Migration),
+            Box::new(m20231116_094127_api_token::Migration),
         ]
     }
 }
@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SigningKey::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SigningKey::Id)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SigningKey::Key).string().not_null())
+                    .col(ColumnDef::new(SigningKey::CreatedAt).date_time().not_null())
+                    // keys are deleted once their grace period has passed since they were retired
+                    .col(ColumnDef::new(SigningKey::RetiredAt).date_time().null())
+                    .col(ColumnDef::new(SigningKey::Replaces).string().null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // each key can only be replaced once, so that concurrent rotations
+        // can't both create a new current key
+        manager
+            .create_index(
+                Index::create()
+                    .unique()
+                    .name("idx-signing_key-replaces")
+                    .table(SigningKey::Table)
+                    .col(SigningKey::Replaces)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(SigningKey::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum SigningKey {
+    Table,
+    Id,
+    Key,
+    CreatedAt,
+    RetiredAt,
+    Replaces,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // columns are added one at a time, since SQLite does not support
+        // adding multiple columns in the same statement
+        // (revoked sessions are kept until they expire, so that requests that were
+        // in flight when they were revoked can't store them again)
+        for mut col in [
+            ColumnDef::new(Session::Username).string().null().to_owned(),
+            ColumnDef::new(Session::UserAgent)
+                .string()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Session::LoginTime)
+                .date_time()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Session::LastActivity)
+                .date_time()
+                .null()
+                .to_owned(),
+            ColumnDef::new(Session::RevokedAt)
+                .date_time()
+                .null()
+                .to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Session::Table)
+                        .add_column(&mut col)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-session-username")
+                    .table(Session::Table)
+                    .col(Session::Username)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx-session-username")
+                    .table(Session::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        for col in [
+            Session::Username,
+            Session::UserAgent,
+            Session::LoginTime,
+            Session::LastActivity,
+            Session::RevokedAt,
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Session::Table)
+                        .drop_column(col)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Session {
+    Table,
+    Username,
+    UserAgent,
+    LoginTime,
+    LastActivity,
+    RevokedAt,
+}
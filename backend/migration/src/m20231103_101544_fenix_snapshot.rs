@@ -0,0 +1,44 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(FenixSnapshot::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(FenixSnapshot::Key)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(FenixSnapshot::Value).text().not_null())
+                    .col(
+                        ColumnDef::new(FenixSnapshot::TakenAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(FenixSnapshot::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum FenixSnapshot {
+    Table,
+    Key,
+    Value,
+    TakenAt,
+}
@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AuditLog::Table)
+                    .if_not_exists()
+                    // ids are assigned sequentially by the app, instead of auto-incremented,
+                    // so that missing entries can be detected
+                    .col(
+                        ColumnDef::new(AuditLog::Id)
+                            .integer()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(AuditLog::Actor).string().not_null())
+                    .col(ColumnDef::new(AuditLog::Action).string().not_null())
+                    .col(ColumnDef::new(AuditLog::Target).string().not_null())
+                    .col(ColumnDef::new(AuditLog::Before).text())
+                    .col(ColumnDef::new(AuditLog::After).text())
+                    .col(ColumnDef::new(AuditLog::CreatedAt).date_time().not_null())
+                    .col(ColumnDef::new(AuditLog::PreviousHash).string().not_null())
+                    .col(ColumnDef::new(AuditLog::Hash).string().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AuditLog::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum AuditLog {
+    Table,
+    Id,
+    Actor,
+    Action,
+    Target,
+    Before,
+    After,
+    CreatedAt,
+    PreviousHash,
+    Hash,
+}
@@ -0,0 +1,54 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // existing admins keep being able to do everything, as before roles existed
+        // (columns are added one at a time, since SQLite does not support
+        // adding multiple columns in the same statement)
+        for mut col in [
+            ColumnDef::new(Admin::Role)
+                .string()
+                .not_null()
+                .default("SUPER_ADMIN")
+                .to_owned(),
+            ColumnDef::new(Admin::Scope).string().null().to_owned(),
+        ] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Admin::Table)
+                        .add_column(&mut col)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for col in [Admin::Role, Admin::Scope] {
+            manager
+                .alter_table(
+                    Table::alter()
+                        .table(Admin::Table)
+                        .drop_column(col)
+                        .to_owned(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Iden)]
+enum Admin {
+    Table,
+    Role,
+    Scope,
+}
@@ -0,0 +1,67 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PendingAction::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PendingAction::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PendingAction::Kind).string().not_null())
+                    .col(ColumnDef::new(PendingAction::Target).string().not_null())
+                    .col(ColumnDef::new(PendingAction::Action).text().not_null())
+                    .col(
+                        ColumnDef::new(PendingAction::ProposedBy)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingAction::ProposedAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PendingAction::ExpiresAt)
+                            .date_time()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PendingAction::Status).string().not_null())
+                    .col(ColumnDef::new(PendingAction::DecidedBy).string().null())
+                    .col(ColumnDef::new(PendingAction::DecidedAt).date_time().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PendingAction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum PendingAction {
+    Table,
+    Id,
+    Kind,
+    Target,
+    Action,
+    ProposedBy,
+    ProposedAt,
+    ExpiresAt,
+    Status,
+    DecidedBy,
+    DecidedAt,
+}
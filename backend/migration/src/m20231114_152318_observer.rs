@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Observer::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Observer::Username)
+                            .string()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Observer::GrantedBy).string().not_null())
+                    .col(ColumnDef::new(Observer::GrantedAt).date_time().not_null())
+                    .col(ColumnDef::new(Observer::ExpiresAt).date_time().not_null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Observer::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(Iden)]
+enum Observer {
+    Table,
+    Username,
+    GrantedBy,
+    GrantedAt,
+    ExpiresAt,
+}
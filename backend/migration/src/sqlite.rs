@@ -0,0 +1,137 @@
+//! SQLite can't modify columns, which some of the migrations do.
+//! Databases used by the tests are migrated with [`SqliteMigrator`] instead, which replaces
+//! those migrations with equivalent ones that rebuild the affected tables.
+
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::ConnectionTrait;
+
+use crate::{m20231001_091623_nomination_valid_null, Migrator};
+
+/// Same migrations as [`Migrator`], but which can be applied to SQLite databases.
+pub struct SqliteMigrator;
+
+#[async_trait::async_trait]
+impl MigratorTrait for SqliteMigrator {
+    fn migrations() -> Vec<Box<dyn MigrationTrait>> {
+        let nomination_valid_null = m20231001_091623_nomination_valid_null::Migration.name();
+
+        Migrator::migrations()
+            .into_iter()
+            .map(|migration| -> Box<dyn MigrationTrait> {
+                if migration.name() == nomination_valid_null {
+                    Box::new(NominationValidNull)
+                } else {
+                    migration
+                }
+            })
+            .collect()
+    }
+}
+
+/// Replacement for `m20231001_091623_nomination_valid_null`.
+struct NominationValidNull;
+
+impl MigrationName for NominationValidNull {
+    fn name(&self) -> &str {
+        m20231001_091623_nomination_valid_null::Migration.name()
+    }
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for NominationValidNull {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        rebuild_nomination_table(manager, ColumnDef::new(Nomination::Valid).boolean().null()).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        rebuild_nomination_table(
+            manager,
+            ColumnDef::new(Nomination::Valid).boolean().not_null(),
+        )
+        .await
+    }
+}
+
+/// Create the nomination table again with the new column definition,
+/// and copy the existing rows over.
+async fn rebuild_nomination_table(
+    manager: &SchemaManager<'_>,
+    valid_column: &mut ColumnDef,
+) -> Result<(), DbErr> {
+    manager
+        .create_table(
+            Table::create()
+                .table(NominationNew::Table)
+                .col(ColumnDef::new(Nomination::Election).integer().not_null())
+                .col(ColumnDef::new(Nomination::Username).string().not_null())
+                .col(ColumnDef::new(Nomination::DisplayName).string().not_null())
+                .col(valid_column)
+                .primary_key(
+                    Index::create()
+                        .col(Nomination::Election)
+                        .col(Nomination::Username),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk-nomination-election")
+                        .from(NominationNew::Table, Nomination::Election)
+                        .to(Election::Table, Election::Id)
+                        .on_delete(ForeignKeyAction::Restrict)
+                        .on_update(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+    let columns = [
+        Nomination::Election,
+        Nomination::Username,
+        Nomination::DisplayName,
+        Nomination::Valid,
+    ];
+    let copy_rows = Query::insert()
+        .into_table(NominationNew::Table)
+        .columns(columns)
+        .select_from(
+            Query::select()
+                .columns(columns)
+                .from(Nomination::Table)
+                .to_owned(),
+        )
+        .map_err(|err| DbErr::Migration(err.to_string()))?
+        .to_owned();
+    let db = manager.get_connection();
+    db.execute(db.get_database_backend().build(&copy_rows))
+        .await?;
+
+    manager
+        .drop_table(Table::drop().table(Nomination::Table).to_owned())
+        .await?;
+    manager
+        .rename_table(
+            Table::rename()
+                .table(NominationNew::Table, Nomination::Table)
+                .to_owned(),
+        )
+        .await
+}
+
+#[derive(Iden, Clone, Copy)]
+enum Nomination {
+    Table,
+    Election,
+    Username,
+    DisplayName,
+    Valid,
+}
+
+#[derive(Iden)]
+enum NominationNew {
+    Table,
+}
+
+#[derive(Iden)]
+enum Election {
+    Table,
+    Id,
+}
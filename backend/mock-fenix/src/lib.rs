@@ -0,0 +1,322 @@
+//! Mock implementation of the subset of the Fénix API used by the backend.
+
+use std::{collections::HashMap, fs, path::Path, sync::Arc};
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Redirect},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+const TECNICO_API_PREFIX: &str = "/tecnico-api/v2";
+const FENIX_OAUTH_PREFIX: &str = "/oauth";
+
+const DEFAULT_FIXTURES: &str = include_str!("../fixtures.toml");
+
+/// Users, degrees and academic year served by the mock server.
+#[derive(Deserialize, Serialize, Clone)]
+pub struct Fixtures {
+    /// Active academic year, in the format "2023/2024".
+    pub active_year: String,
+    #[serde(default)]
+    pub degrees: Vec<FixtureDegree>,
+    #[serde(default)]
+    pub users: Vec<FixtureUser>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct FixtureDegree {
+    pub id: String,
+    pub acronym: String,
+    pub name: HashMap<String, String>,
+    pub degree_type: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct FixtureUser {
+    pub username: String,
+    pub name: String,
+    pub display_name: Option<String>,
+    #[serde(default)]
+    pub degrees: Vec<FixtureDegreeEntry>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct FixtureDegreeEntry {
+    pub degree_id: String,
+    pub curricular_year: u8,
+    /// Registration state in Fénix, such as REGISTERED, MOBILITY or CONCLUDED.
+    #[serde(default = "default_state")]
+    pub state: String,
+}
+
+fn default_state() -> String {
+    "REGISTERED".to_string()
+}
+
+impl Default for Fixtures {
+    fn default() -> Self {
+        toml::from_str(DEFAULT_FIXTURES).expect("built-in fixtures must be valid")
+    }
+}
+
+impl Fixtures {
+    /// Read fixtures from a JSON (if the file has a .json extension) or TOML file.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Fixtures, String> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Failed to read fixtures file: {}", err))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => serde_json::from_str(&contents)
+                .map_err(|err| format!("Invalid fixtures file: {}", err)),
+            _ => toml::from_str(&contents).map_err(|err| format!("Invalid fixtures file: {}", err)),
+        }
+    }
+
+    fn find_user(&self, username: &str) -> Option<&FixtureUser> {
+        self.users.iter().find(|user| user.username == username)
+    }
+
+    fn active_year_json(&self) -> Value {
+        let (begin_year, end_year) = self
+            .active_year
+            .split_once('/')
+            .and_then(|(begin, end)| Some((begin.parse::<u32>().ok()?, end.parse::<u32>().ok()?)))
+            .unwrap_or_default();
+
+        json!({ "beginYear": begin_year, "endYear": end_year })
+    }
+}
+
+type AppState = Arc<Fixtures>;
+
+/// Build a router serving the mock Fénix API with the given fixtures.
+pub fn router(fixtures: Fixtures) -> Router {
+    let oauth_routes = Router::new()
+        .route("/userdialog", get(user_dialog))
+        .route("/access_token", post(access_token));
+
+    let api_routes = Router::new()
+        .route("/about", get(about))
+        .route("/degrees", get(degrees))
+        .route("/person", get(person))
+        .route("/person/search", get(person_search))
+        .route("/student/curriculum", get(curriculum));
+
+    Router::new()
+        .nest(FENIX_OAUTH_PREFIX, oauth_routes)
+        .nest(TECNICO_API_PREFIX, api_routes)
+        .with_state(Arc::new(fixtures))
+}
+
+#[derive(Deserialize)]
+struct UserDialogQuery {
+    redirect_uri: String,
+    state: Option<String>,
+    code: Option<String>,
+}
+
+/// Instead of asking for credentials, list all users so that any of them can be picked.
+async fn user_dialog(
+    State(fixtures): State<AppState>,
+    Query(query): Query<UserDialogQuery>,
+) -> impl IntoResponse {
+    // the state is sent back as is, like Fénix does
+    let state = query.state.as_deref().map(|state| ("state", state));
+
+    if let Some(code) = &query.code {
+        let params = serde_urlencoded::to_string(
+            [("code", code.as_str())]
+                .into_iter()
+                .chain(state)
+                .collect::<Vec<_>>(),
+        )
+        .unwrap_or_default();
+        return Redirect::to(&format!("{}?{}", query.redirect_uri, params)).into_response();
+    }
+
+    let users: String = fixtures
+        .users
+        .iter()
+        .map(|user| {
+            let link = serde_urlencoded::to_string(
+                [
+                    ("redirect_uri", query.redirect_uri.as_str()),
+                    ("code", user.username.as_str()),
+                ]
+                .into_iter()
+                .chain(state)
+                .collect::<Vec<_>>(),
+            )
+            .unwrap_or_default();
+            format!(
+                "<li><a href=\"?{}\">{} ({})</a></li>",
+                escape_html(&link),
+                escape_html(&user.name),
+                escape_html(&user.username)
+            )
+        })
+        .collect();
+
+    Html(format!(
+        "<!DOCTYPE html><html><body><h1>Mock Fénix</h1><p>Log in as:</p><ul>{}</ul></body></html>",
+        users
+    ))
+    .into_response()
+}
+
+/// Escape text so that it can be safely placed in HTML content or attributes.
+fn escape_html(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&#39;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+struct AccessTokenQuery {
+    code: String,
+}
+
+/// The OAuth code is the username, and it is used as the access token as well.
+async fn access_token(
+    State(fixtures): State<AppState>,
+    Query(query): Query<AccessTokenQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    let user = fixtures
+        .find_user(&query.code)
+        .ok_or(StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(json!({
+        "access_token": user.username,
+        "refresh_token": user.username,
+        "token_type": "Bearer",
+        "expires_in": 3600,
+    })))
+}
+
+fn bearer_token(headers: &HeaderMap) -> Result<&str, StatusCode> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .filter(|token| !token.is_empty())
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+fn authenticated_user<'a>(
+    fixtures: &'a Fixtures,
+    headers: &HeaderMap,
+) -> Result<&'a FixtureUser, StatusCode> {
+    fixtures
+        .find_user(bearer_token(headers)?)
+        .ok_or(StatusCode::UNAUTHORIZED)
+}
+
+async fn about(State(fixtures): State<AppState>) -> Json<Value> {
+    Json(json!({
+        "activeSemester": {
+            "year": fixtures.active_year_json(),
+        },
+    }))
+}
+
+async fn degrees(State(fixtures): State<AppState>) -> Json<Value> {
+    Json(
+        fixtures
+            .degrees
+            .iter()
+            .map(|degree| {
+                json!({
+                    "id": degree.id,
+                    "acronym": degree.acronym,
+                    "name": degree.name,
+                    "degreeType": degree.degree_type,
+                })
+            })
+            .collect(),
+    )
+}
+
+async fn person(
+    State(fixtures): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user = authenticated_user(&fixtures, &headers)?;
+
+    Ok(Json(json!({
+        "username": user.username,
+        "name": user.name,
+        "displayName": user.display_name.as_ref().unwrap_or(&user.name),
+    })))
+}
+
+async fn curriculum(
+    State(fixtures): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    let user = authenticated_user(&fixtures, &headers)?;
+
+    Ok(Json(
+        user.degrees
+            .iter()
+            .map(|entry| {
+                json!({
+                    "degree": { "id": entry.degree_id },
+                    "curricularYear": entry.curricular_year,
+                    "state": entry.state,
+                    "academicTerms": [{ "year": fixtures.active_year_json() }],
+                })
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Deserialize)]
+struct PersonSearchQuery {
+    name: String,
+    degree: Option<String>,
+    limit: Option<usize>,
+}
+
+async fn person_search(
+    State(fixtures): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PersonSearchQuery>,
+) -> Result<Json<Value>, StatusCode> {
+    // any token is accepted, so that users that aren't in the fixtures
+    // (e.g., logged in through the backend's development login) can search as well
+    bearer_token(&headers)?;
+
+    let search = query.name.to_lowercase();
+    let items: Vec<_> = fixtures
+        .users
+        .iter()
+        .filter(|user| {
+            user.name.to_lowercase().contains(&search)
+                || user.username.to_lowercase().contains(&search)
+        })
+        .filter(|user| match &query.degree {
+            Some(degree_id) => user
+                .degrees
+                .iter()
+                .any(|entry| &entry.degree_id == degree_id),
+            None => true,
+        })
+        .take(query.limit.unwrap_or(20))
+        .map(|user| json!({ "username": user.username, "name": user.name }))
+        .collect();
+
+    Ok(Json(json!({ "items": items })))
+}
@@ -0,0 +1,38 @@
+use std::net::{IpAddr, Ipv6Addr, SocketAddr};
+
+use mock_fenix::Fixtures;
+use tracing::info;
+
+#[tokio::main]
+async fn main() {
+    // enable console logging
+    tracing_subscriber::fmt::init();
+
+    let port: u16 = std::env::var("MOCK_FENIX_PORT")
+        .map(|port_str| {
+            port_str
+                .parse()
+                .expect("port must be a number between 0 and 65525")
+        })
+        .unwrap_or(5001);
+
+    let fixtures = match std::env::var("MOCK_FENIX_FIXTURES") {
+        Ok(path) => Fixtures::from_path(path).expect("Failed to load fixtures"),
+        Err(_) => Fixtures::default(),
+    };
+
+    info!(
+        "serving {} users and {} degrees",
+        fixtures.users.len(),
+        fixtures.degrees.len()
+    );
+
+    let sock_addr = SocketAddr::from((IpAddr::V6(Ipv6Addr::LOCALHOST), port));
+
+    info!("listening on http://{}", sock_addr);
+
+    axum::Server::bind(&sock_addr)
+        .serve(mock_fenix::router(fixtures).into_make_service())
+        .await
+        .expect("Unable to start server");
+}
@@ -0,0 +1,85 @@
+use axum::http::Method;
+use entity::api_token::{self, Entity as ApiToken};
+use rand::Rng;
+use sea_orm::{prelude::*, sea_query::Expr, DatabaseConnection};
+use sha2::{Digest, Sha256};
+
+use crate::{dtos::ApiTokenScopeDto, errors::AppError};
+
+/// Prepended to every secret, so that leaked tokens are easy to recognize.
+const SECRET_PREFIX: &str = "ide_";
+
+/// Generate the secret of a new token, which is only ever shown to whoever created it.
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+
+    format!("{}{}", SECRET_PREFIX, hex::encode(bytes))
+}
+
+/// Only the hash of a secret is stored, so that tokens can't be used by
+/// anyone with access to the database.
+pub fn hash_secret(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Which scope a token needs to make a request: reading is always done with
+/// GET requests, and everything else is considered a change.
+pub fn required_scope(method: &Method) -> ApiTokenScopeDto {
+    if method == Method::GET || method == Method::HEAD {
+        ApiTokenScopeDto::Read
+    } else {
+        ApiTokenScopeDto::Write
+    }
+}
+
+/// Find the token with the given secret, as long as it hasn't expired and has the scope
+/// required by the request, and record that it has just been used.
+pub async fn authenticate(
+    conn: &DatabaseConnection,
+    secret: &str,
+    method: &Method,
+) -> Result<api_token::Model, AppError> {
+    let now = chrono::Utc::now().naive_utc();
+
+    let token = ApiToken::find()
+        .filter(api_token::Column::TokenHash.eq(hash_secret(secret)))
+        .one(conn)
+        .await?
+        .filter(|token| token.expires_at > now)
+        .ok_or(AppError::Unauthorized)?;
+
+    if !ApiTokenScopeDto::parse_all(&token.scopes)?.contains(&required_scope(method)) {
+        return Err(AppError::ApiTokenScope);
+    }
+
+    ApiToken::update_many()
+        .col_expr(api_token::Column::LastUsedAt, Expr::value(now))
+        .filter(api_token::Column::Id.eq(token.id))
+        .exec(conn)
+        .await?;
+
+    Ok(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secrets() {
+        let secret = generate_secret();
+
+        assert!(secret.starts_with(SECRET_PREFIX));
+        assert_ne!(secret, generate_secret());
+        assert_eq!(hash_secret(&secret), hash_secret(&secret));
+        assert_ne!(hash_secret(&secret), secret);
+    }
+
+    #[test]
+    fn test_required_scope() {
+        assert_eq!(required_scope(&Method::GET), ApiTokenScopeDto::Read);
+        assert_eq!(required_scope(&Method::HEAD), ApiTokenScopeDto::Read);
+        assert_eq!(required_scope(&Method::POST), ApiTokenScopeDto::Write);
+        assert_eq!(required_scope(&Method::DELETE), ApiTokenScopeDto::Write);
+    }
+}
@@ -0,0 +1,301 @@
+use chrono::SubsecRound;
+use entity::audit_log::{self, Entity as AuditLog};
+use sea_orm::{
+    prelude::*, ConnectionTrait, DatabaseBackend, DatabaseTransaction, QueryOrder, QuerySelect,
+    Set, SqlErr, TransactionTrait,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tracing::debug;
+
+use crate::{
+    dtos::{AuditLogIssueDto, AuditLogIssueKindDto, AuditLogVerificationDto},
+    errors::AppError,
+};
+
+/// Previous hash of the first entry of the audit log.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Recorded as the actor of changes that aren't made by a logged in admin,
+/// such as those made from the command line.
+pub const SYSTEM_ACTOR: &str = "system";
+
+/// How many times appending an entry is attempted when other entries are appended concurrently.
+const MAX_RECORD_ATTEMPTS: u32 = 5;
+
+/// How many entries are loaded at a time when verifying the audit log.
+const VERIFICATION_BATCH_SIZE: u64 = 500;
+
+/// Admin operations that are recorded in the audit log.
+#[derive(Clone, Copy)]
+pub enum AuditAction {
+    SetupFirstAdmin,
+    AddAdmin,
+    RemoveAdmin,
+    CreateElections,
+    DeleteElection,
+    RescheduleElection,
+    AddNomination,
+    EditNomination,
+    AddUserDegreeOverrides,
+    DeleteUserDegreeOverrides,
+    PinAcademicYear,
+    ProposeAction,
+    ApproveAction,
+    RejectAction,
+    GrantObserver,
+    RevokeObserver,
+    CreateApiToken,
+    RevokeApiToken,
+}
+
+impl AuditAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SetupFirstAdmin => "admin.setup",
+            Self::AddAdmin => "admin.add",
+            Self::RemoveAdmin => "admin.remove",
+            Self::CreateElections => "elections.create",
+            Self::DeleteElection => "elections.delete",
+            Self::RescheduleElection => "elections.reschedule",
+            Self::AddNomination => "nomination.add",
+            Self::EditNomination => "nomination.edit",
+            Self::AddUserDegreeOverrides => "user-degree-overrides.add",
+            Self::DeleteUserDegreeOverrides => "user-degree-overrides.delete",
+            Self::PinAcademicYear => "academic-year.pin",
+            Self::ProposeAction => "pending-action.propose",
+            Self::ApproveAction => "pending-action.approve",
+            Self::RejectAction => "pending-action.reject",
+            Self::GrantObserver => "observer.grant",
+            Self::RevokeObserver => "observer.revoke",
+            Self::CreateApiToken => "api-token.create",
+            Self::RevokeApiToken => "api-token.revoke",
+        }
+    }
+}
+
+/// Append an entry to the audit log, chained to the previous one.
+///
+/// This must be done in the same transaction as the operation being recorded, so that
+/// neither is persisted without the other. On Postgres, the audit log is locked until
+/// the transaction ends, so that concurrent operations can't fork the chain. Elsewhere,
+/// an entry that clashes with one appended concurrently is retried on top of it.
+pub async fn record(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    action: AuditAction,
+    target: &str,
+    before: Option<Value>,
+    after: Option<Value>,
+) -> Result<(), AppError> {
+    if txn.get_database_backend() == DatabaseBackend::Postgres {
+        txn.execute_unprepared("LOCK TABLE audit_log IN EXCLUSIVE MODE")
+            .await?;
+    }
+
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        // the savepoint keeps a failed insert from aborting the caller's transaction
+        let savepoint = txn.begin().await?;
+        match insert_next(&savepoint, actor, action, target, &before, &after).await {
+            Ok(()) => {
+                savepoint.commit().await?;
+                break;
+            }
+            Err(err)
+                if attempts < MAX_RECORD_ATTEMPTS
+                    && matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) =>
+            {
+                debug!("audit log entry was appended concurrently, retrying");
+                savepoint.rollback().await?;
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+
+    Ok(())
+}
+
+/// Insert the entry that follows the last one in the audit log.
+async fn insert_next(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    action: AuditAction,
+    target: &str,
+    before: &Option<Value>,
+    after: &Option<Value>,
+) -> Result<(), DbErr> {
+    // on MySQL, this locks the last entry until the transaction ends, and makes sure
+    // that entries committed in the meantime by other transactions are seen
+    let last_entry = AuditLog::find()
+        .order_by_desc(audit_log::Column::Id)
+        .lock_exclusive()
+        .one(txn)
+        .await?;
+    let (id, previous_hash) = match last_entry {
+        Some(entry) => (entry.id + 1, entry.hash),
+        None => (1, GENESIS_HASH.to_string()),
+    };
+
+    let mut entry = audit_log::Model {
+        id,
+        actor: actor.to_string(),
+        action: action.as_str().to_string(),
+        target: target.to_string(),
+        before: before.as_ref().map(Value::to_string),
+        after: after.as_ref().map(Value::to_string),
+        // not every database keeps fractions of a second (such as MySQL),
+        // which would make the hash impossible to verify
+        created_at: chrono::Utc::now().naive_utc().trunc_subsecs(0),
+        previous_hash,
+        hash: String::new(),
+    };
+    entry.hash = compute_hash(&entry);
+
+    audit_log::ActiveModel {
+        id: Set(entry.id),
+        actor: Set(entry.actor),
+        action: Set(entry.action),
+        target: Set(entry.target),
+        before: Set(entry.before),
+        after: Set(entry.after),
+        created_at: Set(entry.created_at),
+        previous_hash: Set(entry.previous_hash),
+        hash: Set(entry.hash),
+    }
+    .insert(txn)
+    .await?;
+
+    Ok(())
+}
+
+/// Get a page of the audit log, most recent entries first, starting before the given id.
+pub async fn list<C: ConnectionTrait>(
+    conn: &C,
+    before: Option<i32>,
+    limit: u64,
+) -> Result<Vec<audit_log::Model>, AppError> {
+    let mut query = AuditLog::find();
+    if let Some(before) = before {
+        query = query.filter(audit_log::Column::Id.lt(before));
+    }
+
+    Ok(query
+        .order_by_desc(audit_log::Column::Id)
+        .limit(limit)
+        .all(conn)
+        .await?)
+}
+
+/// Check that no entry of the audit log is missing or has been edited, by following the
+/// chain of hashes from the first entry to the last one.
+///
+/// Entries removed from the end of the log can't be detected this way, so the hash of the
+/// last entry should be kept elsewhere and compared with the one returned.
+pub async fn verify<C: ConnectionTrait>(conn: &C) -> Result<AuditLogVerificationDto, AppError> {
+    let mut entries = 0;
+    let mut last_hash = None;
+    let mut previous_hash = GENESIS_HASH.to_string();
+
+    let mut pages = AuditLog::find()
+        .order_by_asc(audit_log::Column::Id)
+        .paginate(conn, VERIFICATION_BATCH_SIZE);
+    while let Some(page) = pages.fetch_and_next().await? {
+        for entry in page {
+            let issue = if entry.id != entries as i32 + 1 {
+                Some(AuditLogIssueKindDto::Gap)
+            } else if entry.previous_hash != previous_hash {
+                Some(AuditLogIssueKindDto::BrokenChain)
+            } else if compute_hash(&entry) != entry.hash {
+                Some(AuditLogIssueKindDto::HashMismatch)
+            } else {
+                None
+            };
+
+            if let Some(kind) = issue {
+                return Ok(AuditLogVerificationDto {
+                    valid: false,
+                    entries,
+                    last_hash,
+                    issue: Some(AuditLogIssueDto { id: entry.id, kind }),
+                });
+            }
+
+            entries += 1;
+            previous_hash = entry.hash.clone();
+            last_hash = Some(entry.hash);
+        }
+    }
+
+    Ok(AuditLogVerificationDto {
+        valid: true,
+        entries,
+        last_hash,
+        issue: None,
+    })
+}
+
+/// Hash all fields of an entry (except for its own hash), including the hash of the previous one.
+pub fn compute_hash(entry: &audit_log::Model) -> String {
+    let payload = json!([
+        entry.id,
+        entry.actor,
+        entry.action,
+        entry.target,
+        entry.before,
+        entry.after,
+        entry.created_at.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        entry.previous_hash,
+    ]);
+
+    hex::encode(Sha256::digest(payload.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use entity::audit_log;
+
+    use super::{compute_hash, GENESIS_HASH};
+
+    fn entry() -> audit_log::Model {
+        audit_log::Model {
+            id: 1,
+            actor: "ist1100005".to_string(),
+            action: "admin.add".to_string(),
+            target: "ist1100000".to_string(),
+            before: None,
+            after: Some("{\"username\":\"ist1100000\"}".to_string()),
+            created_at: NaiveDate::from_ymd_opt(2023, 11, 8)
+                .unwrap()
+                .and_hms_opt(9, 45, 12)
+                .unwrap(),
+            previous_hash: GENESIS_HASH.to_string(),
+            hash: String::new(),
+        }
+    }
+
+    #[test]
+    fn hash_covers_all_fields() {
+        let hash = compute_hash(&entry());
+        assert_eq!(hash.len(), 64);
+        assert_eq!(compute_hash(&entry()), hash);
+
+        let edits: [fn(&mut audit_log::Model); 8] = [
+            |entry| entry.id = 2,
+            |entry| entry.actor = "ist1100000".to_string(),
+            |entry| entry.action = "admin.remove".to_string(),
+            |entry| entry.target = "ist1100001".to_string(),
+            |entry| entry.before = Some("null".to_string()),
+            |entry| entry.after = None,
+            |entry| entry.created_at += chrono::Duration::seconds(1),
+            |entry| entry.previous_hash = "1".repeat(64),
+        ];
+        for edit in edits {
+            let mut edited = entry();
+            edit(&mut edited);
+            assert_ne!(compute_hash(&edited), hash);
+        }
+    }
+}
@@ -1,15 +1,221 @@
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts},
+};
 use axum_sessions::SessionHandle;
-use entity::admin;
-use sea_orm::{DatabaseConnection, EntityTrait};
+use entity::{admin, election, observer};
+use sea_orm::{prelude::DateTimeUtc, DatabaseConnection, EntityTrait};
 
 use entity::election::Model as Election;
 
-use crate::{dtos::UserDto, errors::AppError, services::fenix::OAuthResponse};
+use crate::{
+    api_tokens,
+    clock::Clock,
+    dtos::{AdminRoleDto, DegreeDto, UserDto},
+    errors::AppError,
+    logging,
+    services::{fenix::FenixService, identity::OAuthResponse},
+};
+
+/// An admin making a request, either logged in or with an API token, along with the role
+/// that determines what they can change.
+///
+/// Extracting it only checks that the user is an admin, whatever their role: changes must be
+/// checked with [`SuperAdmin`], [`AdminUser::get_manageable_election`] or
+/// [`AdminUser::assert_can_manage_degree`], and reads of degree data with
+/// [`AdminUser::can_view_degree`] or [`AdminUser::assert_can_view_degree`].
+pub struct AdminUser {
+    pub username: String,
+    pub role: AdminRoleDto,
+    /// Degree id or degree type the role is restricted to, for coordinators.
+    pub scope: Option<String>,
+    /// Id of the API token the request was made with, if it wasn't made with a session.
+    pub api_token: Option<i32>,
+}
+
+impl AdminUser {
+    /// Whether the admin can change elections, nominations and overrides of the degree.
+    pub fn can_manage_degree(&self, degree: &DegreeDto) -> bool {
+        match (self.role, &self.scope) {
+            (AdminRoleDto::SuperAdmin, _) => true,
+            (AdminRoleDto::DegreeTypeCoordinator, Some(degree_type)) => {
+                degree.degree_type.values().any(|name| name == degree_type)
+            }
+            (AdminRoleDto::DegreeCoordinator, Some(degree_id)) => &degree.id == degree_id,
+            _ => false,
+        }
+    }
+
+    /// Check that the admin can change elections, nominations and overrides of the degree
+    /// with the given id. Unknown degrees can only be managed by super admins.
+    pub async fn assert_can_manage_degree(
+        &self,
+        fenix_service: &FenixService,
+        degree_id: &str,
+    ) -> Result<(), AppError> {
+        if self.role == AdminRoleDto::SuperAdmin {
+            return Ok(());
+        }
+
+        match fenix_service.get_degree(degree_id).await? {
+            Some(degree) if self.can_manage_degree(&degree) => Ok(()),
+            _ => Err(AppError::Forbidden),
+        }
+    }
+
+    /// Whether the admin can see elections, nominations and overrides of the degree, if it is
+    /// known. Super admins and auditors can see every degree, but coordinators only those
+    /// they can manage.
+    pub fn can_view_degree(&self, degree: Option<&DegreeDto>) -> bool {
+        match self.role {
+            AdminRoleDto::SuperAdmin | AdminRoleDto::Auditor => true,
+            _ => degree.is_some_and(|degree| self.can_manage_degree(degree)),
+        }
+    }
+
+    /// Check that the admin can see elections, nominations and overrides of the degree
+    /// with the given id.
+    pub async fn assert_can_view_degree(
+        &self,
+        fenix_service: &FenixService,
+        degree_id: &str,
+    ) -> Result<(), AppError> {
+        if matches!(self.role, AdminRoleDto::SuperAdmin | AdminRoleDto::Auditor) {
+            return Ok(());
+        }
+
+        let degree = fenix_service.get_degree(degree_id).await?;
+        if self.can_view_degree(degree.as_ref()) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden)
+        }
+    }
+
+    /// Get the given election, if the admin can change it, according to its degree.
+    pub async fn get_manageable_election(
+        &self,
+        conn: &DatabaseConnection,
+        fenix_service: &FenixService,
+        election_id: i32,
+    ) -> Result<Election, AppError> {
+        let election = election::Entity::find_by_id(election_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::UnknownElection)?;
+        self.assert_can_manage_degree(fenix_service, &election.degree_id)
+            .await?;
+
+        Ok(election)
+    }
+
+    /// Check that the request wasn't made with an API token, for what tokens can't do,
+    /// such as creating more tokens.
+    pub fn assert_session(&self) -> Result<(), AppError> {
+        match self.api_token {
+            Some(_) => Err(AppError::SessionRequired),
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AdminUser
+where
+    DatabaseConnection: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let conn = DatabaseConnection::from_ref(state);
+
+        if let Some(secret) = get_bearer_token(parts)? {
+            let token = api_tokens::authenticate(&conn, &secret, &parts.method).await?;
+            logging::record_username(&token.username);
+
+            return find_admin(&conn, token.username, Some(token.id)).await;
+        }
+
+        let user = get_user(get_session_handle(parts)?).await?;
+        find_admin(&conn, user.username, None).await
+    }
+}
+
+/// A super admin, which is required to manage other admins and everything that isn't
+/// specific to a degree.
+pub struct SuperAdmin(pub AdminUser);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for SuperAdmin
+where
+    DatabaseConnection: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let admin = AdminUser::from_request_parts(parts, state).await?;
+
+        if admin.role == AdminRoleDto::SuperAdmin {
+            Ok(Self(admin))
+        } else {
+            Err(AppError::Forbidden)
+        }
+    }
+}
+
+/// Someone who can follow elections without changing them: an admin, or a logged in
+/// observer whose access hasn't expired yet.
+/// Observers must only be shown what can't be used to tell how anyone voted.
+pub enum Viewer {
+    Admin(AdminUser),
+    /// A user granted temporary, read-only access, such as a student association.
+    Observer(UserDto),
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Viewer
+where
+    DatabaseConnection: FromRef<S>,
+    Clock: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let conn = DatabaseConnection::from_ref(state);
+
+        // API tokens are only ever issued to admins
+        if get_bearer_token(parts)?.is_some() {
+            return Ok(Self::Admin(
+                AdminUser::from_request_parts(parts, state).await?,
+            ));
+        }
+
+        let user = get_user(get_session_handle(parts)?).await?;
+        if let Some(admin) = admin::Entity::find_by_id(&user.username).one(&conn).await? {
+            return Ok(Self::Admin(AdminUser {
+                username: user.username,
+                role: admin.role.parse()?,
+                scope: admin.scope,
+                api_token: None,
+            }));
+        }
+
+        match get_observer_until(&user.username, &conn, &Clock::from_ref(state)).await? {
+            Some(_) => Ok(Self::Observer(user)),
+            None => Err(AppError::Forbidden),
+        }
+    }
+}
 
 pub async fn get_user(session_handle: &SessionHandle) -> Result<UserDto, AppError> {
     let session = session_handle.read().await;
 
     let user: UserDto = session.get("user").ok_or(AppError::Unauthorized)?;
+    logging::record_username(&user.username);
     Ok(user)
 }
 
@@ -20,24 +226,73 @@ pub async fn get_user_tokens(session_handle: &SessionHandle) -> Result<OAuthResp
     Ok(tokens)
 }
 
-pub async fn get_admin(
-    session_handle: &SessionHandle,
+/// The secret of the API token the request was made with, if any.
+fn get_bearer_token(parts: &Parts) -> Result<Option<String>, AppError> {
+    let Some(header) = parts.headers.get(AUTHORIZATION) else {
+        return Ok(None);
+    };
+
+    header
+        .to_str()
+        .ok()
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .map(|secret| Some(secret.trim().to_string()))
+        .ok_or(AppError::Unauthorized)
+}
+
+fn get_session_handle(parts: &Parts) -> Result<&SessionHandle, AppError> {
+    parts
+        .extensions
+        .get::<SessionHandle>()
+        .ok_or(AppError::Unauthorized)
+}
+
+async fn find_admin(
     conn: &DatabaseConnection,
-) -> Result<UserDto, AppError> {
-    let user = get_user(session_handle).await?;
+    username: String,
+    api_token: Option<i32>,
+) -> Result<AdminUser, AppError> {
+    let admin = admin::Entity::find_by_id(&username)
+        .one(conn)
+        .await?
+        .ok_or(AppError::Forbidden)?;
 
-    if is_admin(&user.username, conn).await? {
-        Ok(user)
-    } else {
-        Err(AppError::Forbidden)
-    }
+    Ok(AdminUser {
+        username,
+        role: admin.role.parse()?,
+        scope: admin.scope,
+        api_token,
+    })
 }
 
-pub async fn is_admin(username: &str, conn: &DatabaseConnection) -> Result<bool, AppError> {
-    Ok(admin::Entity::find_by_id(username)
+/// Until when the user can observe elections, if their access hasn't expired yet
+/// according to the app's clock, which elections follow too.
+pub async fn get_observer_until(
+    username: &str,
+    conn: &DatabaseConnection,
+    clock: &Clock,
+) -> Result<Option<DateTimeUtc>, AppError> {
+    let now = clock.now();
+
+    Ok(observer::Entity::find_by_id(username)
         .one(conn)
         .await?
-        .is_some())
+        .map(|observer| observer.expires_at.and_utc())
+        .filter(|expires_at| *expires_at > now))
+}
+
+pub async fn is_admin(username: &str, conn: &DatabaseConnection) -> Result<bool, AppError> {
+    Ok(get_admin_role(username, conn).await?.is_some())
+}
+
+pub async fn get_admin_role(
+    username: &str,
+    conn: &DatabaseConnection,
+) -> Result<Option<AdminRoleDto>, AppError> {
+    match admin::Entity::find_by_id(username).one(conn).await? {
+        Some(admin) => Ok(Some(admin.role.parse()?)),
+        None => Ok(None),
+    }
 }
 
 /// A user can vote on the election if they are attending the corresponding degree,
@@ -76,7 +331,7 @@ pub fn can_self_nominate_on_election(user: &UserDto, election: &Election) -> Res
 
 #[cfg(test)]
 mod tests {
-    use chrono::NaiveDateTime;
+    use chrono::DateTime;
 
     use crate::dtos::DegreeEntryDto;
 
@@ -105,13 +360,56 @@ mod tests {
             id: 1,
             round: 1,
             academic_year: "2022/2023".to_string(),
-            voting_period_start: NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
-            voting_period_end: NaiveDateTime::from_timestamp_opt(0, 0).unwrap(),
+            voting_period_start: DateTime::from_timestamp(0, 0).unwrap().naive_utc(),
+            voting_period_end: DateTime::from_timestamp(0, 0).unwrap().naive_utc(),
             candidacy_period_start: None,
             candidacy_period_end: None,
         }
     }
 
+    fn get_admin_with_role(role: AdminRoleDto, scope: Option<&str>) -> AdminUser {
+        AdminUser {
+            username: "demo".to_string(),
+            role,
+            scope: scope.map(str::to_string),
+            api_token: None,
+        }
+    }
+
+    fn get_degree(id: &str, degree_type: &str) -> DegreeDto {
+        DegreeDto {
+            id: id.to_string(),
+            acronym: "DEG".to_string(),
+            name: Default::default(),
+            degree_type: [("pt-PT".to_string(), degree_type.to_string())].into(),
+        }
+    }
+
+    #[test]
+    fn test_can_manage_degree() {
+        let bachelor = get_degree("123456", "Licenciatura Bolonha");
+        let master = get_degree("654321", "Mestrado Bolonha");
+
+        let super_admin = get_admin_with_role(AdminRoleDto::SuperAdmin, None);
+        let degree_coordinator =
+            get_admin_with_role(AdminRoleDto::DegreeCoordinator, Some("123456"));
+        let masters_coordinator = get_admin_with_role(
+            AdminRoleDto::DegreeTypeCoordinator,
+            Some("Mestrado Bolonha"),
+        );
+        let unscoped_coordinator = get_admin_with_role(AdminRoleDto::DegreeCoordinator, None);
+        let auditor = get_admin_with_role(AdminRoleDto::Auditor, None);
+
+        assert!(super_admin.can_manage_degree(&bachelor));
+        assert!(super_admin.can_manage_degree(&master));
+        assert!(degree_coordinator.can_manage_degree(&bachelor));
+        assert!(!degree_coordinator.can_manage_degree(&master));
+        assert!(!masters_coordinator.can_manage_degree(&bachelor));
+        assert!(masters_coordinator.can_manage_degree(&master));
+        assert!(!unscoped_coordinator.can_manage_degree(&bachelor));
+        assert!(!auditor.can_manage_degree(&bachelor));
+    }
+
     #[test]
     fn test_can_vote_on_election() {
         let user = get_user_with_degrees(&[("123456", 1), ("654321", 3)]);
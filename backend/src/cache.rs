@@ -1,106 +1,604 @@
 use std::{
+    collections::HashMap,
     future::Future,
-    sync::Arc,
-    time::{Duration, Instant},
+    hash::Hash,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
-use tokio::sync::RwLock;
+use tokio::{sync::RwLock, time::Instant};
+use tracing::warn;
+
+use crate::metrics::{metrics, CacheResult};
 
 struct CachedValue<T: Clone> {
     value: Option<T>,
     instant: Instant,
-    duration_seconds: u64,
+    duration: Duration,
+    max_staleness: Duration,
 }
 
 impl<T: Clone> CachedValue<T> {
-    fn new(duration_seconds: u64) -> Self {
+    fn new(duration: Duration, max_staleness: Duration) -> Self {
         Self {
             value: None,
             instant: Instant::now(),
-            duration_seconds,
+            duration,
+            max_staleness,
         }
     }
 
     fn get_value(&self) -> Option<&T> {
         self.value
             .as_ref()
-            .filter(|_| self.instant.elapsed().as_secs() < self.duration_seconds)
+            .filter(|_| self.instant.elapsed() < self.duration)
+    }
+
+    /// Get the value even if it has expired, as long as it isn't older than the maximum staleness.
+    fn get_stale_value(&self) -> Option<&T> {
+        self.value
+            .as_ref()
+            .filter(|_| self.instant.elapsed() < self.duration + self.max_staleness)
     }
 
     fn with_value(&mut self, value: T) {
         self.value = Some(value);
         self.instant = Instant::now();
     }
+
+    fn with_expired_value(&mut self, value: T) {
+        self.value = Some(value);
+        self.instant = Instant::now()
+            .checked_sub(self.duration)
+            .unwrap_or_else(Instant::now);
+    }
 }
 
 /// Helper structure that caches a specific value for the given amount of time.
 /// If the cache has expired when getting the value, the value will be fetched again.
+///
+/// Optionally, an expired value can keep being served for a while (see [`Cached::with_max_staleness`]),
+/// in which case it is refreshed in the background instead.
+///
+/// Values are fetched without holding the lock of the cached value, so that other callers
+/// aren't held up by a slow fetch. Concurrent misses only fetch the value once.
+///
+/// The name identifies the cache in metrics.
 #[derive(Clone)]
-pub struct Cached<T: Clone>(Arc<RwLock<CachedValue<T>>>);
+pub struct Cached<T: Clone> {
+    name: &'static str,
+    value: Arc<RwLock<CachedValue<T>>>,
+    /// Lock held while fetching a missing value, so that concurrent misses wait for it.
+    fetching: Arc<tokio::sync::Mutex<()>>,
+    refreshing: Arc<AtomicBool>,
+}
 
-impl<T: Clone> Cached<T> {
-    pub fn new(duration: Duration) -> Self {
-        Self(Arc::new(RwLock::new(CachedValue::new(duration.as_secs()))))
+impl<T: Clone + Send + Sync + 'static> Cached<T> {
+    pub fn new(name: &'static str, duration: Duration) -> Self {
+        Self::with_max_staleness(name, duration, Duration::ZERO)
     }
 
-    pub async fn get<E, Fut>(&self, fetch_new_value: impl Fn() -> Fut) -> Result<T, E>
+    /// Cache a value for the given amount of time, after which it can still be served
+    /// for up to `max_staleness` while it is refreshed in the background.
+    /// If refreshing fails, the stale value keeps being served until it is older than that.
+    pub fn with_max_staleness(
+        name: &'static str,
+        duration: Duration,
+        max_staleness: Duration,
+    ) -> Self {
+        Self {
+            name,
+            value: Arc::new(RwLock::new(CachedValue::new(duration, max_staleness))),
+            fetching: Arc::new(tokio::sync::Mutex::new(())),
+            refreshing: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Replace the cached value, as if it had just been fetched.
+    pub async fn set(&self, value: T) {
+        self.value.write().await.with_value(value);
+    }
+
+    /// Set a value that has already expired, such as one loaded from persistent storage.
+    /// If there is no other value cached, it is served (as a stale value) while a new one is fetched.
+    pub async fn set_expired(&self, value: T) {
+        let mut cached_value = self.value.write().await;
+        if cached_value.value.is_none() {
+            cached_value.with_expired_value(value);
+        }
+    }
+
+    pub async fn get<E, Fut>(
+        &self,
+        fetch_new_value: impl FnOnce() -> Fut + Send + 'static,
+    ) -> Result<T, E>
     where
-        Fut: Future<Output = Result<T, E>>,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
     {
-        let cached_value = self.0.read().await;
+        let cached_value = self.value.read().await;
 
         if let Some(value) = cached_value.get_value() {
+            self.record(CacheResult::Hit);
             return Ok(value.clone());
         }
 
-        // cache miss, fetch new value
-        drop(cached_value); // release lock
-        let mut cached_value = self.0.write().await;
+        if let Some(value) = cached_value.get_stale_value().cloned() {
+            self.record(CacheResult::Stale);
+            if !self.refreshing.swap(true, Ordering::AcqRel) {
+                self.refresh_in_background(fetch_new_value);
+            }
+            return Ok(value);
+        }
 
-        // re-check if value is valid, since another thread could have changed the cache
-        if let Some(value) = cached_value.get_value() {
+        // cache miss, wait for any other fetch of the value while not holding its lock
+        drop(cached_value);
+        let _fetch_guard = self.fetching.lock().await;
+
+        // re-check if value is valid, since it could have been fetched while waiting
+        if let Some(value) = self.value.read().await.get_value() {
+            self.record(CacheResult::Hit);
             return Ok(value.clone());
         }
 
+        self.record(CacheResult::Miss);
         let new_value = fetch_new_value().await?;
-        cached_value.with_value(new_value);
+        self.value.write().await.with_value(new_value.clone());
 
-        Ok(cached_value.value.as_ref().unwrap().clone())
+        Ok(new_value)
+    }
+
+    fn refresh_in_background<E, Fut>(&self, fetch_new_value: impl FnOnce() -> Fut + Send + 'static)
+    where
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+    {
+        let cached = self.value.clone();
+        let refresh_guard = RefreshGuard(self.refreshing.clone());
+
+        tokio::spawn(async move {
+            let _refresh_guard = refresh_guard;
+            match fetch_new_value().await.ok() {
+                Some(new_value) => cached.write().await.with_value(new_value),
+                None => warn!("failed to refresh cached value, serving stale value instead"),
+            }
+        });
+    }
+
+    fn record(&self, result: CacheResult) {
+        metrics().record_cache_request(self.name, result);
+    }
+}
+
+/// Allows a new background refresh once the current one is done,
+/// even if it fails or panics.
+struct RefreshGuard(Arc<AtomicBool>);
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
+
+struct CachedMapEntry<V> {
+    value: V,
+    instant: Instant,
+    last_used: Instant,
+}
+
+struct CachedMapState<K, V> {
+    entries: HashMap<K, CachedMapEntry<V>>,
+    /// Locks held while fetching the value of a key, so that concurrent misses wait for it.
+    in_flight: HashMap<K, Arc<tokio::sync::Mutex<()>>>,
+}
+
+/// Helper structure that caches values by key, each one for the given amount of time.
+/// At most `capacity` entries are kept, evicting the least recently used one when full.
+/// Concurrent misses for the same key only fetch the value once.
+///
+/// The name identifies the cache in metrics.
+#[derive(Clone)]
+pub struct CachedMap<K, V> {
+    name: &'static str,
+    state: Arc<Mutex<CachedMapState<K, V>>>,
+    duration: Duration,
+    capacity: usize,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> CachedMap<K, V> {
+    pub fn new(name: &'static str, duration: Duration, capacity: usize) -> Self {
+        Self {
+            name,
+            state: Arc::new(Mutex::new(CachedMapState {
+                entries: HashMap::new(),
+                in_flight: HashMap::new(),
+            })),
+            duration,
+            capacity,
+        }
+    }
+
+    pub async fn get<E, Fut>(&self, key: K, fetch_new_value: impl FnOnce() -> Fut) -> Result<V, E>
+    where
+        Fut: Future<Output = Result<V, E>>,
+    {
+        let fetch_lock = {
+            let mut state = self.lock_state();
+            if let Some(value) = self.get_value(&mut state, &key) {
+                metrics().record_cache_request(self.name, CacheResult::Hit);
+                return Ok(value);
+            }
+
+            state.in_flight.entry(key.clone()).or_default().clone()
+        };
+
+        // cache miss, wait for any other fetch of the same key
+        let _fetch_guard = fetch_lock.lock().await;
+
+        // re-check if value is valid, since it could have been fetched while waiting
+        if let Some(value) = self.get_value(&mut self.lock_state(), &key) {
+            metrics().record_cache_request(self.name, CacheResult::Hit);
+            return Ok(value);
+        }
+
+        metrics().record_cache_request(self.name, CacheResult::Miss);
+        let _in_flight_guard = InFlightGuard {
+            cache: self,
+            key: key.clone(),
+            lock: &fetch_lock,
+        };
+        let new_value = fetch_new_value().await?;
+
+        let mut state = self.lock_state();
+        if !state.entries.contains_key(&key) && state.entries.len() >= self.capacity {
+            let least_recently_used = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(least_recently_used) = least_recently_used {
+                state.entries.remove(&least_recently_used);
+            }
+        }
+
+        let now = Instant::now();
+        state.entries.insert(
+            key,
+            CachedMapEntry {
+                value: new_value.clone(),
+                instant: now,
+                last_used: now,
+            },
+        );
+
+        Ok(new_value)
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, CachedMapState<K, V>> {
+        self.state.lock().expect("cache lock poisoned")
+    }
+
+    fn get_value(&self, state: &mut CachedMapState<K, V>, key: &K) -> Option<V> {
+        let entry = state.entries.get_mut(key)?;
+
+        if entry.instant.elapsed() >= self.duration {
+            state.entries.remove(key);
+            return None;
+        }
+
+        entry.last_used = Instant::now();
+        Some(entry.value.clone())
+    }
+}
+
+/// Removes the in-flight lock of a key once its value has been fetched,
+/// or if fetching it fails or is cancelled, so that later misses fetch it again.
+struct InFlightGuard<'a, K: Clone + Eq + Hash, V: Clone> {
+    cache: &'a CachedMap<K, V>,
+    key: K,
+    lock: &'a Arc<tokio::sync::Mutex<()>>,
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Drop for InFlightGuard<'_, K, V> {
+    fn drop(&mut self) {
+        let mut state = self.cache.lock_state();
+        // the lock might have already been replaced by a newer fetch
+        if state
+            .in_flight
+            .get(&self.key)
+            .is_some_and(|lock| Arc::ptr_eq(lock, self.lock))
+        {
+            state.in_flight.remove(&self.key);
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::{thread::sleep, time::Duration};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
 
-    use super::Cached;
+    use tokio::time::advance;
+
+    use super::{Cached, CachedMap};
 
     #[derive(Debug, PartialEq, Eq)]
     struct NoErr;
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn cache_hit() {
-        let cached: Cached<u32> = Cached::new(Duration::from_secs(2));
+        let cached: Cached<u32> = Cached::new("test", Duration::from_secs(2));
 
         assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(3) }).await, Ok(3));
         assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(5) }).await, Ok(3));
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn cache_miss() {
-        let cached: Cached<u32> = Cached::new(Duration::from_secs(2));
+        let cached: Cached<u32> = Cached::new("test", Duration::from_secs(2));
 
         assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(3) }).await, Ok(3));
-        sleep(Duration::from_secs(2));
+        advance(Duration::from_secs(2)).await;
         assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(5) }).await, Ok(5));
     }
 
-    #[tokio::test]
+    #[tokio::test(start_paused = true)]
     async fn cache_error() {
-        let cached: Cached<u32> = Cached::new(Duration::from_secs(2));
+        let cached: Cached<u32> = Cached::new("test", Duration::from_secs(2));
 
         assert_eq!(
             cached.get(|| async { Err::<u32, NoErr>(NoErr) }).await,
             Err(NoErr)
         );
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_stale_while_revalidate() {
+        let cached: Cached<u32> =
+            Cached::with_max_staleness("test", Duration::from_millis(100), Duration::from_secs(10));
+
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(3) }).await, Ok(3));
+        advance(Duration::from_millis(150)).await;
+
+        // the stale value is served while the new one is fetched in the background
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(5) }).await, Ok(3));
+        advance(Duration::from_millis(50)).await;
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(7) }).await, Ok(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_stale_on_error() {
+        let cached: Cached<u32> =
+            Cached::with_max_staleness("test", Duration::from_millis(100), Duration::from_secs(10));
+
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(3) }).await, Ok(3));
+        advance(Duration::from_millis(150)).await;
+
+        assert_eq!(
+            cached.get(|| async { Err::<u32, NoErr>(NoErr) }).await,
+            Ok(3)
+        );
+        advance(Duration::from_millis(50)).await;
+        assert_eq!(
+            cached.get(|| async { Err::<u32, NoErr>(NoErr) }).await,
+            Ok(3)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_max_staleness_exceeded() {
+        let cached: Cached<u32> = Cached::with_max_staleness(
+            "test",
+            Duration::from_millis(100),
+            Duration::from_millis(100),
+        );
+
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(3) }).await, Ok(3));
+        advance(Duration::from_millis(250)).await;
+
+        assert_eq!(
+            cached.get(|| async { Err::<u32, NoErr>(NoErr) }).await,
+            Err(NoErr)
+        );
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(5) }).await, Ok(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_single_background_refresh() {
+        let cached: Cached<u32> =
+            Cached::with_max_staleness("test", Duration::from_millis(100), Duration::from_secs(10));
+        let fetch_count = Arc::new(AtomicUsize::new(0));
+
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(3) }).await, Ok(3));
+        advance(Duration::from_millis(150)).await;
+
+        for _ in 0..5 {
+            let fetch_count = fetch_count.clone();
+            let value = cached
+                .get(move || async move {
+                    fetch_count.fetch_add(1, Ordering::SeqCst);
+                    Ok::<u32, NoErr>(5)
+                })
+                .await;
+            assert_eq!(value, Ok(3));
+        }
+        advance(Duration::from_millis(50)).await;
+
+        assert_eq!(fetch_count.load(Ordering::SeqCst), 1);
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(7) }).await, Ok(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_refresh_after_panic() {
+        let cached: Cached<u32> =
+            Cached::with_max_staleness("test", Duration::from_millis(100), Duration::from_secs(10));
+
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(3) }).await, Ok(3));
+        advance(Duration::from_millis(150)).await;
+
+        assert_eq!(
+            cached
+                .get(|| async { panic!("refresh failed") as Result<u32, NoErr> })
+                .await,
+            Ok(3)
+        );
+        advance(Duration::from_millis(50)).await;
+
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(5) }).await, Ok(3));
+        advance(Duration::from_millis(50)).await;
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(7) }).await, Ok(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_single_flight() {
+        let cached: Cached<u32> = Cached::new("test", Duration::from_secs(2));
+        let fetch_count = Arc::new(AtomicUsize::new(0));
+
+        let results = futures::future::join_all((0..5).map(|_| {
+            let fetch_count = fetch_count.clone();
+            cached.get(move || async move {
+                fetch_count.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok::<u32, NoErr>(3)
+            })
+        }))
+        .await;
+
+        assert!(results.into_iter().all(|result| result == Ok(3)));
+        assert_eq!(fetch_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cache_fetch_does_not_block_setting_value() {
+        let cached: Cached<u32> = Cached::new("test", Duration::from_secs(2));
+
+        let fetch = cached.get(futures::future::pending::<Result<u32, NoErr>>);
+        assert!(tokio::time::timeout(Duration::from_millis(50), async {
+            tokio::join!(fetch, cached.set(3)).0
+        })
+        .await
+        .is_err());
+
+        assert_eq!(cached.get(|| async { Ok::<u32, NoErr>(5) }).await, Ok(3));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cached_map_hit() {
+        let cached: CachedMap<&str, u32> = CachedMap::new("test", Duration::from_secs(2), 10);
+
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(3) }).await,
+            Ok(3)
+        );
+        assert_eq!(
+            cached.get("b", || async { Ok::<u32, NoErr>(4) }).await,
+            Ok(4)
+        );
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(5) }).await,
+            Ok(3)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cached_map_miss() {
+        let cached: CachedMap<&str, u32> = CachedMap::new("test", Duration::from_millis(100), 10);
+
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(3) }).await,
+            Ok(3)
+        );
+        advance(Duration::from_millis(150)).await;
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(5) }).await,
+            Ok(5)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cached_map_error() {
+        let cached: CachedMap<&str, u32> = CachedMap::new("test", Duration::from_secs(2), 10);
+
+        assert_eq!(
+            cached.get("a", || async { Err::<u32, NoErr>(NoErr) }).await,
+            Err(NoErr)
+        );
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(3) }).await,
+            Ok(3)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cached_map_evicts_least_recently_used() {
+        let cached: CachedMap<&str, u32> = CachedMap::new("test", Duration::from_secs(2), 2);
+
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(1) }).await,
+            Ok(1)
+        );
+        assert_eq!(
+            cached.get("b", || async { Ok::<u32, NoErr>(2) }).await,
+            Ok(2)
+        );
+        advance(Duration::from_millis(10)).await;
+        // use "a", so that "b" becomes the least recently used entry
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(3) }).await,
+            Ok(1)
+        );
+        assert_eq!(
+            cached.get("c", || async { Ok::<u32, NoErr>(4) }).await,
+            Ok(4)
+        );
+
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(5) }).await,
+            Ok(1)
+        );
+        assert_eq!(
+            cached.get("b", || async { Ok::<u32, NoErr>(6) }).await,
+            Ok(6)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cached_map_single_flight() {
+        let cached: CachedMap<&str, u32> = CachedMap::new("test", Duration::from_secs(2), 10);
+        let fetch_count = Arc::new(AtomicUsize::new(0));
+
+        let results = futures::future::join_all((0..5).map(|_| {
+            let fetch_count = fetch_count.clone();
+            cached.get("a", move || async move {
+                fetch_count.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok::<u32, NoErr>(3)
+            })
+        }))
+        .await;
+
+        assert!(results.into_iter().all(|result| result == Ok(3)));
+        assert_eq!(fetch_count.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn cached_map_cancelled_fetch() {
+        let cached: CachedMap<&str, u32> = CachedMap::new("test", Duration::from_secs(2), 10);
+
+        let fetch = cached.get("a", futures::future::pending::<Result<u32, NoErr>>);
+        assert!(tokio::time::timeout(Duration::from_millis(50), fetch)
+            .await
+            .is_err());
+
+        assert!(cached.lock_state().in_flight.is_empty());
+        assert_eq!(
+            cached.get("a", || async { Ok::<u32, NoErr>(3) }).await,
+            Ok(3)
+        );
+    }
 }
@@ -0,0 +1,146 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+struct CircuitState {
+    consecutive_failures: u32,
+    /// When the circuit was opened, if it is open.
+    opened_at: Option<Instant>,
+    /// When the trial request, made after the circuit has been open for a while, started.
+    trial_started_at: Option<Instant>,
+}
+
+/// Helper structure that stops requests to an unhealthy service, so that they fail fast
+/// instead of waiting for a timeout.
+///
+/// After `failure_threshold` consecutive failures, the circuit opens and no requests are allowed
+/// for `open_duration`. Then, a single trial request is allowed: if it succeeds, the circuit
+/// closes again, otherwise it stays open for another `open_duration`.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    state: Arc<Mutex<CircuitState>>,
+    failure_threshold: u32,
+    open_duration: Duration,
+}
+
+impl CircuitBreaker {
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CircuitState {
+                consecutive_failures: 0,
+                opened_at: None,
+                trial_started_at: None,
+            })),
+            failure_threshold,
+            open_duration,
+        }
+    }
+
+    /// Check whether a request can be made, which must then be followed by
+    /// a call to [`CircuitBreaker::record_success`] or [`CircuitBreaker::record_failure`].
+    pub fn allow_request(&self) -> bool {
+        let mut state = self.lock_state();
+
+        let Some(opened_at) = state.opened_at else {
+            return true;
+        };
+        if opened_at.elapsed() < self.open_duration {
+            return false;
+        }
+
+        // only allow one trial request at a time, unless the previous one never finished
+        match state.trial_started_at {
+            Some(started_at) if started_at.elapsed() < self.open_duration => false,
+            _ => {
+                state.trial_started_at = Some(Instant::now());
+                true
+            }
+        }
+    }
+
+    pub fn record_success(&self) {
+        let mut state = self.lock_state();
+        state.consecutive_failures = 0;
+        state.opened_at = None;
+        state.trial_started_at = None;
+    }
+
+    /// Record a failed request, returning whether this opened the circuit.
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.lock_state();
+        state.consecutive_failures += 1;
+
+        let was_trial = state.trial_started_at.take().is_some();
+        if was_trial || state.consecutive_failures == self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+            return true;
+        }
+
+        false
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.lock_state().opened_at.is_some()
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, CircuitState> {
+        self.state.lock().expect("circuit breaker lock poisoned")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{thread::sleep, time::Duration};
+
+    use super::CircuitBreaker;
+
+    #[test]
+    fn circuit_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(3, Duration::from_secs(10));
+
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        breaker.record_success();
+        assert!(!breaker.record_failure());
+        assert!(!breaker.record_failure());
+        assert!(breaker.allow_request());
+
+        assert!(breaker.record_failure());
+        assert!(breaker.is_open());
+        assert!(!breaker.allow_request());
+    }
+
+    #[test]
+    fn circuit_closes_after_successful_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(100));
+
+        breaker.record_failure();
+        assert!(!breaker.allow_request());
+        sleep(Duration::from_millis(100));
+
+        // only one trial request is allowed
+        assert!(breaker.allow_request());
+        assert!(!breaker.allow_request());
+
+        breaker.record_success();
+        assert!(!breaker.is_open());
+        assert!(breaker.allow_request());
+        assert!(breaker.allow_request());
+    }
+
+    #[test]
+    fn circuit_reopens_after_failed_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(100));
+
+        breaker.record_failure();
+        sleep(Duration::from_millis(100));
+
+        assert!(breaker.allow_request());
+        assert!(breaker.record_failure());
+        assert!(!breaker.allow_request());
+
+        sleep(Duration::from_millis(100));
+        assert!(breaker.allow_request());
+    }
+}
@@ -0,0 +1,459 @@
+use std::{collections::BTreeMap, fs::File, io::Write, path::PathBuf};
+
+use chrono::Duration;
+use clap::{Parser, Subcommand, ValueEnum};
+use entity::{
+    admin::{self, Entity as Admin},
+    election::{self, Entity as Election},
+    election_vote::{self, Entity as ElectionVote},
+    vote_log::{self, Entity as VoteLog},
+};
+use migration::{Alias, Migrator, MigratorTrait};
+use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, QuerySelect, TransactionTrait};
+use serde::Deserialize;
+
+use crate::{
+    audit,
+    dtos::{
+        AddAdminDto, AdminRoleDto, BulkAddUserDegreeOverrideDto, BulkCreateElectionsDegreesDto,
+        BulkCreateElectionsDto, DateRangeDto,
+    },
+    election_utils,
+    errors::AppError,
+    routes,
+    services::fenix::FenixService,
+    AppState,
+};
+
+/// Serves the web app, unless given a command.
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Serve the web app, after applying any pending migrations.
+    Serve,
+    /// Apply or roll back database migrations.
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    #[command(flatten)]
+    Manage(ManageCommand),
+}
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Apply all pending migrations.
+    Up,
+    /// Roll back the last applied migrations.
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// List all migrations, and whether they have been applied.
+    Status,
+}
+
+/// Commands that need the same state as the web app.
+#[derive(Subcommand)]
+pub enum ManageCommand {
+    /// Manage admins, without anyone having to log in to the web app.
+    #[command(subcommand)]
+    Admin(AdminCommand),
+    /// Write the results of all elections that have ended to the standard output.
+    ExportResults {
+        /// Academic year, such as 2023/2024, defaulting to the active one.
+        #[arg(long)]
+        year: Option<String>,
+        #[arg(long, value_enum, default_value_t = ResultsFormat::Csv)]
+        format: ResultsFormat,
+    },
+    /// Make users attend degrees in the active academic year, from a CSV file with
+    /// username, degree (id or acronym) and curricular_year columns.
+    ImportOverrides { csv: PathBuf },
+    /// Check that the audit log hasn't been tampered with, and that no election has more
+    /// votes than voters.
+    CheckIntegrity,
+    /// Create an election for every degree, with its candidacy period starting now,
+    /// in order to try out the web app. Never run this in production.
+    SeedDemo,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ResultsFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum AdminCommand {
+    /// List all admins, along with their roles.
+    List,
+    /// Make a user an admin.
+    Add {
+        username: String,
+        /// One of super-admin, degree-type-coordinator, degree-coordinator or auditor.
+        #[arg(long, default_value = "super-admin", value_parser = parse_role)]
+        role: AdminRoleDto,
+        /// Degree id, for degree coordinators, or degree type, for degree type coordinators.
+        #[arg(long)]
+        scope: Option<String>,
+    },
+    /// Remove an admin right away, without another admin having to approve it.
+    Remove { username: String },
+}
+
+#[derive(Deserialize)]
+struct OverrideRow {
+    username: String,
+    degree: String,
+    curricular_year: u8,
+}
+
+fn parse_role(role: &str) -> Result<AdminRoleDto, String> {
+    role.to_uppercase()
+        .replace('-', "_")
+        .parse()
+        .map_err(|_| format!("unknown role '{}'", role))
+}
+
+/// Apply or roll back migrations, writing their status to the given output.
+pub async fn run_migrate_command(
+    conn: &DatabaseConnection,
+    command: MigrateCommand,
+    out: &mut impl Write,
+) -> Result<(), AppError> {
+    match command {
+        MigrateCommand::Up => Migrator::up(conn, None).await?,
+        MigrateCommand::Down { steps } => Migrator::down(conn, Some(steps)).await?,
+        MigrateCommand::Status => {}
+    }
+
+    for migration in Migrator::get_migration_with_status(conn).await? {
+        writeln!(out, "{}\t{}", migration.status(), migration.name())?;
+    }
+
+    Ok(())
+}
+
+/// Run a command, writing its results to the given output.
+/// Returns whether the command succeeded, which is only not the case when
+/// an integrity check finds issues.
+pub async fn run_command(
+    state: &AppState,
+    command: ManageCommand,
+    out: &mut impl Write,
+) -> Result<bool, AppError> {
+    match command {
+        ManageCommand::Admin(command) => {
+            run_admin_command(&state.conn, &state.fenix_service, command, out).await?
+        }
+        ManageCommand::ExportResults { year, format } => {
+            let academic_year = match year {
+                Some(year) => year,
+                None => state.fenix_service.get_active_year().await?,
+            };
+            let (conn, fenix_service, now) = (&state.conn, &state.fenix_service, state.clock.now());
+            let results = match format {
+                ResultsFormat::Csv => {
+                    election_utils::get_all_results_as_csv(
+                        conn,
+                        fenix_service,
+                        &academic_year,
+                        now,
+                        |_| true,
+                    )
+                    .await?
+                }
+                ResultsFormat::Json => {
+                    election_utils::get_all_results_as_json(
+                        conn,
+                        fenix_service,
+                        &academic_year,
+                        now,
+                        |_| true,
+                    )
+                    .await?
+                }
+            };
+
+            write!(out, "{}", results)?;
+        }
+        ManageCommand::ImportOverrides { csv } => {
+            import_overrides(state, File::open(csv)?, out).await?
+        }
+        ManageCommand::CheckIntegrity => return check_integrity(&state.conn, out).await,
+        ManageCommand::SeedDemo => seed_demo(state, out).await?,
+    }
+
+    Ok(true)
+}
+
+/// Run an admin command, writing its results to the given output.
+/// Changes are recorded in the audit log as made by the system.
+pub async fn run_admin_command(
+    conn: &DatabaseConnection,
+    fenix_service: &FenixService,
+    command: AdminCommand,
+    out: &mut impl Write,
+) -> Result<(), AppError> {
+    match command {
+        AdminCommand::List => {
+            let admins = Admin::find()
+                .order_by_asc(admin::Column::DateAdded)
+                .all(conn)
+                .await?;
+
+            for admin in admins {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}",
+                    admin.username,
+                    admin.role,
+                    admin.scope.as_deref().unwrap_or("-"),
+                    admin.date_added.and_utc().to_rfc3339(),
+                )?;
+            }
+        }
+        AdminCommand::Add {
+            username,
+            role,
+            scope,
+        } => {
+            let admin = routes::admin::create_admin(
+                conn,
+                fenix_service,
+                audit::SYSTEM_ACTOR,
+                AddAdminDto {
+                    username,
+                    role,
+                    scope,
+                },
+            )
+            .await?;
+
+            writeln!(out, "added {} as {}", admin.username, admin.role)?;
+        }
+        AdminCommand::Remove { username } => {
+            let txn = conn.begin().await?;
+            routes::admin::delete_admin(&txn, audit::SYSTEM_ACTOR, &username).await?;
+            txn.commit().await?;
+
+            writeln!(out, "removed {}", username)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Import overrides from CSV, grouped by degree and curricular year.
+/// Every degree is checked before importing anything, and all groups are imported
+/// in a single transaction, so that errors don't leave an import half done.
+pub async fn import_overrides(
+    state: &AppState,
+    csv: impl std::io::Read,
+    out: &mut impl Write,
+) -> Result<(), AppError> {
+    let degrees: Vec<_> = state.fenix_service.get_degrees().await?.collect();
+    let mut groups: BTreeMap<(String, u8), (String, Vec<String>)> = BTreeMap::new();
+
+    for row in csv::Reader::from_reader(csv).deserialize() {
+        let row: OverrideRow = row?;
+        let degree = find_degree(&degrees, row.degree.trim())?;
+
+        groups
+            .entry((degree.id.clone(), row.curricular_year))
+            .or_insert_with(|| (degree.acronym.clone(), Vec::new()))
+            .1
+            .push(row.username.trim().to_string());
+    }
+
+    let active_year = state.fenix_service.get_active_year().await?;
+
+    let txn = state.conn.begin().await?;
+    let mut imported = Vec::new();
+    for ((degree_id, curricular_year), (acronym, usernames)) in groups {
+        imported.push((usernames.len(), acronym, curricular_year));
+        routes::user_degree_overrides::add_user_degree_overrides(
+            &txn,
+            &active_year,
+            audit::SYSTEM_ACTOR,
+            BulkAddUserDegreeOverrideDto {
+                degree_id,
+                curricular_year,
+                usernames,
+            },
+        )
+        .await?;
+    }
+    txn.commit().await?;
+
+    for (count, acronym, curricular_year) in imported {
+        writeln!(
+            out,
+            "imported {} overrides to {}, year {}",
+            count, acronym, curricular_year
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Find a degree by its id or, failing that, by its acronym.
+fn find_degree<'a>(
+    degrees: &'a [crate::dtos::DegreeDto],
+    degree: &str,
+) -> Result<&'a crate::dtos::DegreeDto, AppError> {
+    degrees
+        .iter()
+        .find(|found| found.id == degree)
+        .or_else(|| {
+            degrees
+                .iter()
+                .find(|found| found.acronym.eq_ignore_ascii_case(degree))
+        })
+        .ok_or(AppError::InvalidDegree)
+}
+
+/// Verify the audit log, and that the votes counted for each election's nominations
+/// don't outnumber the users who voted in it (blank votes only count towards the latter).
+pub async fn check_integrity(
+    conn: &DatabaseConnection,
+    out: &mut impl Write,
+) -> Result<bool, AppError> {
+    let mut valid = true;
+
+    let verification = audit::verify(conn).await?;
+    match verification.issue {
+        None => writeln!(
+            out,
+            "audit log: {} entries verified, last hash {}",
+            verification.entries,
+            verification.last_hash.as_deref().unwrap_or("-")
+        )?,
+        Some(issue) => {
+            valid = false;
+            writeln!(out, "audit log: {:?} at entry {}", issue.kind, issue.id)?;
+        }
+    }
+
+    let voters: BTreeMap<i32, i64> = VoteLog::find()
+        .select_only()
+        .column(vote_log::Column::Election)
+        .column_as(vote_log::Column::Voter.count(), "voters")
+        .group_by(vote_log::Column::Election)
+        .into_tuple::<(i32, i64)>()
+        .all(conn)
+        .await?
+        .into_iter()
+        .collect();
+    let votes = ElectionVote::find()
+        .select_only()
+        .column(election_vote::Column::Election)
+        .column_as(
+            election_vote::Column::Count
+                .sum()
+                .cast_as(Alias::new("bigint")),
+            "votes",
+        )
+        .group_by(election_vote::Column::Election)
+        .order_by_asc(election_vote::Column::Election)
+        .into_tuple::<(i32, i64)>()
+        .all(conn)
+        .await?;
+
+    let mut elections = 0;
+    for (election, votes) in votes {
+        let voters = voters.get(&election).copied().unwrap_or(0);
+        if votes > voters {
+            valid = false;
+            writeln!(
+                out,
+                "election {}: {} votes, but only {} voters",
+                election, votes, voters
+            )?;
+        }
+        elections += 1;
+    }
+    writeln!(out, "votes: {} elections checked", elections)?;
+
+    Ok(valid)
+}
+
+/// Seed demo elections, unless the active academic year already has some elections,
+/// since whole-degree elections aren't caught by the unique index of elections.
+async fn seed_demo(state: &AppState, out: &mut impl Write) -> Result<(), AppError> {
+    let academic_year = state.fenix_service.get_active_year().await?;
+    let existing = Election::find()
+        .filter(election::Column::AcademicYear.eq(&academic_year))
+        .count(&state.conn)
+        .await?;
+    if existing > 0 {
+        return Err(AppError::DuplicateElection);
+    }
+
+    let now = state.clock.now();
+    let degrees: Vec<_> = state
+        .fenix_service
+        .get_degrees()
+        .await?
+        .map(|degree| BulkCreateElectionsDegreesDto {
+            degree_id: degree.id,
+            curricular_year: None,
+        })
+        .collect();
+    let count = degrees.len();
+
+    routes::elections::create_elections(
+        &state.conn,
+        &state.fenix_service,
+        audit::SYSTEM_ACTOR,
+        BulkCreateElectionsDto {
+            candidacy_period: Some(DateRangeDto {
+                start: now,
+                end: now + Duration::days(3),
+            }),
+            voting_period: DateRangeDto {
+                start: now + Duration::days(4),
+                end: now + Duration::days(7),
+            },
+            round: 1,
+            degrees,
+        },
+    )
+    .await?;
+
+    writeln!(
+        out,
+        "created {} demo elections for {}",
+        count, academic_year
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_role() {
+        assert_eq!(parse_role("super-admin"), Ok(AdminRoleDto::SuperAdmin));
+        assert_eq!(
+            parse_role("DEGREE_COORDINATOR"),
+            Ok(AdminRoleDto::DegreeCoordinator)
+        );
+        assert_eq!(parse_role("auditor"), Ok(AdminRoleDto::Auditor));
+        assert!(parse_role("owner").is_err());
+    }
+
+    #[test]
+    fn test_cli() {
+        use clap::CommandFactory;
+
+        Cli::command().debug_assert();
+    }
+}
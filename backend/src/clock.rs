@@ -0,0 +1,52 @@
+#[cfg(feature = "time-travel")]
+use std::sync::{Arc, RwLock};
+
+use sea_orm::prelude::DateTimeUtc;
+
+/// Source of the current time for everything related to election periods.
+///
+/// When built with the `time-travel` feature, admins can shift this clock by an offset,
+/// which allows rehearsing whole elections in staging environments.
+/// The offset is kept in memory, so it is reset on restart and not shared between instances.
+#[derive(Clone, Default)]
+pub struct Clock {
+    #[cfg(feature = "time-travel")]
+    offset: Arc<RwLock<chrono::Duration>>,
+}
+
+impl Clock {
+    pub fn now(&self) -> DateTimeUtc {
+        #[cfg(feature = "time-travel")]
+        return chrono::Utc::now() + self.offset();
+
+        #[cfg(not(feature = "time-travel"))]
+        chrono::Utc::now()
+    }
+}
+
+#[cfg(feature = "time-travel")]
+impl Clock {
+    pub fn offset(&self) -> chrono::Duration {
+        *self.offset.read().expect("clock offset lock poisoned")
+    }
+
+    pub fn set_offset(&self, offset: chrono::Duration) {
+        *self.offset.write().expect("clock offset lock poisoned") = offset;
+    }
+}
+
+#[cfg(all(test, feature = "time-travel"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_is_shared_between_clones() {
+        let clock = Clock::default();
+        let clone = clock.clone();
+
+        clock.set_offset(chrono::Duration::days(2));
+
+        assert_eq!(clone.offset(), chrono::Duration::days(2));
+        assert!(clone.now() > chrono::Utc::now() + chrono::Duration::days(1));
+    }
+}
@@ -1,5 +1,7 @@
+use chrono::Duration;
 use hmac::{Hmac, Mac};
-use sha2::Sha256;
+use sea_orm::prelude::DateTimeUtc;
+use sha2::{Digest, Sha256};
 
 use crate::{
     dtos::SignedPersonSearchResultDto, errors::AppError, services::fenix::PersonSearchResult,
@@ -7,59 +9,175 @@ use crate::{
 
 type HmacSha256 = Hmac<Sha256>;
 
-fn serialize_person_search_result(election_id: i32, username: &str, display_name: &str) -> Vec<u8> {
+/// Separates the key id from the MAC in a signature.
+const SIGNATURE_KEY_ID_SEPARATOR: char = ':';
+
+/// For how long a signed person search result can be used after the search was made.
+const SIGNATURE_VALIDITY: Duration = Duration::hours(1);
+
+/// A key used to sign person search results, identified by an id
+/// that is embedded in every signature made with it.
+#[derive(Clone)]
+pub struct SigningKey {
+    pub id: String,
+    pub key: Vec<u8>,
+}
+
+impl SigningKey {
+    /// Create a signing key whose id is derived from the key itself.
+    pub fn from_key(key: Vec<u8>) -> Self {
+        let id = hex::encode(&Sha256::digest(&key)[..4]);
+        Self { id, key }
+    }
+}
+
+/// The key that should be used to sign new search results, alongside
+/// the previous keys that are still accepted when validating signatures.
+#[derive(Clone)]
+pub struct SigningKeyring {
+    pub current: SigningKey,
+    pub previous: Vec<SigningKey>,
+}
+
+impl SigningKeyring {
+    /// Whether signatures made with the key with the given id can be validated.
+    pub fn has_key(&self, key_id: &str) -> bool {
+        self.find_key(key_id).is_some()
+    }
+
+    fn find_key(&self, key_id: &str) -> Option<&SigningKey> {
+        std::iter::once(&self.current)
+            .chain(self.previous.iter())
+            .find(|key| key.id == key_id)
+    }
+}
+
+/// Get the id of the key that made the given signature.
+pub fn get_signature_key_id(signature: &str) -> Option<&str> {
+    signature
+        .split_once(SIGNATURE_KEY_ID_SEPARATOR)
+        .map(|(key_id, _)| key_id)
+}
+
+fn serialize_person_search_result(
+    election_id: i32,
+    username: &str,
+    display_name: &str,
+    requested_by: &str,
+    issued_at: &DateTimeUtc,
+) -> Vec<u8> {
     [
         election_id.to_be_bytes().as_slice(),
         b"|",
         username.replace('|', "").as_bytes(),
         b"|",
         display_name.replace('|', "").as_bytes(),
+        b"|",
+        requested_by.replace('|', "").as_bytes(),
+        b"|",
+        issued_at.timestamp().to_be_bytes().as_slice(),
     ]
     .concat()
 }
 
+/// Sign a person search result, binding it to the given election and to the user
+/// that requested the search, so that it can later be used to nominate that person.
 pub fn sign_person_search_result(
     election_id: i32,
     search_result: PersonSearchResult,
-    signing_key: &[u8],
+    requested_by: &str,
+    issued_at: DateTimeUtc,
+    keyring: &SigningKeyring,
 ) -> SignedPersonSearchResultDto {
-    let mut mac = HmacSha256::new_from_slice(signing_key).expect("invalid key length");
-    let payload =
-        serialize_person_search_result(election_id, &search_result.username, &search_result.name);
+    // the signature only covers whole seconds
+    let issued_at = DateTimeUtc::from_timestamp(issued_at.timestamp(), 0).unwrap_or(issued_at);
+
+    let signing_key = &keyring.current;
+    let mut mac = HmacSha256::new_from_slice(&signing_key.key).expect("invalid key length");
+    let payload = serialize_person_search_result(
+        election_id,
+        &search_result.username,
+        &search_result.name,
+        requested_by,
+        &issued_at,
+    );
     mac.update(&payload);
 
     let result = mac.finalize();
     SignedPersonSearchResultDto {
         username: search_result.username,
         display_name: search_result.name,
-        signature: hex::encode(result.into_bytes()),
+        requested_by: requested_by.to_string(),
+        issued_at,
+        signature: format!(
+            "{}{}{}",
+            signing_key.id,
+            SIGNATURE_KEY_ID_SEPARATOR,
+            hex::encode(result.into_bytes())
+        ),
     }
 }
 
+/// Validate the signature of a person search result, using the key
+/// identified in the signature, as long as it is still in the keyring.
+///
+/// The search result must have been requested by the given user,
+/// and must not be older than [`SIGNATURE_VALIDITY`].
 pub fn validate_person_search_result(
     election_id: i32,
     signed_search_result: &SignedPersonSearchResultDto,
-    signing_key: &[u8],
+    requester: &str,
+    now: DateTimeUtc,
+    keyring: &SigningKeyring,
 ) -> Result<(), AppError> {
-    let mut mac = HmacSha256::new_from_slice(signing_key).expect("invalid key length");
+    let (key_id, signature) = signed_search_result
+        .signature
+        .split_once(SIGNATURE_KEY_ID_SEPARATOR)
+        .ok_or(AppError::InvalidPersonSignature)?;
+    let signing_key = keyring
+        .find_key(key_id)
+        .ok_or(AppError::InvalidPersonSignature)?;
+
+    let mut mac = HmacSha256::new_from_slice(&signing_key.key).expect("invalid key length");
     let payload = serialize_person_search_result(
         election_id,
         &signed_search_result.username,
         &signed_search_result.display_name,
+        &signed_search_result.requested_by,
+        &signed_search_result.issued_at,
     );
     mac.update(&payload);
 
-    let signature = hex::decode(&signed_search_result.signature)
-        .map_err(|_| AppError::InvalidPersonSignature)?;
+    let signature = hex::decode(signature).map_err(|_| AppError::InvalidPersonSignature)?;
     mac.verify_slice(&signature)
-        .map_err(|_| AppError::InvalidPersonSignature)
+        .map_err(|_| AppError::InvalidPersonSignature)?;
+
+    if signed_search_result.requested_by != requester {
+        return Err(AppError::ForeignPersonSignature);
+    }
+    if now - signed_search_result.issued_at > SIGNATURE_VALIDITY {
+        return Err(AppError::ExpiredPersonSignature);
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const KEY: &[u8] = b"testing key";
+    const REQUESTER: &str = "ist1111111";
+
+    fn issued_at() -> DateTimeUtc {
+        DateTimeUtc::from_timestamp(1_700_000_000, 0).unwrap()
+    }
+
+    fn keyring() -> SigningKeyring {
+        SigningKeyring {
+            current: SigningKey::from_key(b"testing key".to_vec()),
+            previous: vec![SigningKey::from_key(b"previous testing key".to_vec())],
+        }
+    }
 
     #[test]
     fn validate_signature() {
@@ -67,9 +185,12 @@ mod tests {
             username: "ist1123456".to_string(),
             name: "John Doe".to_string(),
         };
-        let signed = sign_person_search_result(1, search_result, KEY);
+        let signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
 
-        assert!(validate_person_search_result(1, &signed, KEY).is_ok());
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, issued_at(), &keyring()).is_ok()
+        );
     }
 
     #[test]
@@ -78,11 +199,14 @@ mod tests {
             username: "ist1123456".to_string(),
             name: "John Doe".to_string(),
         };
-        let mut signed = sign_person_search_result(1, search_result, KEY);
+        let mut signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
 
         signed.username = "ist1654321".to_string();
 
-        assert!(validate_person_search_result(1, &signed, KEY).is_err());
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, issued_at(), &keyring()).is_err()
+        );
     }
 
     #[test]
@@ -91,11 +215,14 @@ mod tests {
             username: "ist1123456".to_string(),
             name: "John Doe".to_string(),
         };
-        let mut signed = sign_person_search_result(1, search_result, KEY);
+        let mut signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
 
         signed.display_name = "Jane Doe".to_string();
 
-        assert!(validate_person_search_result(1, &signed, KEY).is_err());
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, issued_at(), &keyring()).is_err()
+        );
     }
 
     #[test]
@@ -104,9 +231,12 @@ mod tests {
             username: "ist1123456".to_string(),
             name: "John Doe".to_string(),
         };
-        let signed = sign_person_search_result(1, search_result, KEY);
+        let signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
 
-        assert!(validate_person_search_result(2, &signed, KEY).is_err());
+        assert!(
+            validate_person_search_result(2, &signed, REQUESTER, issued_at(), &keyring()).is_err()
+        );
     }
 
     #[test]
@@ -115,10 +245,139 @@ mod tests {
             username: "ist1123456".to_string(),
             name: "John Doe".to_string(),
         };
-        let mut signed = sign_person_search_result(1, search_result, KEY);
+        let mut signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
 
         signed.signature = signed.signature.replace('0', "1");
 
-        assert!(validate_person_search_result(1, &signed, KEY).is_err());
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, issued_at(), &keyring()).is_err()
+        );
+    }
+
+    #[test]
+    fn validate_signature_with_previous_key() {
+        let search_result = PersonSearchResult {
+            username: "ist1123456".to_string(),
+            name: "John Doe".to_string(),
+        };
+        let old_keyring = SigningKeyring {
+            current: SigningKey::from_key(b"previous testing key".to_vec()),
+            previous: vec![],
+        };
+        let signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &old_keyring);
+
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, issued_at(), &keyring()).is_ok()
+        );
+    }
+
+    #[test]
+    fn validate_signature_with_retired_key() {
+        let search_result = PersonSearchResult {
+            username: "ist1123456".to_string(),
+            name: "John Doe".to_string(),
+        };
+        let retired_keyring = SigningKeyring {
+            current: SigningKey::from_key(b"retired testing key".to_vec()),
+            previous: vec![],
+        };
+        let signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &retired_keyring);
+
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, issued_at(), &keyring()).is_err()
+        );
+    }
+
+    #[test]
+    fn tamper_with_key_id() {
+        let search_result = PersonSearchResult {
+            username: "ist1123456".to_string(),
+            name: "John Doe".to_string(),
+        };
+        let mut signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
+
+        let previous_key_id = &keyring().previous[0].id;
+        let (_, mac) = signed.signature.split_once(':').unwrap();
+        signed.signature = format!("{}:{}", previous_key_id, mac);
+
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, issued_at(), &keyring()).is_err()
+        );
+    }
+
+    #[test]
+    fn tamper_with_requested_by() {
+        let search_result = PersonSearchResult {
+            username: "ist1123456".to_string(),
+            name: "John Doe".to_string(),
+        };
+        let mut signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
+
+        signed.requested_by = "ist1222222".to_string();
+
+        assert!(matches!(
+            validate_person_search_result(1, &signed, "ist1222222", issued_at(), &keyring()),
+            Err(AppError::InvalidPersonSignature)
+        ));
+    }
+
+    #[test]
+    fn tamper_with_issued_at() {
+        let search_result = PersonSearchResult {
+            username: "ist1123456".to_string(),
+            name: "John Doe".to_string(),
+        };
+        let mut signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
+
+        signed.issued_at = issued_at() + Duration::hours(2);
+        let now = issued_at() + Duration::hours(2);
+
+        assert!(matches!(
+            validate_person_search_result(1, &signed, REQUESTER, now, &keyring()),
+            Err(AppError::InvalidPersonSignature)
+        ));
+    }
+
+    #[test]
+    fn foreign_signature() {
+        let search_result = PersonSearchResult {
+            username: "ist1123456".to_string(),
+            name: "John Doe".to_string(),
+        };
+        let signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
+
+        assert!(matches!(
+            validate_person_search_result(1, &signed, "ist1222222", issued_at(), &keyring()),
+            Err(AppError::ForeignPersonSignature)
+        ));
+    }
+
+    #[test]
+    fn expired_signature() {
+        let search_result = PersonSearchResult {
+            username: "ist1123456".to_string(),
+            name: "John Doe".to_string(),
+        };
+        let signed =
+            sign_person_search_result(1, search_result, REQUESTER, issued_at(), &keyring());
+
+        let almost_expired = issued_at() + SIGNATURE_VALIDITY;
+        let expired = almost_expired + Duration::seconds(1);
+
+        assert!(
+            validate_person_search_result(1, &signed, REQUESTER, almost_expired, &keyring())
+                .is_ok()
+        );
+        assert!(matches!(
+            validate_person_search_result(1, &signed, REQUESTER, expired, &keyring()),
+            Err(AppError::ExpiredPersonSignature)
+        ));
     }
 }
@@ -1,11 +1,14 @@
 use std::collections::HashMap;
 
-use entity::{admin, election, nomination};
+use entity::{
+    admin, api_token, audit_log, election, nomination, observer, pending_action, session,
+};
 use sea_orm::prelude::*;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use typeshare::typeshare;
 
-use crate::{errors::AppError, services::fenix::FenixService};
+use crate::{errors::AppError, services::fenix::FenixService, session_store::to_public_session_id};
 
 #[typeshare]
 #[derive(Serialize)]
@@ -18,7 +21,12 @@ pub struct AppErrorDto {
 #[serde(rename_all = "camelCase")]
 pub struct AppConfigDto {
     pub fenix: FenixConfigDto,
+    pub login_url: String,
+    pub dev_login: bool,
+    /// Whether the first admin has been set up, or can only be added from the management CLI.
     pub is_setup: bool,
+    /// Academic year pinned by an admin, which is used instead of Fénix's active year.
+    pub pinned_academic_year: Option<String>,
 }
 
 #[typeshare]
@@ -30,10 +38,157 @@ pub struct FenixConfigDto {
     pub redirect_url: String,
 }
 
+#[typeshare]
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FenixSnapshotDto {
+    pub active_year: Option<String>,
+    pub active_year_taken_at: Option<DateTimeUtc>,
+    pub degrees_taken_at: Option<DateTimeUtc>,
+}
+
+#[typeshare]
+#[derive(Serialize, Clone, Debug, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum HealthStatusDto {
+    Up,
+    /// Working, but serving data that couldn't be refreshed.
+    Stale,
+    Down,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadinessDto {
+    /// Whether no check is down.
+    pub ready: bool,
+    pub database: DatabaseCheckDto,
+    pub migrations: MigrationsCheckDto,
+    pub fenix_degrees: FenixDataCheckDto,
+    pub fenix_academic_year: FenixDataCheckDto,
+    pub session_store: SessionStoreCheckDto,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseCheckDto {
+    pub status: HealthStatusDto,
+    pub latency_ms: Option<u64>,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MigrationsCheckDto {
+    pub status: HealthStatusDto,
+    pub pending: Vec<String>,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FenixDataCheckDto {
+    pub status: HealthStatusDto,
+    /// When the data being served was fetched from Fénix, if known.
+    pub fetched_at: Option<DateTimeUtc>,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStoreCheckDto {
+    pub status: HealthStatusDto,
+    /// Either "database" or "memory".
+    pub kind: String,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogEntryDto {
+    pub id: i32,
+    pub actor: String,
+    pub action: String,
+    pub target: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+    pub created_at: DateTimeUtc,
+    pub hash: String,
+}
+
+impl AuditLogEntryDto {
+    pub fn from_entity(entity: audit_log::Model) -> Self {
+        let parse = |json: Option<String>| json.and_then(|json| serde_json::from_str(&json).ok());
+
+        AuditLogEntryDto {
+            id: entity.id,
+            actor: entity.actor,
+            action: entity.action,
+            target: entity.target,
+            before: parse(entity.before),
+            after: parse(entity.after),
+            created_at: entity.created_at.and_utc(),
+            hash: entity.hash,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AuditLogQuery {
+    /// Only get entries older than the one with this id.
+    pub before: Option<i32>,
+    pub limit: Option<u64>,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogVerificationDto {
+    pub valid: bool,
+    /// Number of entries that were verified, until an issue was found, if any.
+    pub entries: u32,
+    /// Hash of the last verified entry, which can be compared with a previously
+    /// known hash to detect entries removed from the end of the log.
+    pub last_hash: Option<String>,
+    pub issue: Option<AuditLogIssueDto>,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+pub struct AuditLogIssueDto {
+    pub id: i32,
+    pub kind: AuditLogIssueKindDto,
+}
+
+#[typeshare]
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AuditLogIssueKindDto {
+    /// Entries before this one are missing.
+    Gap,
+    /// This entry doesn't follow the previous one, which has been edited.
+    BrokenChain,
+    /// This entry has been edited.
+    HashMismatch,
+}
+
 #[typeshare]
 #[derive(Deserialize)]
 pub struct LoginDto {
     pub code: String,
+    /// State sent back by the identity provider, if it supports one.
+    pub state: Option<String>,
+}
+
+#[typeshare]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevLoginDto {
+    pub username: String,
+    pub name: Option<String>,
+    pub degree_entries: Vec<DegreeEntryDto>,
 }
 
 #[typeshare]
@@ -51,6 +206,9 @@ pub struct DegreeEntryDto {
 pub struct AuthDto {
     pub user: UserDto,
     pub is_admin: bool,
+    pub admin_role: Option<AdminRoleDto>,
+    /// Until when the user can follow elections as an observer, if they have been granted access.
+    pub observer_until: Option<DateTimeUtc>,
 }
 
 #[typeshare]
@@ -69,6 +227,9 @@ pub struct UserDto {
 pub struct AdminDto {
     username: String,
     date_added: DateTimeUtc,
+    role: AdminRoleDto,
+    /// Degree id or degree type the role is restricted to, for coordinators.
+    scope: Option<String>,
 }
 
 impl AdminDto {
@@ -76,19 +237,290 @@ impl AdminDto {
         Ok(Self {
             username: entity.username,
             date_added: entity.date_added.and_utc(),
+            role: entity.role.parse()?,
+            scope: entity.scope,
         })
     }
 }
 
+#[typeshare]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AdminRoleDto {
+    /// Can do everything, including managing other admins and global settings.
+    #[default]
+    SuperAdmin,
+    /// Can manage elections, nominations and overrides of all degrees of a type,
+    /// such as all Masters, which is identified by any of its localized names.
+    DegreeTypeCoordinator,
+    /// Can manage elections, nominations and overrides of a single degree.
+    DegreeCoordinator,
+    /// Can see everything other admins can, but can't change anything.
+    Auditor,
+}
+
+impl AdminRoleDto {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::SuperAdmin => "SUPER_ADMIN",
+            Self::DegreeTypeCoordinator => "DEGREE_TYPE_COORDINATOR",
+            Self::DegreeCoordinator => "DEGREE_COORDINATOR",
+            Self::Auditor => "AUDITOR",
+        }
+    }
+
+    /// Whether the role is restricted to a degree or degree type.
+    pub fn is_scoped(self) -> bool {
+        matches!(self, Self::DegreeTypeCoordinator | Self::DegreeCoordinator)
+    }
+}
+
+impl std::str::FromStr for AdminRoleDto {
+    type Err = DbErr;
+
+    fn from_str(role: &str) -> Result<Self, Self::Err> {
+        match role {
+            "SUPER_ADMIN" => Ok(Self::SuperAdmin),
+            "DEGREE_TYPE_COORDINATOR" => Ok(Self::DegreeTypeCoordinator),
+            "DEGREE_COORDINATOR" => Ok(Self::DegreeCoordinator),
+            "AUDITOR" => Ok(Self::Auditor),
+            _ => Err(DbErr::Type(format!("Unknown admin role '{}'", role))),
+        }
+    }
+}
+
 #[typeshare]
 #[derive(Deserialize)]
 pub struct AddAdminDto {
     pub username: String,
+    /// Defaults to super admin.
+    #[serde(default)]
+    pub role: AdminRoleDto,
+    /// Degree id, for degree coordinators, or degree type, for degree type coordinators.
+    pub scope: Option<String>,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiTokenDto {
+    id: i32,
+    /// Admin the token acts as, with their current role.
+    username: String,
+    name: String,
+    scopes: Vec<ApiTokenScopeDto>,
+    created_at: DateTimeUtc,
+    expires_at: DateTimeUtc,
+    last_used_at: Option<DateTimeUtc>,
+}
+
+impl ApiTokenDto {
+    pub fn from_entity(entity: api_token::Model) -> Result<Self, AppError> {
+        Ok(Self {
+            id: entity.id,
+            username: entity.username,
+            name: entity.name,
+            scopes: ApiTokenScopeDto::parse_all(&entity.scopes)?,
+            created_at: entity.created_at.and_utc(),
+            expires_at: entity.expires_at.and_utc(),
+            last_used_at: entity.last_used_at.map(|date| date.and_utc()),
+        })
+    }
+}
+
+#[typeshare]
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ApiTokenScopeDto {
+    /// Can make GET requests, such as downloading results.
+    Read,
+    /// Can make any other request, such as importing overrides.
+    Write,
+}
+
+impl ApiTokenScopeDto {
+    /// Separates the scopes of a token, when stored in the database.
+    const SEPARATOR: char = ',';
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Read => "READ",
+            Self::Write => "WRITE",
+        }
+    }
+
+    pub fn join(scopes: &[Self]) -> String {
+        scopes
+            .iter()
+            .map(|scope| scope.as_str())
+            .collect::<Vec<_>>()
+            .join(&Self::SEPARATOR.to_string())
+    }
+
+    pub fn parse_all(scopes: &str) -> Result<Vec<Self>, DbErr> {
+        scopes
+            .split(Self::SEPARATOR)
+            .filter(|scope| !scope.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl std::str::FromStr for ApiTokenScopeDto {
+    type Err = DbErr;
+
+    fn from_str(scope: &str) -> Result<Self, Self::Err> {
+        match scope {
+            "READ" => Ok(Self::Read),
+            "WRITE" => Ok(Self::Write),
+            _ => Err(DbErr::Type(format!("Unknown API token scope '{}'", scope))),
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateApiTokenDto {
+    pub name: String,
+    pub scopes: Vec<ApiTokenScopeDto>,
+    pub expires_at: DateTimeUtc,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreatedApiTokenDto {
+    pub token: ApiTokenDto,
+    /// Sent as a Bearer token to authenticate requests. It is only shown once, since
+    /// only its hash is stored.
+    pub secret: String,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObserverDto {
+    username: String,
+    granted_by: String,
+    granted_at: DateTimeUtc,
+    expires_at: DateTimeUtc,
+}
+
+impl ObserverDto {
+    pub fn from_entity(entity: observer::Model) -> Self {
+        Self {
+            username: entity.username,
+            granted_by: entity.granted_by,
+            granted_at: entity.granted_at.and_utc(),
+            expires_at: entity.expires_at.and_utc(),
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddObserverDto {
+    pub username: String,
+    /// When the observer loses access, which can be changed by granting access again.
+    pub expires_at: DateTimeUtc,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDto {
+    id: String,
+    username: Option<String>,
+    user_agent: Option<String>,
+    login_time: Option<DateTimeUtc>,
+    last_activity: Option<DateTimeUtc>,
+    expires_at: Option<DateTimeUtc>,
+    current: bool,
+}
+
+impl SessionDto {
+    pub fn from_entity(entity: session::Model, current_session_id: &str) -> Self {
+        Self {
+            id: to_public_session_id(&entity.id),
+            current: entity.id == current_session_id,
+            username: entity.username,
+            user_agent: entity.user_agent,
+            login_time: entity.login_time.map(|time| time.and_utc()),
+            last_activity: entity.last_activity.map(|time| time.and_utc()),
+            expires_at: entity.expires_at.map(|time| time.and_utc()),
+        }
+    }
 }
 
 #[typeshare]
 type LocalizedStringDto = HashMap<String, String>;
 
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeTravelDto {
+    pub now: DateTimeUtc,
+    pub offset_seconds: i32,
+}
+
+#[cfg(feature = "time-travel")]
+impl TimeTravelDto {
+    pub fn from_clock(clock: &crate::clock::Clock) -> Self {
+        Self {
+            now: clock.now(),
+            offset_seconds: clock
+                .offset()
+                .num_seconds()
+                .try_into()
+                .expect("clock offset should fit in a 32-bit integer"),
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetTimeTravelDto {
+    pub offset_seconds: i32,
+}
+
+/// Query parameters of admin listings that can show past academic years.
+#[derive(Deserialize)]
+pub struct AcademicYearQuery {
+    pub academic_year: Option<String>,
+}
+
+impl AcademicYearQuery {
+    /// Get the requested academic year, defaulting to the active one.
+    pub async fn or_active_year(self, fenix_service: &FenixService) -> Result<String, AppError> {
+        match self.academic_year {
+            Some(academic_year) => Ok(academic_year),
+            None => fenix_service.get_active_year().await,
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AcademicYearDto {
+    /// Academic year used by elections, overrides and results.
+    pub academic_year: String,
+    /// Active academic year in Fénix, if it could be fetched.
+    pub fenix_academic_year: Option<String>,
+    pub pinned: bool,
+}
+
+#[typeshare]
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinAcademicYearDto {
+    /// Academic year to pin, in the format "2023/2024", or null to follow Fénix again.
+    pub academic_year: Option<String>,
+}
+
 #[typeshare]
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -123,9 +555,9 @@ pub struct ElectionDto {
 }
 
 impl ElectionDto {
-    pub fn from_entity(entity: election::Model) -> Result<Self, AppError> {
+    pub fn from_entity(entity: election::Model, now: DateTimeUtc) -> Result<Self, AppError> {
         Ok(Self {
-            status: ElectionStatusDto::from_election(&entity),
+            status: ElectionStatusDto::from_election(&entity, now),
             id: entity.id,
             academic_year: entity.academic_year,
             curricular_year: entity.curricular_year,
@@ -147,12 +579,13 @@ impl ElectionDto {
     }
     pub async fn from_entity_for_user(
         entity: election::Model,
+        now: DateTimeUtc,
         fenix_service: &FenixService,
         has_nominated: bool,
         has_voted: bool,
     ) -> Result<Self, AppError> {
         let degree_id = entity.degree_id.clone();
-        let mut dto = Self::from_entity(entity)?;
+        let mut dto = Self::from_entity(entity, now)?;
 
         dto.degree = fenix_service.get_degree(&degree_id).await?;
         dto.has_nominated = Some(has_nominated);
@@ -162,12 +595,13 @@ impl ElectionDto {
     }
     pub async fn from_entity_for_admin(
         entity: election::Model,
+        now: DateTimeUtc,
         fenix_service: &FenixService,
         nominations: Vec<NominationDto>,
         total_votes: Option<i32>,
     ) -> Result<Self, AppError> {
         let degree_id = entity.degree_id.clone();
-        let mut dto = Self::from_entity(entity)?;
+        let mut dto = Self::from_entity(entity, now)?;
 
         dto.degree = fenix_service.get_degree(&degree_id).await?;
         dto.total_votes = total_votes;
@@ -190,8 +624,7 @@ pub enum ElectionStatusDto {
 }
 
 impl ElectionStatusDto {
-    fn from_election(entity: &election::Model) -> Self {
-        let now = chrono::Utc::now();
+    fn from_election(entity: &election::Model, now: DateTimeUtc) -> Self {
         if now > entity.voting_period_end.and_utc() {
             return Self::Ended;
         }
@@ -220,6 +653,75 @@ pub struct DegreeElectionsDto {
     pub elections: Vec<ElectionDto>,
 }
 
+/// An election as shown to observers, which never includes the votes of each nomination
+/// before the election ends, so that it can't be used to tell who is winning.
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObserverElectionDto {
+    pub id: i32,
+    pub academic_year: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degree: Option<DegreeDto>,
+    pub curricular_year: Option<i32>,
+    pub candidacy_period: Option<DateRangeDto>,
+    pub voting_period: DateRangeDto,
+    pub round: i32,
+    pub status: ElectionStatusDto,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nominations: Option<Vec<NominationDto>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_votes: Option<i32>,
+}
+
+impl ObserverElectionDto {
+    pub fn from_election_dto(dto: ElectionDto) -> Self {
+        let has_ended = matches!(dto.status, ElectionStatusDto::Ended);
+        let nominations = dto.nominations.map(|nominations| {
+            nominations
+                .into_iter()
+                .map(|nomination| NominationDto {
+                    votes: nomination.votes.filter(|_| has_ended),
+                    ..nomination
+                })
+                .collect()
+        });
+
+        Self {
+            id: dto.id,
+            academic_year: dto.academic_year,
+            degree: dto.degree,
+            curricular_year: dto.curricular_year,
+            candidacy_period: dto.candidacy_period,
+            voting_period: dto.voting_period,
+            round: dto.round,
+            status: dto.status,
+            nominations,
+            total_votes: dto.total_votes,
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Serialize)]
+pub struct ObserverDegreeElectionsDto {
+    pub degree: DegreeDto,
+    pub elections: Vec<ObserverElectionDto>,
+}
+
+impl ObserverDegreeElectionsDto {
+    pub fn from_degree_elections_dto(dto: DegreeElectionsDto) -> Self {
+        Self {
+            degree: dto.degree,
+            elections: dto
+                .elections
+                .into_iter()
+                .map(ObserverElectionDto::from_election_dto)
+                .collect(),
+        }
+    }
+}
+
 #[typeshare]
 #[derive(Deserialize)]
 pub struct SearchPersonDto {
@@ -233,6 +735,8 @@ pub struct SearchPersonDto {
 pub struct SignedPersonSearchResultDto {
     pub username: String,
     pub display_name: String,
+    pub requested_by: String,
+    pub issued_at: DateTimeUtc,
     pub signature: String, // in ascii
 }
 
@@ -245,7 +749,7 @@ pub struct DateRangeDto {
 }
 
 #[typeshare]
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BulkCreateElectionsDegreesDto {
     pub degree_id: String,
@@ -253,7 +757,7 @@ pub struct BulkCreateElectionsDegreesDto {
 }
 
 #[typeshare]
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BulkCreateElectionsDto {
     pub candidacy_period: Option<DateRangeDto>,
@@ -313,7 +817,7 @@ impl NominationDto {
 }
 
 #[typeshare]
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EditNominationDto {
     pub username: String,
@@ -322,7 +826,138 @@ pub struct EditNominationDto {
 }
 
 #[typeshare]
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RescheduleElectionDto {
+    pub candidacy_period: Option<DateRangeDto>,
+    pub voting_period: DateRangeDto,
+}
+
+#[typeshare]
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingActionDto {
+    pub id: i32,
+    pub kind: PendingActionKindDto,
+    /// Username of the admin, id of the election, or "{election}/{username}" of the nomination.
+    pub target: String,
+    /// Everything needed to execute the action, such as the new periods of an election.
+    pub action: Value,
+    pub proposed_by: String,
+    pub proposed_at: DateTimeUtc,
+    /// After this, the action can no longer be approved.
+    pub expires_at: DateTimeUtc,
+    pub status: PendingActionStatusDto,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTimeUtc>,
+}
+
+impl PendingActionDto {
+    pub fn from_entity(entity: pending_action::Model, now: DateTimeUtc) -> Result<Self, AppError> {
+        let mut status = entity.status.parse()?;
+        if status == PendingActionStatusDto::Pending && entity.expires_at.and_utc() <= now {
+            status = PendingActionStatusDto::Expired;
+        }
+
+        Ok(Self {
+            id: entity.id,
+            kind: entity.kind.parse()?,
+            target: entity.target,
+            action: serde_json::from_str(&entity.action)
+                .map_err(|err| DbErr::Json(err.to_string()))?,
+            proposed_by: entity.proposed_by,
+            proposed_at: entity.proposed_at.and_utc(),
+            expires_at: entity.expires_at.and_utc(),
+            status,
+            decided_by: entity.decided_by,
+            decided_at: entity.decided_at.map(|date| date.and_utc()),
+        })
+    }
+}
+
+#[typeshare]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PendingActionKindDto {
+    RemoveAdmin,
+    /// Invalidate a nomination that had already been validated.
+    InvalidateNomination,
+    /// Delete an election whose voting period has already started.
+    DeleteElection,
+    /// Change the periods of an election whose voting period has already started.
+    RescheduleElection,
+}
+
+impl PendingActionKindDto {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::RemoveAdmin => "REMOVE_ADMIN",
+            Self::InvalidateNomination => "INVALIDATE_NOMINATION",
+            Self::DeleteElection => "DELETE_ELECTION",
+            Self::RescheduleElection => "RESCHEDULE_ELECTION",
+        }
+    }
+}
+
+impl std::str::FromStr for PendingActionKindDto {
+    type Err = DbErr;
+
+    fn from_str(kind: &str) -> Result<Self, Self::Err> {
+        match kind {
+            "REMOVE_ADMIN" => Ok(Self::RemoveAdmin),
+            "INVALIDATE_NOMINATION" => Ok(Self::InvalidateNomination),
+            "DELETE_ELECTION" => Ok(Self::DeleteElection),
+            "RESCHEDULE_ELECTION" => Ok(Self::RescheduleElection),
+            _ => Err(DbErr::Type(format!(
+                "Unknown pending action kind '{}'",
+                kind
+            ))),
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PendingActionStatusDto {
+    Pending,
+    /// Approved by a second admin, and executed.
+    Approved,
+    Rejected,
+    /// Not approved before its deadline.
+    Expired,
+}
+
+impl PendingActionStatusDto {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "PENDING",
+            Self::Approved => "APPROVED",
+            Self::Rejected => "REJECTED",
+            Self::Expired => "EXPIRED",
+        }
+    }
+}
+
+impl std::str::FromStr for PendingActionStatusDto {
+    type Err = DbErr;
+
+    fn from_str(status: &str) -> Result<Self, Self::Err> {
+        match status {
+            "PENDING" => Ok(Self::Pending),
+            "APPROVED" => Ok(Self::Approved),
+            "REJECTED" => Ok(Self::Rejected),
+            "EXPIRED" => Ok(Self::Expired),
+            _ => Err(DbErr::Type(format!(
+                "Unknown pending action status '{}'",
+                status
+            ))),
+        }
+    }
+}
+
+#[typeshare]
+#[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BulkAddUserDegreeOverrideDto {
     pub degree_id: String,
@@ -331,7 +966,7 @@ pub struct BulkAddUserDegreeOverrideDto {
 }
 
 #[typeshare]
-#[derive(Deserialize)]
+#[derive(Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct BulkDeleteUserDegreeOverrideDto {
     pub degree_id: String,
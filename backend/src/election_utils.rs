@@ -12,13 +12,15 @@ use sea_orm::{
 use serde::Serialize;
 
 use crate::{
-    dtos::{DegreeEntryDto, UserDto},
+    dtos::{DegreeDto, DegreeEntryDto, UserDto},
     errors::AppError,
     services::fenix::FenixService,
 };
 
-pub fn is_in_candidacy_period(election: &election::Model) -> Result<(), AppError> {
-    let now = chrono::Utc::now();
+pub fn is_in_candidacy_period(
+    election: &election::Model,
+    now: DateTimeUtc,
+) -> Result<(), AppError> {
     match (
         election.candidacy_period_start,
         election.candidacy_period_end,
@@ -30,9 +32,7 @@ pub fn is_in_candidacy_period(election: &election::Model) -> Result<(), AppError
     .ok_or(AppError::OutsideCandidacyPeriod)
 }
 
-pub fn is_in_voting_period(election: &election::Model) -> Result<(), AppError> {
-    let now = chrono::Utc::now();
-
+pub fn is_in_voting_period(election: &election::Model, now: DateTimeUtc) -> Result<(), AppError> {
     let start = election.voting_period_start;
     let end = election.voting_period_end;
     (now >= start.and_utc() && now <= end.and_utc())
@@ -135,13 +135,52 @@ struct ElectionBlankVotes {
     non_blank_votes: Option<i32>,
 }
 
+/// Get the results of all elections of the academic year that have ended, as CSV.
+/// Only elections of the degrees for which `include_degree` is true are included
+/// (the degree is `None` if it is unknown).
 pub async fn get_all_results_as_csv(
     conn: &DatabaseConnection,
     fenix_service: &FenixService,
+    academic_year: &str,
+    now: DateTimeUtc,
+    include_degree: impl Fn(Option<&DegreeDto>) -> bool,
 ) -> Result<String, AppError> {
-    let active_year = fenix_service.get_active_year().await?;
+    let all_results =
+        get_all_results(conn, fenix_service, academic_year, now, include_degree).await?;
+
+    let mut wtr = csv::Writer::from_writer(vec![]);
+    for result in all_results {
+        wtr.serialize(result)?;
+    }
+
+    wtr.flush()?;
+
+    String::from_utf8(wtr.into_inner().map_err(|_| AppError::CsvError(None))?)
+        .map_err(|_| AppError::CsvError(None))
+}
+
+/// Same as [`get_all_results_as_csv`], but with each row as an object of a JSON array.
+pub async fn get_all_results_as_json(
+    conn: &DatabaseConnection,
+    fenix_service: &FenixService,
+    academic_year: &str,
+    now: DateTimeUtc,
+    include_degree: impl Fn(Option<&DegreeDto>) -> bool,
+) -> Result<String, AppError> {
+    let all_results =
+        get_all_results(conn, fenix_service, academic_year, now, include_degree).await?;
+
+    Ok(serde_json::to_string_pretty(&all_results).expect("results to be serializable"))
+}
 
-    let now = chrono::Utc::now().naive_utc();
+async fn get_all_results(
+    conn: &DatabaseConnection,
+    fenix_service: &FenixService,
+    academic_year: &str,
+    now: DateTimeUtc,
+    include_degree: impl Fn(Option<&DegreeDto>) -> bool,
+) -> Result<Vec<ElectionAllResults>, AppError> {
+    let now = now.naive_utc();
 
     let txn = conn
         .begin_with_config(None, Some(sea_orm::AccessMode::ReadOnly))
@@ -167,7 +206,7 @@ pub async fn get_all_results_as_csv(
         )
         .filter(
             Condition::all()
-                .add(election::Column::AcademicYear.eq(active_year.clone()))
+                .add(election::Column::AcademicYear.eq(academic_year))
                 .add(election::Column::VotingPeriodEnd.lt(now))
                 .add(nomination::Column::Valid.eq(Some(true))),
         )
@@ -231,7 +270,7 @@ pub async fn get_all_results_as_csv(
         .to_owned()
         .filter(
             Condition::all()
-                .add(election::Column::AcademicYear.eq(active_year))
+                .add(election::Column::AcademicYear.eq(academic_year))
                 .add(election::Column::VotingPeriodEnd.lt(now)),
         )
         .order_by_asc(election::Column::Round)
@@ -257,14 +296,20 @@ pub async fn get_all_results_as_csv(
         })
     }
 
-    // replace degree id with acronym
-    for result in &mut all_results {
-        result.degree_id = fenix_service
-            .get_degree(&result.degree_id)
-            .await?
+    // replace degree id with acronym, leaving out degrees that aren't included
+    let mut included_results = Vec::with_capacity(all_results.len());
+    for mut result in all_results {
+        let degree = fenix_service.get_degree(&result.degree_id).await?;
+        if !include_degree(degree.as_ref()) {
+            continue;
+        }
+
+        result.degree_id = degree
             .map(|degree| degree.acronym)
             .unwrap_or("unknown".to_owned());
+        included_results.push(result);
     }
+    let mut all_results = included_results;
 
     // sort by round, degree, curricular year
     all_results.sort_by_key(|row| {
@@ -275,13 +320,59 @@ pub async fn get_all_results_as_csv(
         )
     });
 
-    let mut wtr = csv::Writer::from_writer(vec![]);
-    for result in all_results {
-        wtr.serialize(result)?;
+    Ok(all_results)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::*;
+
+    fn get_election() -> election::Model {
+        let date = |day| Utc.with_ymd_and_hms(2023, 10, day, 0, 0, 0).unwrap();
+
+        election::Model {
+            id: 1,
+            academic_year: "2023/2024".to_string(),
+            degree_id: "123456".to_string(),
+            curricular_year: None,
+            candidacy_period_start: Some(date(1).naive_utc()),
+            candidacy_period_end: Some(date(5).naive_utc()),
+            voting_period_start: date(10).naive_utc(),
+            voting_period_end: date(12).naive_utc(),
+            round: 1,
+        }
     }
 
-    wtr.flush()?;
+    #[test]
+    fn test_is_in_candidacy_period() {
+        let election = get_election();
+        let start = election.candidacy_period_start.unwrap().and_utc();
+        let end = election.candidacy_period_end.unwrap().and_utc();
 
-    String::from_utf8(wtr.into_inner().map_err(|_| AppError::CsvError(None))?)
-        .map_err(|_| AppError::CsvError(None))
+        assert!(is_in_candidacy_period(&election, start - Duration::seconds(1)).is_err());
+        assert!(is_in_candidacy_period(&election, start).is_ok());
+        assert!(is_in_candidacy_period(&election, end).is_ok());
+        assert!(is_in_candidacy_period(&election, end + Duration::seconds(1)).is_err());
+
+        let election = election::Model {
+            candidacy_period_start: None,
+            candidacy_period_end: None,
+            ..get_election()
+        };
+        assert!(is_in_candidacy_period(&election, start).is_err());
+    }
+
+    #[test]
+    fn test_is_in_voting_period() {
+        let election = get_election();
+        let start = election.voting_period_start.and_utc();
+        let end = election.voting_period_end.and_utc();
+
+        assert!(is_in_voting_period(&election, start - Duration::seconds(1)).is_err());
+        assert!(is_in_voting_period(&election, start).is_ok());
+        assert!(is_in_voting_period(&election, end).is_ok());
+        assert!(is_in_voting_period(&election, end + Duration::seconds(1)).is_err());
+    }
 }
@@ -1,18 +1,22 @@
-use async_session::log::error;
+use std::fmt;
+
 use axum::{
     http::StatusCode,
     response::{IntoResponse, Response},
     Json,
 };
 use sea_orm::error::DbErr;
+use tracing::error;
 
 use crate::dtos::AppErrorDto;
 
+#[derive(Debug)]
 pub enum AppError {
     BadInput(&'static str),
     DuplicateAdmin,
     UnknownAdmin,
     NotEnoughAdmins,
+    InvalidAdminScope,
     UnknownElection,
     InvalidDateRange,
     ElectionCandidacyAfterVoting,
@@ -27,23 +31,39 @@ pub enum AppError {
     OutsideVotingPeriod,
     ElectionUnauthorized,
     InvalidPersonSignature,
+    ForeignPersonSignature,
+    ExpiredPersonSignature,
     UnknownNomination,
+    UnknownSession,
+    UnknownPendingAction,
+    UnknownObserver,
+    UnknownApiToken,
+    ApiTokenScope,
+    SessionRequired,
+    DuplicatePendingAction,
+    PendingActionDecided,
+    PendingActionExpired,
+    SelfApproval,
+    UnsupportedSessionStore,
     Unauthorized,
     Forbidden,
     FenixError,
+    IdentityProviderError,
     SessionSerializationError(async_session::serde_json::Error),
     DbError(DbErr),
     CsvError(Option<csv::Error>),
     IoError(std::io::Error),
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, key) = match self {
-            AppError::BadInput(error) => (StatusCode::BAD_REQUEST, error),
+impl AppError {
+    /// The status of the response for this error, along with the translation key shown to users.
+    pub fn status_and_key(&self) -> (StatusCode, &'static str) {
+        match self {
+            AppError::BadInput(error) => (StatusCode::BAD_REQUEST, *error),
             AppError::DuplicateAdmin => (StatusCode::CONFLICT, "error.duplicate.admin"),
             AppError::UnknownAdmin => (StatusCode::NOT_FOUND, "error.unknown.admin"),
             AppError::NotEnoughAdmins => (StatusCode::NOT_FOUND, "error.not.enough.admins"),
+            AppError::InvalidAdminScope => (StatusCode::BAD_REQUEST, "error.admin.scope.invalid"),
             AppError::UnknownElection => (StatusCode::NOT_FOUND, "error.unknown.election"),
             AppError::InvalidDateRange => (StatusCode::BAD_REQUEST, "error.daterange.invalid"),
             AppError::ElectionCandidacyAfterVoting => (
@@ -78,15 +98,64 @@ impl IntoResponse for AppError {
             AppError::InvalidPersonSignature => {
                 (StatusCode::UNAUTHORIZED, "error.person-signature.invalid")
             }
+            AppError::ForeignPersonSignature => {
+                (StatusCode::FORBIDDEN, "error.person-signature.foreign")
+            }
+            AppError::ExpiredPersonSignature => {
+                (StatusCode::UNAUTHORIZED, "error.person-signature.expired")
+            }
             AppError::UnknownNomination => (StatusCode::NOT_FOUND, "error.unknown.nomination"),
+            AppError::UnknownSession => (StatusCode::NOT_FOUND, "error.unknown.session"),
+            AppError::UnknownObserver => (StatusCode::NOT_FOUND, "error.unknown.observer"),
+            AppError::UnknownApiToken => (StatusCode::NOT_FOUND, "error.unknown.api-token"),
+            AppError::ApiTokenScope => (StatusCode::FORBIDDEN, "error.api-token.scope"),
+            AppError::SessionRequired => (StatusCode::FORBIDDEN, "error.session-required"),
+            AppError::UnknownPendingAction => {
+                (StatusCode::NOT_FOUND, "error.unknown.pending-action")
+            }
+            AppError::DuplicatePendingAction => {
+                (StatusCode::CONFLICT, "error.duplicate.pending-action")
+            }
+            AppError::PendingActionDecided => {
+                (StatusCode::CONFLICT, "error.pending-action.decided")
+            }
+            AppError::PendingActionExpired => {
+                (StatusCode::CONFLICT, "error.pending-action.expired")
+            }
+            AppError::SelfApproval => (StatusCode::FORBIDDEN, "error.pending-action.self-approval"),
+            AppError::UnsupportedSessionStore => (
+                StatusCode::NOT_IMPLEMENTED,
+                "error.session-store.unsupported",
+            ),
             AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "error.unauthorized"),
             AppError::Forbidden => (StatusCode::FORBIDDEN, "error.forbidden"),
             AppError::FenixError => (StatusCode::BAD_GATEWAY, "error.fenix"),
+            AppError::IdentityProviderError => (StatusCode::BAD_GATEWAY, "error.identity-provider"),
             AppError::SessionSerializationError(_)
             | AppError::DbError(_)
             | AppError::CsvError(_)
             | AppError::IoError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "error.internal"),
-        };
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let key = self.status_and_key().1;
+        // internal errors share the same key, so include what caused them
+        match self {
+            AppError::SessionSerializationError(err) => write!(f, "{}: {}", key, err),
+            AppError::DbError(err) => write!(f, "{}: {}", key, err),
+            AppError::CsvError(Some(err)) => write!(f, "{}: {}", key, err),
+            AppError::IoError(err) => write!(f, "{}: {}", key, err),
+            _ => write!(f, "{}", key),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let (status, key) = self.status_and_key();
 
         let error = AppErrorDto {
             key: key.to_string(),
@@ -0,0 +1,251 @@
+use axum::{
+    extract::FromRef,
+    http::HeaderName,
+    middleware,
+    routing::{delete, get, patch, post, put},
+    Router,
+};
+use axum_sessions::{PersistencePolicy, SessionLayer};
+use sea_orm::DatabaseConnection;
+use tower::ServiceBuilder;
+use tower_http::{
+    request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer},
+    trace::{DefaultOnResponse, TraceLayer},
+};
+#[cfg(feature = "dev-login")]
+use tracing::warn;
+use tracing::{info, Level};
+
+use clock::Clock;
+use pending_actions::ApprovalDeadline;
+use services::{
+    fenix::FenixService, identity::SharedIdentityProvider, signing_keys::SigningKeyService,
+};
+use session_store::AppSessionStore;
+
+pub mod api_tokens;
+pub mod audit;
+pub mod auth_utils;
+pub mod cache;
+pub mod circuit_breaker;
+pub mod cli;
+pub mod clock;
+pub mod crypto_utils;
+pub mod dtos;
+pub mod election_utils;
+pub mod errors;
+pub mod logging;
+pub mod metrics;
+pub mod pending_actions;
+pub mod routes;
+pub mod services;
+pub mod session_store;
+pub mod settings;
+
+#[derive(Clone, FromRef)]
+pub struct AppState {
+    pub fenix_service: FenixService,
+    pub identity_provider: SharedIdentityProvider,
+    pub conn: DatabaseConnection,
+    pub signing_key_service: SigningKeyService,
+    pub session_store: AppSessionStore,
+    pub clock: Clock,
+    pub approval_deadline: ApprovalDeadline,
+}
+
+/// Build the application's router, with all API routes nested under `/api`.
+/// Sessions are kept in the state's session store, and their cookies signed with
+/// the given secret.
+pub fn build_router(state: AppState, session_secret: &[u8]) -> Router {
+    // only persist sessions when their data changes, so that neither anonymous requests
+    // nor each authenticated one write to the store (see `track_session_activity`)
+    let session_layer = SessionLayer::new(state.session_store.clone(), session_secret)
+        .with_persistence_policy(PersistencePolicy::ChangedOnly);
+
+    let api_routes = Router::new()
+        .route(
+            "/academic-year",
+            get(routes::academic_year::get_academic_year)
+                .put(routes::academic_year::pin_academic_year),
+        )
+        .route(
+            "/academic-years",
+            get(routes::academic_year::list_academic_years),
+        )
+        .route("/admins", get(routes::admin::list_admins))
+        .route("/admin", post(routes::admin::add_admin))
+        .route("/admin/:username", delete(routes::admin::remove_admin))
+        .route("/api-tokens", get(routes::api_tokens::list_api_tokens))
+        .route("/api-token", post(routes::api_tokens::create_api_token))
+        .route(
+            "/api-token/:id",
+            delete(routes::api_tokens::revoke_api_token),
+        )
+        .route("/audit-log", get(routes::audit_log::list_audit_log))
+        .route(
+            "/audit-log/verify",
+            get(routes::audit_log::verify_audit_log),
+        )
+        .route("/config", get(routes::config::get_config))
+        .route("/degrees", get(routes::degrees::list_degrees))
+        .route(
+            "/degrees/elections",
+            get(routes::degrees::list_degrees_with_elections),
+        )
+        .route(
+            "/elections/bulk",
+            post(routes::elections::bulk_create_elections),
+        )
+        .route(
+            "/elections/results/download/csv",
+            get(routes::elections::download_results),
+        )
+        .route(
+            "/elections/nominations/unverified",
+            get(routes::elections::get_unverified_nominations),
+        )
+        .route(
+            "/elections/nominations/unverified-count",
+            get(routes::elections::get_unverified_nominations_count),
+        )
+        .route(
+            "/elections/user",
+            get(routes::elections::get_user_elections),
+        )
+        .route(
+            "/election/:election_id",
+            get(routes::elections::get_election).delete(routes::elections::delete_election),
+        )
+        .route(
+            "/election/:election_id/details",
+            get(routes::elections::get_election_details),
+        )
+        .route(
+            "/election/:election_id/nominate",
+            post(routes::elections::nominate_others),
+        )
+        .route(
+            "/election/:election_id/nomination",
+            post(routes::elections::add_nomination),
+        )
+        .route(
+            "/election/:election_id/nomination",
+            patch(routes::elections::edit_nomination),
+        )
+        .route(
+            "/election/:election_id/periods",
+            put(routes::elections::reschedule_election),
+        )
+        .route(
+            "/election/:election_id/self-nominate",
+            post(routes::elections::self_nominate),
+        )
+        .route(
+            "/election/:election_id/vote",
+            post(routes::elections::cast_vote),
+        )
+        .route(
+            "/election/:election_id/vote-options",
+            get(routes::elections::get_vote_options),
+        )
+        .route(
+            "/fenix-snapshot",
+            get(routes::fenix_snapshot::get_fenix_snapshot),
+        )
+        .route(
+            "/fenix-snapshot/refresh",
+            post(routes::fenix_snapshot::refresh_fenix_snapshot),
+        )
+        .route("/login", post(routes::login::login))
+        .route("/login/start", get(routes::login::start_login))
+        .route("/logout", post(routes::login::logout))
+        .route("/observers", get(routes::observers::list_observers))
+        .route("/observer", post(routes::observers::grant_observer))
+        .route(
+            "/observer/:username",
+            delete(routes::observers::revoke_observer),
+        )
+        .route(
+            "/pending-actions",
+            get(routes::pending_actions::list_pending_actions),
+        )
+        .route(
+            "/pending-action/:id/approve",
+            post(routes::pending_actions::approve_pending_action),
+        )
+        .route(
+            "/pending-action/:id/reject",
+            post(routes::pending_actions::reject_pending_action),
+        )
+        .route("/search-user", post(routes::search_user::search_user))
+        .route(
+            "/session/:session_id",
+            delete(routes::sessions::revoke_session),
+        )
+        .route("/sessions", get(routes::sessions::list_own_sessions))
+        .route(
+            "/user-degree-overrides",
+            get(routes::user_degree_overrides::get_user_degree_overrides),
+        )
+        .route(
+            "/user-degree-overrides",
+            post(routes::user_degree_overrides::bulk_add_user_degree_override),
+        )
+        .route(
+            "/user-degree-overrides",
+            delete(routes::user_degree_overrides::bulk_delete_user_degree_override),
+        )
+        .route(
+            "/user/:username/sessions",
+            get(routes::sessions::list_user_sessions),
+        )
+        .route("/whoami", get(routes::login::whoami));
+
+    let api_routes = if routes::admin::is_setup_allowed() {
+        api_routes.route("/setup/admin", post(routes::admin::setup_first_admin))
+    } else {
+        info!("admin setup is disabled: admins can only be added from the management CLI");
+        api_routes
+    };
+
+    #[cfg(feature = "dev-login")]
+    let api_routes = if routes::dev_login::is_allowed() {
+        warn!("development login is enabled: anyone can log in as any user");
+        api_routes.route("/dev/login", post(routes::dev_login::dev_login))
+    } else {
+        warn!("development login is not allowed in release builds, unless DEV_LOGIN=true");
+        api_routes
+    };
+
+    #[cfg(feature = "time-travel")]
+    let api_routes = api_routes.route(
+        "/time-travel",
+        get(routes::time_travel::get_time_travel).put(routes::time_travel::set_time_travel),
+    );
+
+    Router::new()
+        .nest("/api", api_routes)
+        .layer(
+            ServiceBuilder::new()
+                .layer(SetRequestIdLayer::new(
+                    HeaderName::from_static(logging::REQUEST_ID_HEADER),
+                    MakeRequestUuid,
+                ))
+                .layer(PropagateRequestIdLayer::new(HeaderName::from_static(
+                    logging::REQUEST_ID_HEADER,
+                )))
+                .layer(middleware::from_fn(metrics::track_http_requests))
+                .layer(
+                    TraceLayer::new_for_http()
+                        .make_span_with(logging::make_request_span)
+                        .on_response(DefaultOnResponse::new().level(Level::INFO)),
+                )
+                .layer(session_layer)
+                .layer(middleware::from_fn(session_store::track_session_activity)),
+        )
+        // scrapes and probes are neither tracked nor given a session
+        .route("/healthz", get(routes::health::healthz))
+        .route("/metrics", get(metrics::get_metrics))
+        .route("/readyz", get(routes::health::readyz))
+        .with_state(state)
+}
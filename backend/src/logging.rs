@@ -0,0 +1,63 @@
+use std::env;
+
+use axum::{extract::MatchedPath, http::Request};
+use tower_http::request_id::RequestId;
+use tracing::{field, info_span, Span};
+use tracing_subscriber::EnvFilter;
+
+/// Name of the header with the id of each request, which is generated unless
+/// a proxy in front of the app already set it, and returned in the response.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Initialize logging according to the LOG_FORMAT env variable, which can either
+/// be "text" (default) or "json". Levels are filtered according to RUST_LOG,
+/// which defaults to "info".
+pub fn init_from_env() -> Result<(), String> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let subscriber = tracing_subscriber::fmt().with_env_filter(filter);
+
+    match env::var("LOG_FORMAT").as_deref() {
+        Err(_) | Ok("text") => subscriber.init(),
+        Ok("json") => subscriber
+            .json()
+            .with_current_span(false)
+            .with_span_list(true)
+            .init(),
+        Ok(other) => {
+            return Err(format!(
+                "Invalid LOG_FORMAT '{}': must be either 'text' or 'json'",
+                other
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+/// Create the span in which a request is handled, so that everything logged while handling it
+/// includes the request id and route, as well as the username, once the user is known
+/// (see [`record_username`]).
+pub fn make_request_span<B>(request: &Request<B>) -> Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .and_then(|id| id.header_value().to_str().ok())
+        .unwrap_or_default();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(MatchedPath::as_str);
+
+    info_span!(
+        "request",
+        request_id,
+        method = %request.method(),
+        route,
+        username = field::Empty,
+    )
+}
+
+/// Attach the authenticated user to the span of the current request.
+pub fn record_username(username: &str) {
+    Span::current().record("username", username);
+}
@@ -1,45 +1,101 @@
-use crate::services::fenix::FenixService;
 use std::env;
 use std::net::{IpAddr, Ipv6Addr, SocketAddr};
 use std::path::PathBuf;
 
-use axum::extract::FromRef;
-
-use axum::routing::{delete, get, patch, post};
-use axum::Router;
-use axum_sessions::SessionLayer;
+use clap::Parser;
+use ist_delegate_election::{
+    cli::{self, Cli, Command},
+    clock::Clock,
+    logging,
+    pending_actions::ApprovalDeadline,
+    routes,
+    services::{self, fenix::FenixService, signing_keys::SigningKeyService},
+    session_store::AppSessionStore,
+    AppState,
+};
 use migration::{Migrator, MigratorTrait};
 use sea_orm::{Database, DatabaseConnection};
 
-use tower::ServiceBuilder;
-
 use tower_http::services::{ServeDir, ServeFile};
-use tower_http::trace::TraceLayer;
 use tracing::{info, warn};
 
 use rand::Rng;
 
-mod auth_utils;
-mod cache;
-mod crypto_utils;
-mod dtos;
-mod election_utils;
-mod errors;
-mod routes;
-mod services;
-
-#[derive(Clone, FromRef)]
-struct AppState {
-    fenix_service: FenixService,
-    conn: DatabaseConnection,
-    signing_key: [u8; 64],
-}
-
 #[tokio::main]
 async fn main() {
-    // enable console logging
-    tracing_subscriber::fmt::init();
+    let cli = Cli::parse();
+    logging::init_from_env().expect("Failed to initialize logging");
+
+    let mut out = std::io::stdout();
+    let result = match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => {
+            serve().await;
+            return;
+        }
+        Command::Migrate(command) => {
+            let conn = connect_database().await;
+            cli::run_migrate_command(&conn, command, &mut out)
+                .await
+                .map(|()| true)
+        }
+        Command::Manage(command) => {
+            let state = create_state().await;
+            cli::run_command(&state, command, &mut out).await
+        }
+    };
+
+    match result {
+        Ok(true) => {}
+        Ok(false) => std::process::exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn connect_database() -> DatabaseConnection {
+    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+    Database::connect(database_url)
+        .await
+        .expect("Database connection failed")
+}
+
+/// Build the state shared by the web app and the management commands,
+/// applying any pending migrations first.
+async fn create_state() -> AppState {
+    let conn = connect_database().await;
+    Migrator::up(&conn, None).await.expect("Migration failed");
+
+    let fenix_service = FenixService::new()
+        .expect("Failed to initialize FenixService")
+        .with_database(conn.clone());
+    if fenix_service.load_snapshots().await.is_err() {
+        warn!("Failed to load the Fénix snapshot, it will only be available after fetching from Fénix");
+    }
+    let identity_provider = services::identity::from_env(&fenix_service)
+        .expect("Failed to initialize identity provider");
+
+    let session_store =
+        AppSessionStore::from_env(&conn).expect("Failed to initialize session store");
+    let signing_key_service =
+        SigningKeyService::new(conn.clone()).expect("Failed to initialize SigningKeyService");
 
+    let approval_deadline =
+        ApprovalDeadline::from_env().expect("Failed to read the pending action deadline");
+
+    AppState {
+        fenix_service,
+        identity_provider,
+        conn,
+        signing_key_service,
+        session_store,
+        clock: Clock::default(),
+        approval_deadline,
+    }
+}
+
+async fn serve() {
     let port: u16 = std::env::var("PORT")
         .map(|port_str| {
             port_str
@@ -49,10 +105,15 @@ async fn main() {
         .unwrap_or(5000);
     let static_dir = std::env::var("STATIC_DIR").ok();
 
-    let fenix_service = FenixService::new().expect("Failed to initialize FenixService");
+    let state = create_state().await;
+    if routes::admin::bootstrap_admin(&state.conn)
+        .await
+        .expect("Failed to bootstrap the first admin")
+    {
+        info!("made BOOTSTRAP_ADMIN the first admin");
+    }
 
-    // TODO store session in database
-    let session_store = async_session::MemoryStore::new();
+    state.session_store.spawn_cleanup_task();
     let session_secret = env::var("SESSION_SECRET").map_or_else(
         |_| {
             warn!("SESSION_SECRET not set, generating a random one. Set this to, at least, a 64-byte hex string to persist sessions");
@@ -61,111 +122,11 @@ async fn main() {
             random_bytes.to_vec()
         },
         |secret| hex::decode(secret).expect("Invalid SESSION_SECRET: not a hex string"));
-    let session_layer = SessionLayer::new(session_store, &session_secret);
 
-    let mut signing_key = [0u8; 64];
-    rand::thread_rng().fill(&mut signing_key);
-
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL is not set");
-    let conn = Database::connect(database_url)
-        .await
-        .expect("Database connection failed");
-    Migrator::up(&conn, None).await.expect("Migration failed");
-
-    let state = AppState {
-        fenix_service,
-        conn,
-        signing_key,
-    };
+    #[cfg(feature = "time-travel")]
+    warn!("time travel is enabled: admins can change the time used for election periods, which must never happen in production");
 
-    let api_routes = Router::new()
-        .route("/admins", get(routes::admin::list_admins))
-        .route("/admin", post(routes::admin::add_admin))
-        .route("/admin/:username", delete(routes::admin::remove_admin))
-        .route("/config", get(routes::config::get_config))
-        .route("/degrees", get(routes::degrees::list_degrees))
-        .route(
-            "/degrees/elections",
-            get(routes::degrees::list_degrees_with_elections),
-        )
-        .route(
-            "/elections/bulk",
-            post(routes::elections::bulk_create_elections),
-        )
-        .route(
-            "/elections/results/download/csv",
-            get(routes::elections::download_results),
-        )
-        .route(
-            "/elections/nominations/unverified",
-            get(routes::elections::get_unverified_nominations),
-        )
-        .route(
-            "/elections/nominations/unverified-count",
-            get(routes::elections::get_unverified_nominations_count),
-        )
-        .route(
-            "/elections/user",
-            get(routes::elections::get_user_elections),
-        )
-        .route(
-            "/election/:election_id",
-            get(routes::elections::get_election),
-        )
-        .route(
-            "/election/:election_id/details",
-            get(routes::elections::get_election_details),
-        )
-        .route(
-            "/election/:election_id/nominate",
-            post(routes::elections::nominate_others),
-        )
-        .route(
-            "/election/:election_id/nomination",
-            post(routes::elections::add_nomination),
-        )
-        .route(
-            "/election/:election_id/nomination",
-            patch(routes::elections::edit_nomination),
-        )
-        .route(
-            "/election/:election_id/self-nominate",
-            post(routes::elections::self_nominate),
-        )
-        .route(
-            "/election/:election_id/vote",
-            post(routes::elections::cast_vote),
-        )
-        .route(
-            "/election/:election_id/vote-options",
-            get(routes::elections::get_vote_options),
-        )
-        .route("/login", post(routes::login::login))
-        .route("/logout", post(routes::login::logout))
-        .route("/search-user", post(routes::search_user::search_user))
-        .route("/setup/admin", post(routes::admin::setup_first_admin))
-        .route(
-            "/user-degree-overrides",
-            get(routes::user_degree_overrides::get_user_degree_overrides),
-        )
-        .route(
-            "/user-degree-overrides",
-            post(routes::user_degree_overrides::bulk_add_user_degree_override),
-        )
-        .route(
-            "/user-degree-overrides",
-            delete(routes::user_degree_overrides::bulk_delete_user_degree_override),
-        )
-        .route("/whoami", get(routes::login::whoami));
-
-    let mut app = Router::new()
-        .nest("/api", api_routes)
-        .layer(
-            ServiceBuilder::new()
-                .layer(session_layer)
-                .layer(TraceLayer::new_for_http()),
-        )
-        .with_state(state);
+    let mut app = ist_delegate_election::build_router(state, &session_secret);
 
     if let Some(static_dir) = &static_dir {
         app = app.fallback_service(
@@ -0,0 +1,373 @@
+use std::{
+    env,
+    sync::LazyLock,
+    time::{Duration, Instant},
+};
+
+use async_session::MemoryStore;
+use axum::{
+    extract::{MatchedPath, State},
+    http::{header, HeaderMap, Request},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use entity::{
+    election::{self, Entity as Election},
+    nomination::{self, Entity as Nomination},
+    session::{self, Entity as SessionEntity},
+    vote_log::{self, Entity as VoteLog},
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+    TextEncoder,
+};
+use sea_orm::{prelude::*, Condition, DatabaseBackend, DatabaseConnection, QuerySelect};
+use sha2::{Digest, Sha256};
+
+use crate::{clock::Clock, errors::AppError, session_store::AppSessionStore};
+
+/// Prometheus metrics exposed by the `/metrics` endpoint.
+///
+/// Counters and histograms are updated as requests are handled, whereas gauges about
+/// the database and elections are computed whenever the metrics are scraped.
+pub struct Metrics {
+    registry: Registry,
+    /// Metrics that could influence ongoing elections, only exposed to authorized scrapers.
+    election_registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    fenix_request_duration: HistogramVec,
+    fenix_request_errors: IntCounterVec,
+    cache_requests: IntCounterVec,
+    db_pool_connections: IntGaugeVec,
+    election_votes: IntGaugeVec,
+    unverified_nominations: IntGauge,
+    active_sessions: IntGauge,
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+/// Get the metrics shared by the whole application.
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// Result of getting a value from a cache, used as a metric label.
+#[derive(Clone, Copy)]
+pub enum CacheResult {
+    Hit,
+    /// An expired value was served, while a new one is fetched in the background.
+    Stale,
+    Miss,
+}
+
+impl CacheResult {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Hit => "hit",
+            Self::Stale => "stale",
+            Self::Miss => "miss",
+        }
+    }
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("ist_delegate_election".to_string()), None)
+            .expect("metrics prefix must be valid");
+        let election_registry =
+            Registry::new_custom(Some("ist_delegate_election".to_string()), None)
+                .expect("metrics prefix must be valid");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Number of HTTP requests handled"),
+            &["method", "route", "status"],
+        )
+        .unwrap();
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "Time taken to handle HTTP requests",
+            ),
+            &["method", "route"],
+        )
+        .unwrap();
+        let fenix_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "fenix_request_duration_seconds",
+                "Time taken by requests to Fénix, including retries",
+            ),
+            &["method"],
+        )
+        .unwrap();
+        let fenix_request_errors = IntCounterVec::new(
+            Opts::new(
+                "fenix_request_errors_total",
+                "Number of failed requests to Fénix",
+            ),
+            &["method"],
+        )
+        .unwrap();
+        let cache_requests = IntCounterVec::new(
+            Opts::new("cache_requests_total", "Number of cache lookups, by result"),
+            &["cache", "result"],
+        )
+        .unwrap();
+        let db_pool_connections = IntGaugeVec::new(
+            Opts::new(
+                "db_pool_connections",
+                "Number of connections in the database pool, by state",
+            ),
+            &["state"],
+        )
+        .unwrap();
+        let election_votes = IntGaugeVec::new(
+            Opts::new(
+                "election_votes_cast",
+                "Number of votes cast in each election currently in its voting period",
+            ),
+            &["election"],
+        )
+        .unwrap();
+        let unverified_nominations = IntGauge::new(
+            "unverified_nominations",
+            "Number of nominations yet to be verified, in elections that haven't ended",
+        )
+        .unwrap();
+        let active_sessions =
+            IntGauge::new("active_sessions", "Number of sessions that haven't expired").unwrap();
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(fenix_request_duration.clone()),
+            Box::new(fenix_request_errors.clone()),
+            Box::new(cache_requests.clone()),
+            Box::new(db_pool_connections.clone()),
+            Box::new(unverified_nominations.clone()),
+            Box::new(active_sessions.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metrics must only be registered once");
+        }
+        election_registry
+            .register(Box::new(election_votes.clone()))
+            .expect("metrics must only be registered once");
+
+        Self {
+            registry,
+            election_registry,
+            http_requests,
+            http_request_duration,
+            fenix_request_duration,
+            fenix_request_errors,
+            cache_requests,
+            db_pool_connections,
+            election_votes,
+            unverified_nominations,
+            active_sessions,
+        }
+    }
+
+    /// Record a request to Fénix, made on behalf of the given `FenixService` method.
+    pub fn record_fenix_request(&self, method: &str, duration: Duration, success: bool) {
+        self.fenix_request_duration
+            .with_label_values(&[method])
+            .observe(duration.as_secs_f64());
+        if !success {
+            self.fenix_request_errors.with_label_values(&[method]).inc();
+        }
+    }
+
+    pub fn record_cache_request(&self, cache: &str, result: CacheResult) {
+        self.cache_requests
+            .with_label_values(&[cache, result.as_str()])
+            .inc();
+    }
+
+    /// Update the gauges that are computed from the database and the session store.
+    async fn update_gauges(
+        &self,
+        conn: &DatabaseConnection,
+        session_store: &AppSessionStore,
+        clock: &Clock,
+    ) -> Result<(), AppError> {
+        let (size, idle) = match conn.get_database_backend() {
+            DatabaseBackend::Postgres => {
+                let pool = conn.get_postgres_connection_pool();
+                (pool.size(), pool.num_idle())
+            }
+            DatabaseBackend::MySql => {
+                let pool = conn.get_mysql_connection_pool();
+                (pool.size(), pool.num_idle())
+            }
+            DatabaseBackend::Sqlite => {
+                let pool = conn.get_sqlite_connection_pool();
+                (pool.size(), pool.num_idle())
+            }
+        };
+        self.db_pool_connections
+            .with_label_values(&["idle"])
+            .set(idle as i64);
+        self.db_pool_connections
+            .with_label_values(&["in_use"])
+            .set(size as i64 - idle as i64);
+
+        let now = clock.now().naive_utc();
+
+        let votes: Vec<(i32, i64)> = Election::find()
+            .select_only()
+            .column(election::Column::Id)
+            .column_as(vote_log::Column::Voter.count(), "votes")
+            .left_join(VoteLog)
+            .filter(election::Column::VotingPeriodStart.lte(now))
+            .filter(election::Column::VotingPeriodEnd.gte(now))
+            .group_by(election::Column::Id)
+            .into_tuple()
+            .all(conn)
+            .await?;
+        // elections that are no longer in their voting period must stop being reported
+        self.election_votes.reset();
+        for (election_id, votes) in votes {
+            self.election_votes
+                .with_label_values(&[&election_id.to_string()])
+                .set(votes);
+        }
+
+        let unverified_nominations = Nomination::find()
+            .inner_join(Election)
+            .filter(nomination::Column::Valid.is_null())
+            .filter(election::Column::VotingPeriodEnd.gte(now))
+            .count(conn)
+            .await?;
+        self.unverified_nominations
+            .set(unverified_nominations as i64);
+
+        let active_sessions = match session_store {
+            AppSessionStore::Memory(store) => MemoryStore::count(store).await as u64,
+            AppSessionStore::Database(_) => {
+                let now = chrono::Utc::now().naive_utc();
+                SessionEntity::find()
+                    .filter(
+                        Condition::any()
+                            .add(session::Column::ExpiresAt.is_null())
+                            .add(session::Column::ExpiresAt.gt(now)),
+                    )
+                    .count(conn)
+                    .await?
+            }
+        };
+        self.active_sessions.set(active_sessions as i64);
+
+        Ok(())
+    }
+
+    /// Encode the metrics, including the election ones only if `include_elections` is set.
+    fn encode(&self, include_elections: bool) -> String {
+        let mut families = self.registry.gather();
+        if include_elections {
+            families.extend(self.election_registry.gather());
+        }
+
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&families, &mut buffer)
+            .expect("metrics must be encodable");
+
+        String::from_utf8(buffer).expect("metrics must be valid UTF-8")
+    }
+}
+
+/// Count requests and measure their latency, by route.
+/// Requests that don't match any route are grouped together, to keep the number of labels bounded.
+pub async fn track_http_requests<B>(request: Request<B>, next: Next<B>) -> Response {
+    let method = request.method().to_string();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or("unmatched", MatchedPath::as_str)
+        .to_string();
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    let elapsed = start.elapsed();
+
+    let metrics = metrics();
+    metrics
+        .http_requests
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .inc();
+    metrics
+        .http_request_duration
+        .with_label_values(&[&method, &route])
+        .observe(elapsed.as_secs_f64());
+
+    response
+}
+
+/// Check whether the request carries the token required to scrape metrics, if any.
+fn is_authorized(headers: &HeaderMap, token: Option<&str>) -> bool {
+    let Some(token) = token else {
+        return true;
+    };
+
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        // compare digests, so that the time taken doesn't depend on how much of the token matches
+        .is_some_and(|bearer| Sha256::digest(bearer) == Sha256::digest(token))
+}
+
+/// Expose all metrics in the Prometheus text format.
+///
+/// If the METRICS_TOKEN env variable is set, scrapers must send it as a bearer token.
+/// Otherwise, metrics about ongoing elections (such as how many votes have been cast)
+/// are left out, since anyone can scrape them.
+pub async fn get_metrics(
+    State(ref conn): State<DatabaseConnection>,
+    State(ref session_store): State<AppSessionStore>,
+    State(ref clock): State<Clock>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let token = env::var("METRICS_TOKEN").ok();
+    if !is_authorized(&headers, token.as_deref()) {
+        return Err(AppError::Unauthorized);
+    }
+
+    let metrics = metrics();
+    metrics.update_gauges(conn, session_store, clock).await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, TextEncoder::new().format_type())],
+        metrics.encode(token.is_some()),
+    )
+        .into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::{header, HeaderMap, HeaderValue};
+
+    use super::is_authorized;
+
+    #[test]
+    fn metrics_token() {
+        let mut headers = HeaderMap::new();
+        assert!(is_authorized(&headers, None));
+        assert!(!is_authorized(&headers, Some("secret")));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer nope"),
+        );
+        assert!(!is_authorized(&headers, Some("secret")));
+
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_static("Bearer secret"),
+        );
+        assert!(is_authorized(&headers, Some("secret")));
+    }
+}
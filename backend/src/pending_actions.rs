@@ -0,0 +1,272 @@
+use entity::{
+    election::Entity as Election,
+    pending_action::{self, Entity as PendingAction},
+};
+use sea_orm::{prelude::*, sea_query::Expr, DatabaseTransaction, QueryOrder, QuerySelect, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::{
+    audit::{self, AuditAction},
+    auth_utils::AdminUser,
+    clock::Clock,
+    dtos::{
+        AdminRoleDto, EditNominationDto, PendingActionKindDto, PendingActionStatusDto,
+        RescheduleElectionDto,
+    },
+    errors::AppError,
+    services::fenix::FenixService,
+    settings::parse_env_number,
+};
+
+/// How long a second admin has to approve a proposed action, unless
+/// overridden by the PENDING_ACTION_DEADLINE_HOURS env variable.
+const DEFAULT_DEADLINE_HOURS: i64 = 48;
+
+/// Admin operations that can't be undone, or that affect elections that are
+/// already being voted on, and so must be approved by a second admin before
+/// being executed.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SensitiveAction {
+    RemoveAdmin {
+        username: String,
+    },
+    InvalidateNomination {
+        election: i32,
+        nomination: EditNominationDto,
+    },
+    DeleteElection {
+        election: i32,
+    },
+    RescheduleElection {
+        election: i32,
+        periods: RescheduleElectionDto,
+    },
+}
+
+impl SensitiveAction {
+    pub fn kind(&self) -> PendingActionKindDto {
+        match self {
+            Self::RemoveAdmin { .. } => PendingActionKindDto::RemoveAdmin,
+            Self::InvalidateNomination { .. } => PendingActionKindDto::InvalidateNomination,
+            Self::DeleteElection { .. } => PendingActionKindDto::DeleteElection,
+            Self::RescheduleElection { .. } => PendingActionKindDto::RescheduleElection,
+        }
+    }
+
+    /// What the action applies to, as recorded in the audit log.
+    pub fn target(&self) -> String {
+        match self {
+            Self::RemoveAdmin { username } => username.clone(),
+            Self::InvalidateNomination {
+                election,
+                nomination,
+            } => format!("{}/{}", election, nomination.username),
+            Self::DeleteElection { election } | Self::RescheduleElection { election, .. } => {
+                election.to_string()
+            }
+        }
+    }
+
+    /// Check that the admin is allowed to propose, approve or reject this action:
+    /// only super admins can manage admins, and elections can only be managed by
+    /// admins of their degree.
+    pub async fn authorize<C: ConnectionTrait>(
+        &self,
+        admin: &AdminUser,
+        conn: &C,
+        fenix_service: &FenixService,
+    ) -> Result<(), AppError> {
+        let election_id = match self {
+            Self::RemoveAdmin { .. } => {
+                return (admin.role == AdminRoleDto::SuperAdmin)
+                    .then_some(())
+                    .ok_or(AppError::Forbidden);
+            }
+            Self::InvalidateNomination { election, .. }
+            | Self::DeleteElection { election }
+            | Self::RescheduleElection { election, .. } => *election,
+        };
+
+        let election = Election::find_by_id(election_id)
+            .one(conn)
+            .await?
+            .ok_or(AppError::UnknownElection)?;
+        admin
+            .assert_can_manage_degree(fenix_service, &election.degree_id)
+            .await
+    }
+}
+
+/// How long a second admin has to approve a proposed action.
+#[derive(Clone, Copy)]
+pub struct ApprovalDeadline(chrono::Duration);
+
+impl ApprovalDeadline {
+    /// Read the deadline, in hours, from the PENDING_ACTION_DEADLINE_HOURS env variable.
+    pub fn from_env() -> Result<ApprovalDeadline, String> {
+        let hours = parse_env_number("PENDING_ACTION_DEADLINE_HOURS", DEFAULT_DEADLINE_HOURS)?;
+        if hours <= 0 {
+            return Err(
+                "Environment variable PENDING_ACTION_DEADLINE_HOURS must be positive".to_string(),
+            );
+        }
+
+        Ok(ApprovalDeadline(chrono::Duration::hours(hours)))
+    }
+}
+
+impl Default for ApprovalDeadline {
+    fn default() -> Self {
+        ApprovalDeadline(chrono::Duration::hours(DEFAULT_DEADLINE_HOURS))
+    }
+}
+
+/// Propose an action, which is only executed once approved by another admin.
+/// The same action can't be proposed again while it is waiting for approval.
+pub async fn propose(
+    txn: &DatabaseTransaction,
+    proposer: &str,
+    action: &SensitiveAction,
+    clock: &Clock,
+    deadline: ApprovalDeadline,
+) -> Result<pending_action::Model, AppError> {
+    let now = clock.now().naive_utc();
+    let kind = action.kind();
+    let target = action.target();
+
+    let duplicate = PendingAction::find()
+        .filter(pending_action::Column::Kind.eq(kind.as_str()))
+        .filter(pending_action::Column::Target.eq(&target))
+        .filter(pending_action::Column::Status.eq(PendingActionStatusDto::Pending.as_str()))
+        .filter(pending_action::Column::ExpiresAt.gt(now))
+        .one(txn)
+        .await?;
+    if duplicate.is_some() {
+        return Err(AppError::DuplicatePendingAction);
+    }
+
+    let pending_action = pending_action::ActiveModel {
+        kind: Set(kind.as_str().to_string()),
+        target: Set(target),
+        action: Set(json!(action).to_string()),
+        proposed_by: Set(proposer.to_string()),
+        proposed_at: Set(now),
+        expires_at: Set(now + deadline.0),
+        status: Set(PendingActionStatusDto::Pending.as_str().to_string()),
+        decided_by: Set(None),
+        decided_at: Set(None),
+        ..Default::default()
+    }
+    .insert(txn)
+    .await?;
+
+    audit::record(
+        txn,
+        proposer,
+        AuditAction::ProposeAction,
+        &pending_action.id.to_string(),
+        None,
+        Some(pending_action_to_json(&pending_action)),
+    )
+    .await?;
+
+    Ok(pending_action)
+}
+
+/// Get an action that is still waiting for approval, along with what it does.
+pub async fn find_pending(
+    txn: &DatabaseTransaction,
+    id: i32,
+    clock: &Clock,
+) -> Result<(pending_action::Model, SensitiveAction), AppError> {
+    let pending_action = PendingAction::find_by_id(id)
+        .one(txn)
+        .await?
+        .ok_or(AppError::UnknownPendingAction)?;
+
+    if pending_action.status != PendingActionStatusDto::Pending.as_str() {
+        return Err(AppError::PendingActionDecided);
+    }
+    if pending_action.expires_at <= clock.now().naive_utc() {
+        return Err(AppError::PendingActionExpired);
+    }
+
+    let action =
+        serde_json::from_str(&pending_action.action).map_err(|err| DbErr::Json(err.to_string()))?;
+
+    Ok((pending_action, action))
+}
+
+/// Approve or reject an action that is waiting for approval.
+/// Executing an approved action is up to the caller, in the same transaction.
+pub async fn decide(
+    txn: &DatabaseTransaction,
+    pending_action: pending_action::Model,
+    decider: &str,
+    approved: bool,
+    clock: &Clock,
+) -> Result<(), AppError> {
+    let (status, audit_action) = if approved {
+        (PendingActionStatusDto::Approved, AuditAction::ApproveAction)
+    } else {
+        (PendingActionStatusDto::Rejected, AuditAction::RejectAction)
+    };
+    let now = clock.now().naive_utc();
+
+    // only update the action if it is still pending, so that it can't be decided twice
+    let result = PendingAction::update_many()
+        .col_expr(pending_action::Column::Status, Expr::value(status.as_str()))
+        .col_expr(pending_action::Column::DecidedBy, Expr::value(decider))
+        .col_expr(pending_action::Column::DecidedAt, Expr::value(now))
+        .filter(pending_action::Column::Id.eq(pending_action.id))
+        .filter(pending_action::Column::Status.eq(PendingActionStatusDto::Pending.as_str()))
+        .exec(txn)
+        .await?;
+    if result.rows_affected != 1 {
+        return Err(AppError::PendingActionDecided);
+    }
+
+    let decided_action = pending_action::Model {
+        status: status.as_str().to_string(),
+        decided_by: Some(decider.to_string()),
+        decided_at: Some(now),
+        ..pending_action.clone()
+    };
+    audit::record(
+        txn,
+        decider,
+        audit_action,
+        &pending_action.id.to_string(),
+        Some(pending_action_to_json(&pending_action)),
+        Some(pending_action_to_json(&decided_action)),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Get the most recently proposed actions, whatever their status.
+pub async fn list<C: ConnectionTrait>(
+    conn: &C,
+    limit: u64,
+) -> Result<Vec<pending_action::Model>, AppError> {
+    Ok(PendingAction::find()
+        .order_by_desc(pending_action::Column::Id)
+        .limit(limit)
+        .all(conn)
+        .await?)
+}
+
+fn pending_action_to_json(pending_action: &pending_action::Model) -> Value {
+    json!({
+        "kind": pending_action.kind,
+        "target": pending_action.target,
+        "action": serde_json::from_str::<Value>(&pending_action.action).ok(),
+        "proposedBy": pending_action.proposed_by,
+        "expiresAt": pending_action.expires_at.and_utc(),
+        "status": pending_action.status,
+        "decidedBy": pending_action.decided_by,
+    })
+}
@@ -0,0 +1,105 @@
+use axum::{extract::State, Json};
+use entity::election::{self, Entity as Election};
+use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, QuerySelect};
+use tracing::info;
+
+use crate::{
+    auth_utils::{AdminUser, SuperAdmin},
+    dtos::{AcademicYearDto, PinAcademicYearDto},
+    errors::AppError,
+    services::fenix::FenixService,
+};
+
+pub async fn get_academic_year(
+    _admin: AdminUser,
+    State(ref fenix_service): State<FenixService>,
+) -> Result<Json<AcademicYearDto>, AppError> {
+    Ok(Json(get_academic_year_dto(fenix_service).await?))
+}
+
+/// List all academic years that have elections, most recent first.
+pub async fn list_academic_years(
+    _admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<String>>, AppError> {
+    let academic_years = Election::find()
+        .select_only()
+        .column(election::Column::AcademicYear)
+        .distinct()
+        .order_by_desc(election::Column::AcademicYear)
+        .into_tuple()
+        .all(conn)
+        .await?;
+
+    Ok(Json(academic_years))
+}
+
+pub async fn pin_academic_year(
+    SuperAdmin(admin): SuperAdmin,
+    State(ref fenix_service): State<FenixService>,
+    Json(pin_dto): Json<PinAcademicYearDto>,
+) -> Result<Json<AcademicYearDto>, AppError> {
+    let academic_year = pin_dto.academic_year.map(|year| year.trim().to_string());
+    if let Some(year) = &academic_year {
+        if !is_valid_academic_year(year) {
+            return Err(AppError::BadInput("error.academic-year.invalid"));
+        }
+    }
+
+    fenix_service
+        .set_pinned_year(academic_year.clone(), &admin.username)
+        .await?;
+    match academic_year {
+        Some(year) => info!("{} pinned the academic year to {}", admin.username, year),
+        None => info!("{} unpinned the academic year", admin.username),
+    }
+
+    Ok(Json(get_academic_year_dto(fenix_service).await?))
+}
+
+async fn get_academic_year_dto(fenix_service: &FenixService) -> Result<AcademicYearDto, AppError> {
+    let pinned_year = fenix_service.get_pinned_year().await?;
+    // Fénix being unavailable shouldn't prevent admins from pinning the year
+    let fenix_year = fenix_service.get_fenix_active_year().await.ok();
+
+    Ok(AcademicYearDto {
+        academic_year: pinned_year
+            .clone()
+            .or(fenix_year.clone())
+            .ok_or(AppError::FenixError)?,
+        fenix_academic_year: fenix_year,
+        pinned: pinned_year.is_some(),
+    })
+}
+
+/// Check whether the year is in the same format as Fénix's, such as "2023/2024".
+fn is_valid_academic_year(year: &str) -> bool {
+    let Some((begin, end)) = year.split_once('/') else {
+        return false;
+    };
+    let parse_year = |year: &str| {
+        (year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()))
+            .then(|| year.parse::<u32>().ok())
+            .flatten()
+    };
+
+    match (parse_year(begin), parse_year(end)) {
+        (Some(begin), Some(end)) => end == begin + 1,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_academic_year;
+
+    #[test]
+    fn test_is_valid_academic_year() {
+        assert!(is_valid_academic_year("2023/2024"));
+        assert!(!is_valid_academic_year("2023/2025"));
+        assert!(!is_valid_academic_year("2023-2024"));
+        assert!(!is_valid_academic_year("23/24"));
+        assert!(!is_valid_academic_year("+123/+124"));
+        assert!(!is_valid_academic_year(""));
+    }
+}
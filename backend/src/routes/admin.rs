@@ -1,26 +1,42 @@
+use std::env;
+
 use axum::{
     extract::{Path, State},
     http::StatusCode,
+    response::Response,
     Extension, Json,
 };
 use axum_sessions::SessionHandle;
 
-use entity::admin::{self, Entity as Admin};
-use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, Set, TransactionTrait};
+use entity::{
+    admin::{self, Entity as Admin},
+    api_token::{self, Entity as ApiToken},
+};
+use sea_orm::{
+    prelude::*, DatabaseConnection, DatabaseTransaction, QueryOrder, Set, TransactionTrait,
+};
+
+use serde_json::{json, Value};
 
 use crate::{
-    auth_utils,
-    dtos::{AddAdminDto, AdminDto},
+    audit::{self, AuditAction},
+    auth_utils::{self, AdminUser, SuperAdmin},
+    clock::Clock,
+    dtos::{AddAdminDto, AdminDto, AdminRoleDto},
     errors::AppError,
+    pending_actions::{self, ApprovalDeadline, SensitiveAction},
+    services::fenix::FenixService,
 };
 
+use super::pending_actions::pending_action_accepted;
+
+/// Env variable with the username of the first admin, made on startup.
+const BOOTSTRAP_ADMIN_ENV: &str = "BOOTSTRAP_ADMIN";
+
 pub async fn list_admins(
-    Extension(ref session_handle): Extension<SessionHandle>,
+    _admin: AdminUser,
     State(ref conn): State<DatabaseConnection>,
 ) -> Result<Json<Vec<AdminDto>>, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
     let admins = Admin::find()
         .order_by_asc(admin::Column::DateAdded)
         .all(conn)
@@ -33,20 +49,39 @@ pub async fn list_admins(
 }
 
 pub async fn add_admin(
-    Extension(ref session_handle): Extension<SessionHandle>,
+    SuperAdmin(actor): SuperAdmin,
     State(ref conn): State<DatabaseConnection>,
+    State(ref fenix_service): State<FenixService>,
     Json(admin_dto): Json<AddAdminDto>,
 ) -> Result<StatusCode, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
+    create_admin(conn, fenix_service, &actor.username, admin_dto).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
 
+/// Make a user an admin, with the given role, which is also used by the management CLI.
+pub async fn create_admin(
+    conn: &DatabaseConnection,
+    fenix_service: &FenixService,
+    actor: &str,
+    admin_dto: AddAdminDto,
+) -> Result<admin::Model, AppError> {
     // validate username
     let username = admin_dto.username.trim();
     if username.is_empty() {
         return Err(AppError::BadInput("error.username.empty"));
     }
 
-    let admin = Admin::find_by_id(username).one(conn).await?;
+    let scope = admin_dto
+        .scope
+        .as_deref()
+        .map(str::trim)
+        .filter(|scope| !scope.is_empty());
+    validate_scope(fenix_service, admin_dto.role, scope).await?;
+
+    let txn = conn.begin().await?;
+
+    let admin = Admin::find_by_id(username).one(&txn).await?;
     if admin.is_some() {
         return Err(AppError::DuplicateAdmin);
     }
@@ -56,20 +91,33 @@ pub async fn add_admin(
     let admin = admin::ActiveModel {
         username: Set(username.to_string()),
         date_added: Set(now),
+        role: Set(admin_dto.role.as_str().to_string()),
+        scope: Set(scope.map(str::to_string)),
     };
-    admin.insert(conn).await?;
+    let admin = admin.insert(&txn).await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    audit::record(
+        &txn,
+        actor,
+        AuditAction::AddAdmin,
+        username,
+        None,
+        Some(admin_to_json(&admin)),
+    )
+    .await?;
+
+    txn.commit().await?;
+    Ok(admin)
 }
 
+/// Propose removing an admin, which must be approved by another super admin.
 pub async fn remove_admin(
     Path(username): Path<String>,
-    Extension(ref session_handle): Extension<SessionHandle>,
+    SuperAdmin(actor): SuperAdmin,
     State(ref conn): State<DatabaseConnection>,
-) -> Result<StatusCode, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
+    State(ref clock): State<Clock>,
+    State(approval_deadline): State<ApprovalDeadline>,
+) -> Result<Response, AppError> {
     // validate username
     if username.is_empty() {
         return Err(AppError::BadInput("error.username.empty"));
@@ -77,17 +125,76 @@ pub async fn remove_admin(
 
     let txn = conn.begin().await?;
 
-    let count = Admin::find().count(&txn).await?;
-    if count <= 1 {
-        return Err(AppError::NotEnoughAdmins);
-    }
+    find_removable_admin(&txn, &username).await?;
+    let pending_action = pending_actions::propose(
+        &txn,
+        &actor.username,
+        &SensitiveAction::RemoveAdmin { username },
+        clock,
+        approval_deadline,
+    )
+    .await?;
 
-    let res = Admin::delete_by_id(username).exec(&txn).await?;
     txn.commit().await?;
-    match res.rows_affected {
-        0 => Err(AppError::UnknownAdmin),
-        _ => Ok(StatusCode::NO_CONTENT),
+    pending_action_accepted(pending_action, clock)
+}
+
+/// Remove an admin, once the removal has been approved, or when done from the management CLI.
+pub async fn delete_admin(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    username: &str,
+) -> Result<(), AppError> {
+    let admin = find_removable_admin(txn, username).await?;
+    Admin::delete_by_id(username).exec(txn).await?;
+
+    // otherwise, the tokens would be usable again if the user was made an admin again
+    ApiToken::delete_many()
+        .filter(api_token::Column::Username.eq(username))
+        .exec(txn)
+        .await?;
+
+    audit::record(
+        txn,
+        actor,
+        AuditAction::RemoveAdmin,
+        username,
+        Some(admin_to_json(&admin)),
+        None,
+    )
+    .await
+}
+
+async fn find_removable_admin(
+    txn: &DatabaseTransaction,
+    username: &str,
+) -> Result<admin::Model, AppError> {
+    let admin = Admin::find_by_id(username)
+        .one(txn)
+        .await?
+        .ok_or(AppError::UnknownAdmin)?;
+
+    // there must always be someone who can manage admins
+    if admin.role == AdminRoleDto::SuperAdmin.as_str() {
+        let super_admins = Admin::find()
+            .filter(admin::Column::Role.eq(AdminRoleDto::SuperAdmin.as_str()))
+            .count(txn)
+            .await?;
+        if super_admins <= 1 {
+            return Err(AppError::NotEnoughAdmins);
+        }
     }
+
+    Ok(admin)
+}
+
+/// Whether the first admin can be set up by whoever logs in first. This is always allowed in
+/// debug builds, whereas release builds refuse it unless the ADMIN_SETUP env variable is set
+/// to "true", so that admins are otherwise only ever added from the management CLI.
+/// It is disabled in any case when a BOOTSTRAP_ADMIN is given.
+pub fn is_setup_allowed() -> bool {
+    (cfg!(debug_assertions) || env::var("ADMIN_SETUP").is_ok_and(|value| value == "true"))
+        && env::var(BOOTSTRAP_ADMIN_ENV).is_err()
 }
 
 pub async fn setup_first_admin(
@@ -99,19 +206,88 @@ pub async fn setup_first_admin(
 
     let txn = conn.begin().await?;
 
-    let count = Admin::find().count(&txn).await?;
-    if count != 0 {
+    if !insert_first_admin(&txn, &user.username, &user.username).await? {
         return Err(AppError::Forbidden);
     }
 
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Make the user given by the BOOTSTRAP_ADMIN env variable a super admin,
+/// as long as there are no admins yet. Returns whether they were made an admin.
+pub async fn bootstrap_admin(conn: &DatabaseConnection) -> Result<bool, AppError> {
+    let Ok(username) = env::var(BOOTSTRAP_ADMIN_ENV) else {
+        return Ok(false);
+    };
+
+    let txn = conn.begin().await?;
+    let inserted = insert_first_admin(&txn, audit::SYSTEM_ACTOR, username.trim()).await?;
+    txn.commit().await?;
+
+    Ok(inserted)
+}
+
+/// Make the user a super admin, unless there are admins already.
+async fn insert_first_admin(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    username: &str,
+) -> Result<bool, AppError> {
+    let count = Admin::find().count(txn).await?;
+    if count != 0 {
+        return Ok(false);
+    }
+
     let now = chrono::offset::Utc::now().naive_utc();
 
     let admin = admin::ActiveModel {
-        username: Set(user.username.to_string()),
+        username: Set(username.to_string()),
         date_added: Set(now),
+        role: Set(AdminRoleDto::SuperAdmin.as_str().to_string()),
+        scope: Set(None),
     };
-    admin.insert(&txn).await?;
+    let admin = admin.insert(txn).await?;
 
-    txn.commit().await?;
-    Ok(StatusCode::NO_CONTENT)
+    audit::record(
+        txn,
+        actor,
+        AuditAction::SetupFirstAdmin,
+        username,
+        None,
+        Some(admin_to_json(&admin)),
+    )
+    .await?;
+
+    Ok(true)
+}
+
+fn admin_to_json(admin: &admin::Model) -> Value {
+    json!({
+        "username": admin.username,
+        "dateAdded": admin.date_added.and_utc(),
+        "role": admin.role,
+        "scope": admin.scope,
+    })
+}
+
+/// Coordinators must be restricted to an existing degree or degree type,
+/// whereas other roles can't be restricted at all.
+async fn validate_scope(
+    fenix_service: &FenixService,
+    role: AdminRoleDto,
+    scope: Option<&str>,
+) -> Result<(), AppError> {
+    let valid = match (role, scope) {
+        (AdminRoleDto::DegreeCoordinator, Some(degree_id)) => {
+            fenix_service.get_degree(degree_id).await?.is_some()
+        }
+        (AdminRoleDto::DegreeTypeCoordinator, Some(degree_type)) => fenix_service
+            .get_degrees()
+            .await?
+            .any(|degree| degree.degree_type.values().any(|name| name == degree_type)),
+        (role, scope) => !role.is_scoped() && scope.is_none(),
+    };
+
+    valid.then_some(()).ok_or(AppError::InvalidAdminScope)
 }
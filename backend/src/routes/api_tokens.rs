@@ -0,0 +1,136 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use entity::api_token::{self, Entity as ApiToken};
+use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, Set, TransactionTrait};
+
+use serde_json::{json, Value};
+
+use crate::{
+    api_tokens,
+    audit::{self, AuditAction},
+    auth_utils::AdminUser,
+    dtos::{AdminRoleDto, ApiTokenDto, ApiTokenScopeDto, CreateApiTokenDto, CreatedApiTokenDto},
+    errors::AppError,
+};
+
+/// List the tokens of the admin, or of every admin, for super admins.
+pub async fn list_api_tokens(
+    admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<ApiTokenDto>>, AppError> {
+    let mut query = ApiToken::find().order_by_desc(api_token::Column::CreatedAt);
+    if admin.role != AdminRoleDto::SuperAdmin {
+        query = query.filter(api_token::Column::Username.eq(&admin.username));
+    }
+
+    let tokens = query
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(ApiTokenDto::from_entity)
+        .collect::<Result<_, _>>()?;
+
+    Ok(Json(tokens))
+}
+
+/// Create a token that acts as the admin, with whatever role they have when it is used.
+/// Tokens can't be used to create other tokens, so that a leaked token can't outlive its expiry.
+pub async fn create_api_token(
+    admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+    Json(token_dto): Json<CreateApiTokenDto>,
+) -> Result<Json<CreatedApiTokenDto>, AppError> {
+    admin.assert_session()?;
+
+    let name = token_dto.name.trim();
+    if name.is_empty() {
+        return Err(AppError::BadInput("error.api-token.name.empty"));
+    }
+    if token_dto.scopes.is_empty() {
+        return Err(AppError::BadInput("error.api-token.scopes.empty"));
+    }
+
+    let now = chrono::Utc::now();
+    if token_dto.expires_at <= now {
+        return Err(AppError::BadInput("error.api-token.expiry.invalid"));
+    }
+
+    let mut scopes = token_dto.scopes;
+    scopes.sort_by_key(|scope| scope.as_str());
+    scopes.dedup();
+    let secret = api_tokens::generate_secret();
+
+    let txn = conn.begin().await?;
+
+    let token = api_token::ActiveModel {
+        username: Set(admin.username.clone()),
+        name: Set(name.to_string()),
+        token_hash: Set(api_tokens::hash_secret(&secret)),
+        scopes: Set(ApiTokenScopeDto::join(&scopes)),
+        created_at: Set(now.naive_utc()),
+        expires_at: Set(token_dto.expires_at.naive_utc()),
+        last_used_at: Set(None),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await?;
+
+    audit::record(
+        &txn,
+        &admin.username,
+        AuditAction::CreateApiToken,
+        &token.id.to_string(),
+        None,
+        Some(api_token_to_json(&token)),
+    )
+    .await?;
+
+    txn.commit().await?;
+
+    Ok(Json(CreatedApiTokenDto {
+        token: ApiTokenDto::from_entity(token)?,
+        secret,
+    }))
+}
+
+/// Revoke one of the admin's tokens, or any token, for super admins.
+pub async fn revoke_api_token(
+    Path(id): Path<i32>,
+    admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<StatusCode, AppError> {
+    let txn = conn.begin().await?;
+
+    let token = ApiToken::find_by_id(id)
+        .one(&txn)
+        .await?
+        .filter(|token| admin.role == AdminRoleDto::SuperAdmin || token.username == admin.username)
+        .ok_or(AppError::UnknownApiToken)?;
+    ApiToken::delete_by_id(id).exec(&txn).await?;
+
+    audit::record(
+        &txn,
+        &admin.username,
+        AuditAction::RevokeApiToken,
+        &id.to_string(),
+        Some(api_token_to_json(&token)),
+        None,
+    )
+    .await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn api_token_to_json(token: &api_token::Model) -> Value {
+    json!({
+        "username": token.username,
+        "name": token.name,
+        "scopes": token.scopes,
+        "expiresAt": token.expires_at.and_utc(),
+    })
+}
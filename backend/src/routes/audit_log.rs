@@ -0,0 +1,37 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use sea_orm::DatabaseConnection;
+
+use crate::{
+    audit,
+    auth_utils::AdminUser,
+    dtos::{AuditLogEntryDto, AuditLogQuery, AuditLogVerificationDto},
+    errors::AppError,
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 50;
+const MAX_PAGE_SIZE: u64 = 500;
+
+pub async fn list_audit_log(
+    _admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+    Query(query): Query<AuditLogQuery>,
+) -> Result<Json<Vec<AuditLogEntryDto>>, AppError> {
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).min(MAX_PAGE_SIZE);
+    let entries = audit::list(conn, query.before, limit)
+        .await?
+        .into_iter()
+        .map(AuditLogEntryDto::from_entity)
+        .collect();
+
+    Ok(Json(entries))
+}
+
+pub async fn verify_audit_log(
+    _admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<AuditLogVerificationDto>, AppError> {
+    Ok(Json(audit::verify(conn).await?))
+}
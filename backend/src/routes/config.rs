@@ -1,4 +1,8 @@
-use crate::{dtos::AppConfigDto, errors::AppError, services::fenix::FenixService};
+use crate::{
+    dtos::AppConfigDto,
+    errors::AppError,
+    services::{fenix::FenixService, identity::SharedIdentityProvider},
+};
 use axum::{extract::State, Json};
 
 use entity::admin::Entity as Admin;
@@ -6,14 +10,26 @@ use sea_orm::{prelude::*, DatabaseConnection};
 
 pub async fn get_config(
     State(ref fenix_service): State<FenixService>,
+    State(ref identity_provider): State<SharedIdentityProvider>,
     State(ref conn): State<DatabaseConnection>,
 ) -> Result<Json<AppConfigDto>, AppError> {
     let fenix_config = fenix_service.to_dto();
+    let login_url = identity_provider.login_url().await?;
 
-    let is_setup = Admin::find().count(conn).await? > 0;
+    // there is nothing to set up from the web app if admins can only be added from the CLI
+    let is_setup = !super::admin::is_setup_allowed() || Admin::find().count(conn).await? > 0;
+    let pinned_academic_year = fenix_service.get_pinned_year().await?;
+
+    #[cfg(feature = "dev-login")]
+    let dev_login = super::dev_login::is_allowed();
+    #[cfg(not(feature = "dev-login"))]
+    let dev_login = false;
 
     Ok(Json(AppConfigDto {
         fenix: fenix_config,
+        login_url,
+        dev_login,
         is_setup,
+        pinned_academic_year,
     }))
 }
@@ -1,5 +1,8 @@
-use axum::{extract::State, Extension, Json};
-use axum_sessions::SessionHandle;
+use axum::{
+    extract::{Query, State},
+    response::{IntoResponse, Response},
+    Json,
+};
 
 use entity::election::{self, Entity as Election};
 use sea_orm::{prelude::*, DatabaseConnection};
@@ -7,46 +10,52 @@ use sea_orm::{prelude::*, DatabaseConnection};
 use futures::stream::{self, StreamExt};
 
 use crate::{
-    auth_utils,
-    dtos::{DegreeDto, DegreeElectionsDto, ElectionDto},
+    auth_utils::{AdminUser, Viewer},
+    clock::Clock,
+    dtos::{
+        AcademicYearQuery, DegreeDto, DegreeElectionsDto, ElectionDto, ObserverDegreeElectionsDto,
+    },
     errors::AppError,
     services::fenix::FenixService,
 };
 
 pub async fn list_degrees(
-    Extension(ref session_handle): Extension<SessionHandle>,
-    State(ref conn): State<DatabaseConnection>,
+    _admin: AdminUser,
     State(ref fenix_service): State<FenixService>,
 ) -> Result<Json<Vec<DegreeDto>>, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
     let degrees = fenix_service.get_degrees().await?.collect();
 
     Ok(Json(degrees))
 }
 
 pub async fn list_degrees_with_elections(
-    Extension(ref session_handle): Extension<SessionHandle>,
+    viewer: Viewer,
     State(ref conn): State<DatabaseConnection>,
     State(ref fenix_service): State<FenixService>,
-) -> Result<Json<Vec<DegreeElectionsDto>>, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
-    let degrees = fenix_service.get_degrees().await?;
-    let active_year = fenix_service.get_active_year().await?;
+    State(ref clock): State<Clock>,
+    Query(query): Query<AcademicYearQuery>,
+) -> Result<Response, AppError> {
+    // observers can follow elections of every degree, but coordinators only their own
+    let degrees = fenix_service
+        .get_degrees()
+        .await?
+        .filter(|degree| match &viewer {
+            Viewer::Admin(admin) => admin.can_view_degree(Some(degree)),
+            Viewer::Observer(_) => true,
+        });
+    let academic_year = query.or_active_year(fenix_service).await?;
+    let now = clock.now();
 
-    let degrees_with_elections = stream::iter(degrees.into_iter())
+    let degrees_with_elections: Vec<DegreeElectionsDto> = stream::iter(degrees)
         .then(|degree| async {
             Ok(DegreeElectionsDto {
                 elections: Election::find()
                     .filter(election::Column::DegreeId.eq(degree.id.clone()))
-                    .filter(election::Column::AcademicYear.eq(active_year.clone()))
+                    .filter(election::Column::AcademicYear.eq(academic_year.clone()))
                     .all(conn)
                     .await?
                     .into_iter()
-                    .map(ElectionDto::from_entity)
+                    .map(|election| ElectionDto::from_entity(election, now))
                     .collect::<Result<_, _>>()?,
                 degree,
             })
@@ -56,5 +65,14 @@ pub async fn list_degrees_with_elections(
         .into_iter()
         .collect::<Result<_, AppError>>()?;
 
-    Ok(Json(degrees_with_elections))
+    Ok(match viewer {
+        Viewer::Admin(_) => Json(degrees_with_elections).into_response(),
+        Viewer::Observer(_) => Json(
+            degrees_with_elections
+                .into_iter()
+                .map(ObserverDegreeElectionsDto::from_degree_elections_dto)
+                .collect::<Vec<_>>(),
+        )
+        .into_response(),
+    })
 }
@@ -0,0 +1,68 @@
+use std::env;
+
+use axum::{extract::State, headers::UserAgent, Extension, Json, TypedHeader};
+use axum_sessions::SessionHandle;
+use sea_orm::DatabaseConnection;
+use tracing::warn;
+
+use crate::{
+    clock::Clock,
+    dtos::{AuthDto, DevLoginDto, UserDto},
+    errors::AppError,
+    services::{fenix::FenixService, identity::OAuthResponse},
+};
+
+use super::login::start_session;
+
+/// The development login is always allowed in debug builds, whereas release builds
+/// refuse it unless the DEV_LOGIN env variable is set to "true".
+pub fn is_allowed() -> bool {
+    cfg!(debug_assertions) || env::var("DEV_LOGIN").is_ok_and(|value| value == "true")
+}
+
+/// Log in as any user, attending whichever degrees are given, without going through Fénix.
+pub async fn dev_login(
+    State(ref fenix_service): State<FenixService>,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
+    Extension(ref session_handle): Extension<SessionHandle>,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    Json(login_dto): Json<DevLoginDto>,
+) -> Result<Json<AuthDto>, AppError> {
+    let username = login_dto.username.trim().to_string();
+    if username.is_empty() {
+        return Err(AppError::BadInput("error.username.empty"));
+    }
+    let name = login_dto
+        .name
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or(username.clone());
+
+    warn!("development login as {}", username);
+
+    // the mock Fénix server accepts usernames as access tokens,
+    // so that searching for users works as well
+    let oauth_tokens = OAuthResponse {
+        access_token: username.clone(),
+        refresh_token: username.clone(),
+    };
+    let user_details = UserDto {
+        username,
+        display_name: name.clone(),
+        name,
+        degree_entries: login_dto.degree_entries,
+    };
+
+    let auth_details = start_session(
+        user_details,
+        oauth_tokens,
+        user_agent,
+        fenix_service,
+        conn,
+        clock,
+        session_handle,
+    )
+    .await?;
+
+    Ok(Json(auth_details))
+}
@@ -2,9 +2,9 @@ use slice_group_by::GroupBy;
 use std::collections::HashMap;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{header, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Extension, Json,
 };
 use axum_sessions::SessionHandle;
@@ -18,48 +18,64 @@ use entity::{
 use futures::stream::{self, StreamExt};
 use migration::OnConflict;
 use sea_orm::{
-    prelude::*, ActiveValue, Condition, DatabaseConnection, JoinType, QueryOrder, QuerySelect,
-    RelationTrait, Set, TransactionTrait,
+    prelude::*, ActiveValue, Condition, DatabaseConnection, DatabaseTransaction, JoinType,
+    QueryOrder, QuerySelect, RelationTrait, Set, TransactionTrait,
 };
 
+use serde_json::{json, Value};
+
 use crate::{
-    auth_utils, crypto_utils,
+    audit::{self, AuditAction},
+    auth_utils::{self, AdminUser, Viewer},
+    clock::Clock,
+    crypto_utils,
     dtos::{
-        BulkCreateElectionsDto, CastVoteDto, EditNominationDto, ElectionDto,
-        ElectionWithUnverifiedNominationsDto, NominationDto, SignedPersonSearchResultDto,
-        VoteOptionDto,
+        AcademicYearQuery, BulkCreateElectionsDto, CastVoteDto, DateRangeDto, EditNominationDto,
+        ElectionDto, ElectionWithUnverifiedNominationsDto, NominationDto, ObserverElectionDto,
+        RescheduleElectionDto, SignedPersonSearchResultDto, VoteOptionDto,
     },
     election_utils::{
         get_all_results_as_csv, get_nomination_upsert_on_conflict, get_user_in_election_condition,
         is_in_candidacy_period, is_in_voting_period,
     },
     errors::AppError,
-    services::fenix::FenixService,
+    pending_actions::{self, ApprovalDeadline, SensitiveAction},
+    services::{fenix::FenixService, signing_keys::SigningKeyService},
 };
 
+use super::pending_actions::pending_action_accepted;
+
 pub async fn bulk_create_elections(
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
     State(ref conn): State<DatabaseConnection>,
     State(ref fenix_service): State<FenixService>,
     Json(elections_dto): Json<BulkCreateElectionsDto>,
 ) -> Result<StatusCode, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
+    for degree in &elections_dto.degrees {
+        admin
+            .assert_can_manage_degree(fenix_service, &degree.degree_id)
+            .await?;
+    }
+
+    create_elections(conn, fenix_service, &admin.username, elections_dto).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
 
+/// Create elections in the active academic year, which is also used to seed demo data.
+pub async fn create_elections(
+    conn: &DatabaseConnection,
+    fenix_service: &FenixService,
+    actor: &str,
+    elections_dto: BulkCreateElectionsDto,
+) -> Result<(), AppError> {
     if elections_dto.round <= 0 {
         return Err(AppError::InvalidRound);
     }
-    if elections_dto.voting_period.start >= elections_dto.voting_period.end {
-        return Err(AppError::InvalidDateRange);
-    }
-    if let Some(candidacy_period) = &elections_dto.candidacy_period {
-        if candidacy_period.start >= candidacy_period.end {
-            return Err(AppError::InvalidDateRange);
-        }
-        if candidacy_period.end >= elections_dto.voting_period.start {
-            return Err(AppError::ElectionCandidacyAfterVoting);
-        }
-    }
+    validate_periods(
+        elections_dto.candidacy_period.as_ref(),
+        &elections_dto.voting_period,
+    )?;
 
     for degree in &elections_dto.degrees {
         fenix_service
@@ -69,6 +85,7 @@ pub async fn bulk_create_elections(
     }
 
     let academic_year = fenix_service.get_active_year().await?;
+    let audit_details = json!(elections_dto);
 
     let txn = conn.begin().await?;
 
@@ -99,15 +116,218 @@ pub async fn bulk_create_elections(
         .await
         .map_err(|_| AppError::DuplicateElection)?;
 
+    audit::record(
+        &txn,
+        actor,
+        AuditAction::CreateElections,
+        &academic_year,
+        None,
+        Some(audit_details),
+    )
+    .await?;
+
     txn.commit().await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    Ok(())
+}
+
+/// Delete an election, along with its nominations and votes.
+/// Once its voting period has started, this must be approved by another admin.
+pub async fn delete_election(
+    Path(election_id): Path<i32>,
+    admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref fenix_service): State<FenixService>,
+    State(ref clock): State<Clock>,
+    State(approval_deadline): State<ApprovalDeadline>,
+) -> Result<Response, AppError> {
+    let election = admin
+        .get_manageable_election(conn, fenix_service, election_id)
+        .await?;
+
+    let txn = conn.begin().await?;
+
+    if clock.now() >= election.voting_period_start.and_utc() {
+        let pending_action = pending_actions::propose(
+            &txn,
+            &admin.username,
+            &SensitiveAction::DeleteElection {
+                election: election_id,
+            },
+            clock,
+            approval_deadline,
+        )
+        .await?;
+
+        txn.commit().await?;
+        return pending_action_accepted(pending_action, clock);
+    }
+
+    remove_election(&txn, &admin.username, election_id).await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Delete an election, once the deletion has been approved if needed.
+pub(crate) async fn remove_election(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    election_id: i32,
+) -> Result<(), AppError> {
+    let election = Election::find_by_id(election_id)
+        .one(txn)
+        .await?
+        .ok_or(AppError::UnknownElection)?;
+
+    ElectionVote::delete_many()
+        .filter(election_vote::Column::Election.eq(election_id))
+        .exec(txn)
+        .await?;
+    VoteLog::delete_many()
+        .filter(vote_log::Column::Election.eq(election_id))
+        .exec(txn)
+        .await?;
+    NominationLog::delete_many()
+        .filter(nomination_log::Column::Election.eq(election_id))
+        .exec(txn)
+        .await?;
+    Nomination::delete_many()
+        .filter(nomination::Column::Election.eq(election_id))
+        .exec(txn)
+        .await?;
+    Election::delete_by_id(election_id).exec(txn).await?;
+
+    audit::record(
+        txn,
+        actor,
+        AuditAction::DeleteElection,
+        &election_id.to_string(),
+        Some(election_to_json(&election)),
+        None,
+    )
+    .await
+}
+
+/// Change the candidacy and voting periods of an election.
+/// Once its voting period has started, this must be approved by another admin.
+pub async fn reschedule_election(
+    Path(election_id): Path<i32>,
+    admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref fenix_service): State<FenixService>,
+    State(ref clock): State<Clock>,
+    State(approval_deadline): State<ApprovalDeadline>,
+    Json(periods): Json<RescheduleElectionDto>,
+) -> Result<Response, AppError> {
+    let election = admin
+        .get_manageable_election(conn, fenix_service, election_id)
+        .await?;
+
+    validate_periods(periods.candidacy_period.as_ref(), &periods.voting_period)?;
+
+    let txn = conn.begin().await?;
+
+    if clock.now() >= election.voting_period_start.and_utc() {
+        let pending_action = pending_actions::propose(
+            &txn,
+            &admin.username,
+            &SensitiveAction::RescheduleElection {
+                election: election_id,
+                periods,
+            },
+            clock,
+            approval_deadline,
+        )
+        .await?;
+
+        txn.commit().await?;
+        return pending_action_accepted(pending_action, clock);
+    }
+
+    apply_election_periods(&txn, &admin.username, election_id, periods).await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Change the periods of an election, once the change has been approved if needed.
+pub(crate) async fn apply_election_periods(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    election_id: i32,
+    periods: RescheduleElectionDto,
+) -> Result<(), AppError> {
+    let previous_election = Election::find_by_id(election_id)
+        .one(txn)
+        .await?
+        .ok_or(AppError::UnknownElection)?;
+
+    let election = election::ActiveModel {
+        id: ActiveValue::Unchanged(election_id),
+        candidacy_period_start: Set(periods
+            .candidacy_period
+            .as_ref()
+            .map(|range| range.start.naive_utc())),
+        candidacy_period_end: Set(periods
+            .candidacy_period
+            .as_ref()
+            .map(|range| range.end.naive_utc())),
+        voting_period_start: Set(periods.voting_period.start.naive_utc()),
+        voting_period_end: Set(periods.voting_period.end.naive_utc()),
+        ..Default::default()
+    }
+    .update(txn)
+    .await?;
+
+    audit::record(
+        txn,
+        actor,
+        AuditAction::RescheduleElection,
+        &election_id.to_string(),
+        Some(election_to_json(&previous_election)),
+        Some(election_to_json(&election)),
+    )
+    .await
+}
+
+fn validate_periods(
+    candidacy_period: Option<&DateRangeDto>,
+    voting_period: &DateRangeDto,
+) -> Result<(), AppError> {
+    if voting_period.start >= voting_period.end {
+        return Err(AppError::InvalidDateRange);
+    }
+    if let Some(candidacy_period) = candidacy_period {
+        if candidacy_period.start >= candidacy_period.end {
+            return Err(AppError::InvalidDateRange);
+        }
+        if candidacy_period.end >= voting_period.start {
+            return Err(AppError::ElectionCandidacyAfterVoting);
+        }
+    }
+
+    Ok(())
+}
+
+fn election_to_json(election: &election::Model) -> Value {
+    json!({
+        "degreeId": election.degree_id,
+        "curricularYear": election.curricular_year,
+        "academicYear": election.academic_year,
+        "round": election.round,
+        "candidacyPeriodStart": election.candidacy_period_start.map(|date| date.and_utc()),
+        "candidacyPeriodEnd": election.candidacy_period_end.map(|date| date.and_utc()),
+        "votingPeriodStart": election.voting_period_start.and_utc(),
+        "votingPeriodEnd": election.voting_period_end.and_utc(),
+    })
 }
 
 pub async fn get_election(
     Path(election_id): Path<i32>,
     Extension(ref session_handle): Extension<SessionHandle>,
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
     State(ref fenix_service): State<FenixService>,
 ) -> Result<Json<ElectionDto>, AppError> {
     let user = auth_utils::get_user(session_handle).await?;
@@ -132,20 +352,24 @@ pub async fn get_election(
     txn.commit().await?;
 
     Ok(Json(
-        ElectionDto::from_entity_for_user(election, fenix_service, nominations > 0, votes > 0)
-            .await?,
+        ElectionDto::from_entity_for_user(
+            election,
+            clock.now(),
+            fenix_service,
+            nominations > 0,
+            votes > 0,
+        )
+        .await?,
     ))
 }
 
 pub async fn get_election_details(
     Path(election_id): Path<i32>,
-    Extension(ref session_handle): Extension<SessionHandle>,
+    viewer: Viewer,
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
     State(ref fenix_service): State<FenixService>,
-) -> Result<Json<ElectionDto>, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
+) -> Result<Response, AppError> {
     let txn = conn
         .begin_with_config(None, Some(sea_orm::AccessMode::ReadOnly))
         .await?;
@@ -168,8 +392,16 @@ pub async fn get_election_details(
 
     txn.commit().await?;
 
+    // observers can follow elections of every degree, but coordinators only their own
+    if let Viewer::Admin(admin) = &viewer {
+        admin
+            .assert_can_view_degree(fenix_service, &election.degree_id)
+            .await?;
+    }
+
     // convert nominations to dto, and only shows votes if election has ended
-    let has_ended = chrono::Utc::now() > election.voting_period_end.and_utc();
+    let now = clock.now();
+    let has_ended = now > election.voting_period_end.and_utc();
     let nominations = nominations
         .into_iter()
         .map(|(nomination, vote_opt)| {
@@ -180,22 +412,32 @@ pub async fn get_election_details(
         })
         .collect();
 
-    // don't show total votes if election is still on-going
-    let total_votes = has_ended.then_some(
-        total_votes
-            .try_into()
-            .expect("total votes should fit in a 32-bit integer"),
-    );
-
-    Ok(Json(
-        ElectionDto::from_entity_for_admin(election, fenix_service, nominations, total_votes)
-            .await?,
-    ))
+    // turnout can be followed while the election is on-going,
+    // since it doesn't tell anything about who is winning
+    let total_votes = total_votes
+        .try_into()
+        .expect("total votes should fit in a 32-bit integer");
+
+    let election = ElectionDto::from_entity_for_admin(
+        election,
+        now,
+        fenix_service,
+        nominations,
+        Some(total_votes),
+    )
+    .await?;
+    Ok(match viewer {
+        Viewer::Admin(_) => Json(election).into_response(),
+        Viewer::Observer(_) => {
+            Json(ObserverElectionDto::from_election_dto(election)).into_response()
+        }
+    })
 }
 
 pub async fn get_user_elections(
     Extension(ref session_handle): Extension<SessionHandle>,
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
     State(ref fenix_service): State<FenixService>,
 ) -> Result<Json<Vec<ElectionDto>>, AppError> {
     let user = auth_utils::get_user(session_handle).await?;
@@ -244,11 +486,13 @@ pub async fn get_user_elections(
 
     txn.commit().await?;
 
+    let now = clock.now();
     let dtos = stream::iter(elections)
         .then(|election| async {
             let election_id = election.id;
             ElectionDto::from_entity_for_user(
                 election,
+                now,
                 fenix_service,
                 nominations.binary_search(&election_id).is_ok(),
                 votes.binary_search(&election_id).is_ok(),
@@ -267,6 +511,7 @@ pub async fn self_nominate(
     Path(election_id): Path<i32>,
     Extension(ref session_handle): Extension<SessionHandle>,
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
 ) -> Result<StatusCode, AppError> {
     let user = auth_utils::get_user(session_handle).await?;
 
@@ -277,7 +522,7 @@ pub async fn self_nominate(
         .await?
         .ok_or(AppError::UnknownElection)?;
     auth_utils::can_self_nominate_on_election(&user, &election)?;
-    is_in_candidacy_period(&election)?;
+    is_in_candidacy_period(&election, clock.now())?;
 
     let nomination_log = nomination_log::ActiveModel {
         election: ActiveValue::set(election_id),
@@ -310,7 +555,8 @@ pub async fn nominate_others(
     Path(election_id): Path<i32>,
     Extension(ref session_handle): Extension<SessionHandle>,
     State(ref conn): State<DatabaseConnection>,
-    State(ref signing_key): State<[u8; 64]>,
+    State(ref clock): State<Clock>,
+    State(ref signing_key_service): State<SigningKeyService>,
     Json(nomination_dto): Json<SignedPersonSearchResultDto>,
 ) -> Result<StatusCode, AppError> {
     let user = auth_utils::get_user(session_handle).await?;
@@ -326,9 +572,18 @@ pub async fn nominate_others(
     } else {
         auth_utils::can_vote_on_election(&user, &election)?;
     }
-    is_in_candidacy_period(&election)?;
+    is_in_candidacy_period(&election, clock.now())?;
 
-    crypto_utils::validate_person_search_result(election_id, &nomination_dto, signing_key)?;
+    let keyring = signing_key_service
+        .get_keyring_for(&nomination_dto.signature)
+        .await?;
+    crypto_utils::validate_person_search_result(
+        election_id,
+        &nomination_dto,
+        &user.username,
+        clock.now(),
+        &keyring,
+    )?;
 
     let nomination_log = nomination_log::ActiveModel {
         election: ActiveValue::set(election_id),
@@ -361,6 +616,7 @@ pub async fn get_vote_options(
     Path(election_id): Path<i32>,
     Extension(ref session_handle): Extension<SessionHandle>,
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
 ) -> Result<Json<Vec<VoteOptionDto>>, AppError> {
     let user = auth_utils::get_user(session_handle).await?;
 
@@ -373,7 +629,7 @@ pub async fn get_vote_options(
         .await?
         .ok_or(AppError::UnknownElection)?;
     auth_utils::can_vote_on_election(&user, &election)?;
-    is_in_voting_period(&election)?;
+    is_in_voting_period(&election, clock.now())?;
 
     let nominations = Nomination::find()
         .filter(nomination::Column::Election.eq(election_id))
@@ -406,6 +662,7 @@ pub async fn cast_vote(
     Path(election_id): Path<i32>,
     Extension(ref session_handle): Extension<SessionHandle>,
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
     Json(vote_dto): Json<CastVoteDto>,
 ) -> Result<StatusCode, AppError> {
     let user = auth_utils::get_user(session_handle).await?;
@@ -417,7 +674,7 @@ pub async fn cast_vote(
         .await?
         .ok_or(AppError::UnknownElection)?;
     auth_utils::can_vote_on_election(&user, &election)?;
-    is_in_voting_period(&election)?;
+    is_in_voting_period(&election, clock.now())?;
 
     // don't allow voting if not all nominations have been verified
     if Nomination::find()
@@ -485,14 +742,12 @@ pub async fn cast_vote(
 }
 
 pub async fn get_unverified_nominations_count(
-    Extension(ref session_handle): Extension<SessionHandle>,
+    _viewer: Viewer,
     State(ref conn): State<DatabaseConnection>,
     State(ref fenix_service): State<FenixService>,
+    Query(query): Query<AcademicYearQuery>,
 ) -> Result<Json<HashMap<i32, i64>>, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
-    let active_year = fenix_service.get_active_year().await?;
+    let academic_year = query.or_active_year(fenix_service).await?;
 
     let election: Vec<(i32, i64)> = Nomination::find()
         .join(JoinType::InnerJoin, nomination::Relation::Election.def())
@@ -500,7 +755,7 @@ pub async fn get_unverified_nominations_count(
         .column(nomination::Column::Election)
         .column_as(nomination::Column::Username.count(), "invalid_count")
         .filter(nomination::Column::Valid.is_null())
-        .filter(election::Column::AcademicYear.eq(active_year))
+        .filter(election::Column::AcademicYear.eq(academic_year))
         .group_by(nomination::Column::Election)
         .having(Expr::expr(nomination::Column::Username.count()).gt(0))
         .into_tuple()
@@ -511,19 +766,17 @@ pub async fn get_unverified_nominations_count(
 }
 
 pub async fn get_unverified_nominations(
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
     State(ref conn): State<DatabaseConnection>,
     State(ref fenix_service): State<FenixService>,
+    Query(query): Query<AcademicYearQuery>,
 ) -> Result<Json<Vec<ElectionWithUnverifiedNominationsDto>>, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
-    let active_year = fenix_service.get_active_year().await?;
+    let academic_year = query.or_active_year(fenix_service).await?;
 
     let nominations: Vec<(nomination::Model, election::Model)> = Nomination::find()
         .find_also_related(Election)
         .filter(nomination::Column::Valid.is_null())
-        .filter(election::Column::AcademicYear.eq(active_year))
+        .filter(election::Column::AcademicYear.eq(academic_year))
         .order_by_asc(election::Column::VotingPeriodStart)
         .order_by_asc(election::Column::Id)
         .order_by_asc(nomination::Column::DisplayName)
@@ -545,9 +798,14 @@ pub async fn get_unverified_nominations(
     let mut grouped_nominations = Vec::new();
     for group in &group_by {
         let (_, election) = group.first().unwrap();
+        let degree = fenix_service.get_degree(&election.degree_id).await?;
+        if !admin.can_view_degree(degree.as_ref()) {
+            continue;
+        }
+
         let election_dto = ElectionWithUnverifiedNominationsDto {
             id: election.id,
-            degree: fenix_service.get_degree(&election.degree_id).await?,
+            degree,
             curricular_year: election.curricular_year,
             round: election.round,
             nominations: group
@@ -563,27 +821,39 @@ pub async fn get_unverified_nominations(
 
 pub async fn add_nomination(
     Path(election_id): Path<i32>,
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
     State(ref conn): State<DatabaseConnection>,
-    State(ref signing_key): State<[u8; 64]>,
+    State(ref fenix_service): State<FenixService>,
+    State(ref signing_key_service): State<SigningKeyService>,
+    State(ref clock): State<Clock>,
     Json(nomination_dto): Json<SignedPersonSearchResultDto>,
 ) -> Result<StatusCode, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
+    let _ = admin
+        .get_manageable_election(conn, fenix_service, election_id)
+        .await?;
 
     let txn = conn.begin().await?;
 
-    Election::find_by_id(election_id)
-        .one(&txn)
-        .await?
-        .ok_or(AppError::UnknownElection)?;
-
-    crypto_utils::validate_person_search_result(election_id, &nomination_dto, signing_key)?;
+    let keyring = signing_key_service
+        .get_keyring_for(&nomination_dto.signature)
+        .await?;
+    crypto_utils::validate_person_search_result(
+        election_id,
+        &nomination_dto,
+        &admin.username,
+        clock.now(),
+        &keyring,
+    )?;
+
+    let previous_nomination =
+        Nomination::find_by_id((election_id, nomination_dto.username.clone()))
+            .one(&txn)
+            .await?;
 
     let nomination = nomination::ActiveModel {
         election: ActiveValue::set(election_id),
         valid: ActiveValue::set(Some(true)),
-        username: ActiveValue::set(nomination_dto.username),
+        username: ActiveValue::set(nomination_dto.username.clone()),
         display_name: ActiveValue::set(nomination_dto.display_name),
     };
 
@@ -593,23 +863,86 @@ pub async fn add_nomination(
         .exec(&txn)
         .await?;
 
+    let nomination = Nomination::find_by_id((election_id, nomination_dto.username.clone()))
+        .one(&txn)
+        .await?;
+    audit::record(
+        &txn,
+        &admin.username,
+        AuditAction::AddNomination,
+        &format!("{}/{}", election_id, nomination_dto.username),
+        previous_nomination.as_ref().map(nomination_to_json),
+        nomination.as_ref().map(nomination_to_json),
+    )
+    .await?;
+
     txn.commit().await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Edit a nomination, e.g. to validate it.
+/// Invalidating a nomination that had already been validated must be approved by another admin.
 pub async fn edit_nomination(
     Path(election_id): Path<i32>,
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
     State(ref conn): State<DatabaseConnection>,
+    State(ref fenix_service): State<FenixService>,
+    State(ref clock): State<Clock>,
+    State(approval_deadline): State<ApprovalDeadline>,
     Json(nomination_dto): Json<EditNominationDto>,
-) -> Result<StatusCode, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
+) -> Result<Response, AppError> {
+    let _ = admin
+        .get_manageable_election(conn, fenix_service, election_id)
+        .await?;
+
+    let txn = conn.begin().await?;
+
+    let previous_nomination =
+        Nomination::find_by_id((election_id, nomination_dto.username.clone()))
+            .one(&txn)
+            .await?
+            .ok_or(AppError::UnknownNomination)?;
+
+    if previous_nomination.valid == Some(true) && nomination_dto.valid == Some(false) {
+        let pending_action = pending_actions::propose(
+            &txn,
+            &admin.username,
+            &SensitiveAction::InvalidateNomination {
+                election: election_id,
+                nomination: nomination_dto,
+            },
+            clock,
+            approval_deadline,
+        )
+        .await?;
+
+        txn.commit().await?;
+        return pending_action_accepted(pending_action, clock);
+    }
+
+    apply_nomination_edit(&txn, &admin.username, election_id, nomination_dto).await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Edit a nomination, once the edit has been approved if needed.
+pub(crate) async fn apply_nomination_edit(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    election_id: i32,
+    nomination_dto: EditNominationDto,
+) -> Result<(), AppError> {
+    let previous_nomination =
+        Nomination::find_by_id((election_id, nomination_dto.username.clone()))
+            .one(txn)
+            .await?
+            .ok_or(AppError::UnknownNomination)?;
 
     let updated_nomination = nomination::ActiveModel {
         election: ActiveValue::Unchanged(election_id),
-        username: ActiveValue::Unchanged(nomination_dto.username),
+        username: ActiveValue::Unchanged(nomination_dto.username.clone()),
         display_name: nomination_dto
             .display_name
             .map_or(ActiveValue::NotSet, ActiveValue::Set),
@@ -618,35 +951,58 @@ pub async fn edit_nomination(
             .map_or(ActiveValue::NotSet, |v| ActiveValue::Set(Some(v))),
     };
 
-    updated_nomination
-        .update(conn)
+    let nomination = updated_nomination
+        .update(txn)
         .await
         .map_err(|err| match err {
             DbErr::RecordNotUpdated => AppError::UnknownNomination,
             _ => err.into(),
         })?;
 
-    Ok(StatusCode::NO_CONTENT)
+    audit::record(
+        txn,
+        actor,
+        AuditAction::EditNomination,
+        &format!("{}/{}", election_id, nomination_dto.username),
+        Some(nomination_to_json(&previous_nomination)),
+        Some(nomination_to_json(&nomination)),
+    )
+    .await
+}
+
+fn nomination_to_json(nomination: &nomination::Model) -> Value {
+    json!({
+        "election": nomination.election,
+        "username": nomination.username,
+        "displayName": nomination.display_name,
+        "valid": nomination.valid,
+    })
 }
 
 pub async fn download_results(
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
     State(ref fenix_service): State<FenixService>,
     State(ref conn): State<DatabaseConnection>,
-    // Json(nomination_dto): Json<EditNominationDto>,
+    State(ref clock): State<Clock>,
+    Query(query): Query<AcademicYearQuery>,
 ) -> Result<impl IntoResponse, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
+    let academic_year = query.or_active_year(fenix_service).await?;
 
     let headers = [
-        (header::CONTENT_TYPE, "text/csv; charset=utf-8"),
+        (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
         (
             header::CONTENT_DISPOSITION,
-            "attachment; filename=\"election_results.csv\"",
+            format!(
+                "attachment; filename=\"election_results_{}.csv\"",
+                academic_year.replace('/', "-")
+            ),
         ),
     ];
 
-    let data = get_all_results_as_csv(conn, fenix_service).await?;
+    let data = get_all_results_as_csv(conn, fenix_service, &academic_year, clock.now(), |degree| {
+        admin.can_view_degree(degree)
+    })
+    .await?;
 
     Ok((headers, data))
 }
@@ -0,0 +1,26 @@
+use axum::{extract::State, Json};
+use tracing::info;
+
+use crate::{
+    auth_utils::{AdminUser, SuperAdmin},
+    dtos::FenixSnapshotDto,
+    errors::AppError,
+    services::fenix::FenixService,
+};
+
+pub async fn get_fenix_snapshot(
+    _admin: AdminUser,
+    State(ref fenix_service): State<FenixService>,
+) -> Result<Json<FenixSnapshotDto>, AppError> {
+    Ok(Json(fenix_service.get_snapshots().await?))
+}
+
+pub async fn refresh_fenix_snapshot(
+    SuperAdmin(admin): SuperAdmin,
+    State(ref fenix_service): State<FenixService>,
+) -> Result<Json<FenixSnapshotDto>, AppError> {
+    fenix_service.refresh_snapshots().await?;
+    info!("{} refreshed the Fénix snapshot", admin.username);
+
+    Ok(Json(fenix_service.get_snapshots().await?))
+}
@@ -0,0 +1,101 @@
+use std::time::Instant;
+
+use axum::{extract::State, http::StatusCode, Json};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::{
+    dtos::{
+        DatabaseCheckDto, HealthStatusDto, MigrationsCheckDto, ReadinessDto, SessionStoreCheckDto,
+    },
+    services::fenix::FenixService,
+    session_store::AppSessionStore,
+};
+
+/// Liveness probe: answers as long as the process is able to handle requests.
+pub async fn healthz() -> Json<Value> {
+    Json(json!({ "status": HealthStatusDto::Up }))
+}
+
+/// Readiness probe: check the database, migrations, Fénix data and session store.
+/// Responds with 503 Service Unavailable if any of them is down.
+pub async fn readyz(
+    State(ref conn): State<DatabaseConnection>,
+    State(ref fenix_service): State<FenixService>,
+    State(ref session_store): State<AppSessionStore>,
+) -> (StatusCode, Json<ReadinessDto>) {
+    let start = Instant::now();
+    let database = match conn.ping().await {
+        Ok(()) => DatabaseCheckDto {
+            status: HealthStatusDto::Up,
+            latency_ms: Some(start.elapsed().as_millis() as u64),
+        },
+        Err(err) => {
+            warn!("readiness check failed to ping the database: {}", err);
+            DatabaseCheckDto {
+                status: HealthStatusDto::Down,
+                latency_ms: None,
+            }
+        }
+    };
+
+    let migrations = match Migrator::get_pending_migrations(conn).await {
+        Ok(pending) => MigrationsCheckDto {
+            status: if pending.is_empty() {
+                HealthStatusDto::Up
+            } else {
+                HealthStatusDto::Down
+            },
+            pending: pending
+                .iter()
+                .map(|migration| migration.name().to_string())
+                .collect(),
+        },
+        Err(err) => {
+            warn!("readiness check failed to get pending migrations: {}", err);
+            MigrationsCheckDto {
+                status: HealthStatusDto::Down,
+                pending: vec![],
+            }
+        }
+    };
+
+    let (fenix_degrees, fenix_academic_year) = fenix_service.check_readiness().await;
+
+    let session_store = SessionStoreCheckDto {
+        status: match session_store.ping().await {
+            Ok(()) => HealthStatusDto::Up,
+            Err(_) => HealthStatusDto::Down,
+        },
+        kind: session_store.kind().to_string(),
+    };
+
+    let ready = [
+        database.status,
+        migrations.status,
+        fenix_degrees.status,
+        fenix_academic_year.status,
+        session_store.status,
+    ]
+    .iter()
+    .all(|status| *status != HealthStatusDto::Down);
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+
+    (
+        status,
+        Json(ReadinessDto {
+            ready,
+            database,
+            migrations,
+            fenix_degrees,
+            fenix_academic_year,
+            session_store,
+        }),
+    )
+}
@@ -1,25 +1,92 @@
 use crate::{
     auth_utils::{self, get_user},
-    dtos::{AuthDto, DegreeEntryDto, LoginDto},
+    clock::Clock,
+    dtos::{AuthDto, DegreeEntryDto, LoginDto, UserDto},
     election_utils::validate_nominations_of_user,
     errors::AppError,
-    services::fenix::FenixService,
+    logging,
+    services::{
+        fenix::FenixService,
+        identity::{LoginState, OAuthResponse, SharedIdentityProvider},
+    },
+    session_store::{SessionMetadata, SESSION_METADATA_KEY},
+};
+use axum::{
+    extract::State, headers::UserAgent, http::StatusCode, response::Redirect, Extension, Json,
+    TypedHeader,
 };
-use axum::{extract::State, http::StatusCode, Extension, Json};
 use axum_sessions::SessionHandle;
 use entity::user_degree_override::{self, Entity as UserDegreeOverride};
+use rand::Rng;
 use sea_orm::{prelude::*, Condition};
 
+/// Session key of the state generated when starting a login.
+const LOGIN_STATE_KEY: &str = "login_state";
+
+/// Redirect to the provider's login page, remembering the state that it must send back.
+pub async fn start_login(
+    State(ref identity_provider): State<SharedIdentityProvider>,
+    Extension(ref session_handle): Extension<SessionHandle>,
+) -> Result<Redirect, AppError> {
+    let state = hex::encode(rand::thread_rng().gen::<[u8; 16]>());
+    let url = identity_provider.authorization_url(&state).await?;
+
+    let mut session = session_handle.write().await;
+    session.insert(LOGIN_STATE_KEY, state)?;
+
+    Ok(Redirect::to(&url))
+}
+
 pub async fn login(
+    State(ref identity_provider): State<SharedIdentityProvider>,
     State(ref fenix_service): State<FenixService>,
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
     Extension(ref session_handle): Extension<SessionHandle>,
+    user_agent: Option<TypedHeader<UserAgent>>,
     Json(login_dto): Json<LoginDto>,
 ) -> Result<Json<AuthDto>, AppError> {
-    let (mut user_details, oauth_tokens) = fenix_service
-        .authenticate_from_code(&login_dto.code)
+    // a state can only be used once
+    let expected_state = {
+        let mut session = session_handle.write().await;
+        let state = session.get::<String>(LOGIN_STATE_KEY);
+        session.remove(LOGIN_STATE_KEY);
+        state
+    };
+    let state = LoginState {
+        received: login_dto.state.as_deref(),
+        expected: expected_state.as_deref(),
+    };
+    let (user_details, oauth_tokens) = identity_provider
+        .authenticate_from_code(&login_dto.code, state)
         .await?;
 
+    let auth_details = start_session(
+        user_details,
+        oauth_tokens,
+        user_agent,
+        fenix_service,
+        conn,
+        clock,
+        session_handle,
+    )
+    .await?;
+
+    Ok(Json(auth_details))
+}
+
+/// Store an authenticated user in the session, after applying their degree overrides
+/// and validating their pending nominations.
+pub async fn start_session(
+    mut user_details: UserDto,
+    oauth_tokens: OAuthResponse,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    fenix_service: &FenixService,
+    conn: &DatabaseConnection,
+    clock: &Clock,
+    session_handle: &SessionHandle,
+) -> Result<AuthDto, AppError> {
+    logging::record_username(&user_details.username);
     let active_year = fenix_service.get_active_year().await?;
 
     // override degrees of user
@@ -58,26 +125,45 @@ pub async fn login(
 
     validate_nominations_of_user(&user_details, conn, &active_year).await?;
 
+    let now = chrono::Utc::now();
+    let metadata = SessionMetadata {
+        username: user_details.username.clone(),
+        user_agent: user_agent.map(|TypedHeader(user_agent)| user_agent.to_string()),
+        login_time: now,
+        last_activity: now,
+    };
+
     let mut session = session_handle.write().await;
     session.insert("user", &user_details)?;
     session.insert("oauth_tokens", &oauth_tokens)?;
+    session.insert(SESSION_METADATA_KEY, metadata)?;
 
+    let admin_role = auth_utils::get_admin_role(&user_details.username, conn).await?;
+    let observer_until =
+        auth_utils::get_observer_until(&user_details.username, conn, clock).await?;
     let auth_details = AuthDto {
-        is_admin: auth_utils::is_admin(&user_details.username, conn).await?,
+        is_admin: admin_role.is_some(),
+        admin_role,
+        observer_until,
         user: user_details,
     };
 
-    Ok(Json(auth_details))
+    Ok(auth_details)
 }
 
 pub async fn whoami(
     State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
     Extension(ref session_handle): Extension<SessionHandle>,
 ) -> Result<Json<AuthDto>, AppError> {
     let user = get_user(session_handle).await?;
 
+    let admin_role = auth_utils::get_admin_role(&user.username, conn).await?;
+    let observer_until = auth_utils::get_observer_until(&user.username, conn, clock).await?;
     let auth_details = AuthDto {
-        is_admin: auth_utils::is_admin(&user.username, conn).await?,
+        is_admin: admin_role.is_some(),
+        admin_role,
+        observer_until,
         user,
     };
     Ok(Json(auth_details))
@@ -1,7 +1,19 @@
+pub mod academic_year;
 pub mod admin;
+pub mod api_tokens;
+pub mod audit_log;
 pub mod config;
 pub mod degrees;
+#[cfg(feature = "dev-login")]
+pub mod dev_login;
 pub mod elections;
+pub mod fenix_snapshot;
+pub mod health;
 pub mod login;
+pub mod observers;
+pub mod pending_actions;
 pub mod search_user;
+pub mod sessions;
+#[cfg(feature = "time-travel")]
+pub mod time_travel;
 pub mod user_degree_overrides;
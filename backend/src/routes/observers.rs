@@ -0,0 +1,117 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use entity::observer::{self, Entity as Observer};
+use sea_orm::{prelude::*, DatabaseConnection, QueryOrder, Set, TransactionTrait};
+
+use serde_json::{json, Value};
+
+use crate::{
+    audit::{self, AuditAction},
+    auth_utils::{AdminUser, SuperAdmin},
+    clock::Clock,
+    dtos::{AddObserverDto, ObserverDto},
+    errors::AppError,
+};
+
+pub async fn list_observers(
+    _admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<Json<Vec<ObserverDto>>, AppError> {
+    let observers = Observer::find()
+        .order_by_desc(observer::Column::ExpiresAt)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(ObserverDto::from_entity)
+        .collect();
+
+    Ok(Json(observers))
+}
+
+/// Grant a user read-only access to elections until the given date,
+/// or change until when an existing observer has access.
+pub async fn grant_observer(
+    SuperAdmin(actor): SuperAdmin,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
+    Json(observer_dto): Json<AddObserverDto>,
+) -> Result<StatusCode, AppError> {
+    // validate username
+    let username = observer_dto.username.trim();
+    if username.is_empty() {
+        return Err(AppError::BadInput("error.username.empty"));
+    }
+
+    // access expires according to the app's clock, like elections
+    if observer_dto.expires_at <= clock.now() {
+        return Err(AppError::BadInput("error.observer.expiry.invalid"));
+    }
+
+    let txn = conn.begin().await?;
+
+    let previous = Observer::find_by_id(username).one(&txn).await?;
+
+    let observer = observer::ActiveModel {
+        username: Set(username.to_string()),
+        granted_by: Set(actor.username.clone()),
+        granted_at: Set(chrono::Utc::now().naive_utc()),
+        expires_at: Set(observer_dto.expires_at.naive_utc()),
+    };
+    let observer = match previous {
+        Some(_) => observer.update(&txn).await?,
+        None => observer.insert(&txn).await?,
+    };
+
+    audit::record(
+        &txn,
+        &actor.username,
+        AuditAction::GrantObserver,
+        username,
+        previous.as_ref().map(observer_to_json),
+        Some(observer_to_json(&observer)),
+    )
+    .await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn revoke_observer(
+    Path(username): Path<String>,
+    SuperAdmin(actor): SuperAdmin,
+    State(ref conn): State<DatabaseConnection>,
+) -> Result<StatusCode, AppError> {
+    let txn = conn.begin().await?;
+
+    let observer = Observer::find_by_id(&username)
+        .one(&txn)
+        .await?
+        .ok_or(AppError::UnknownObserver)?;
+    Observer::delete_by_id(&username).exec(&txn).await?;
+
+    audit::record(
+        &txn,
+        &actor.username,
+        AuditAction::RevokeObserver,
+        &username,
+        Some(observer_to_json(&observer)),
+        None,
+    )
+    .await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn observer_to_json(observer: &observer::Model) -> Value {
+    json!({
+        "username": observer.username,
+        "grantedBy": observer.granted_by,
+        "grantedAt": observer.granted_at.and_utc(),
+        "expiresAt": observer.expires_at.and_utc(),
+    })
+}
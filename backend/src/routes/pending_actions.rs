@@ -0,0 +1,113 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use entity::pending_action;
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+
+use crate::{
+    auth_utils::AdminUser,
+    clock::Clock,
+    dtos::PendingActionDto,
+    errors::AppError,
+    pending_actions::{self, SensitiveAction},
+    services::fenix::FenixService,
+};
+
+use super::{admin, elections};
+
+/// How many of the most recently proposed actions are listed.
+const LIST_LIMIT: u64 = 100;
+
+pub async fn list_pending_actions(
+    _admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref clock): State<Clock>,
+) -> Result<Json<Vec<PendingActionDto>>, AppError> {
+    let now = clock.now();
+    let pending_actions = pending_actions::list(conn, LIST_LIMIT)
+        .await?
+        .into_iter()
+        .map(|pending_action| PendingActionDto::from_entity(pending_action, now))
+        .collect::<Result<_, _>>()?;
+
+    Ok(Json(pending_actions))
+}
+
+/// Approve and execute an action proposed by another admin.
+pub async fn approve_pending_action(
+    Path(id): Path<i32>,
+    admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref fenix_service): State<FenixService>,
+    State(ref clock): State<Clock>,
+) -> Result<StatusCode, AppError> {
+    let username = &admin.username;
+
+    let txn = conn.begin().await?;
+
+    let (pending_action, action) = pending_actions::find_pending(&txn, id, clock).await?;
+    if &pending_action.proposed_by == username {
+        return Err(AppError::SelfApproval);
+    }
+    action.authorize(&admin, &txn, fenix_service).await?;
+
+    pending_actions::decide(&txn, pending_action, username, true, clock).await?;
+    execute(&txn, username, action).await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Reject an action, which can also be done by the admin who proposed it, to withdraw it.
+pub async fn reject_pending_action(
+    Path(id): Path<i32>,
+    admin: AdminUser,
+    State(ref conn): State<DatabaseConnection>,
+    State(ref fenix_service): State<FenixService>,
+    State(ref clock): State<Clock>,
+) -> Result<StatusCode, AppError> {
+    let txn = conn.begin().await?;
+
+    let (pending_action, action) = pending_actions::find_pending(&txn, id, clock).await?;
+    action.authorize(&admin, &txn, fenix_service).await?;
+
+    pending_actions::decide(&txn, pending_action, &admin.username, false, clock).await?;
+
+    txn.commit().await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Response for requests whose action must be approved by another admin before being executed.
+pub(crate) fn pending_action_accepted(
+    pending_action: pending_action::Model,
+    clock: &Clock,
+) -> Result<Response, AppError> {
+    let pending_action = PendingActionDto::from_entity(pending_action, clock.now())?;
+
+    Ok((StatusCode::ACCEPTED, Json(pending_action)).into_response())
+}
+
+async fn execute(
+    txn: &DatabaseTransaction,
+    actor: &str,
+    action: SensitiveAction,
+) -> Result<(), AppError> {
+    match action {
+        SensitiveAction::RemoveAdmin { username } => {
+            admin::delete_admin(txn, actor, &username).await
+        }
+        SensitiveAction::InvalidateNomination {
+            election,
+            nomination,
+        } => elections::apply_nomination_edit(txn, actor, election, nomination).await,
+        SensitiveAction::DeleteElection { election } => {
+            elections::remove_election(txn, actor, election).await
+        }
+        SensitiveAction::RescheduleElection { election, periods } => {
+            elections::apply_election_periods(txn, actor, election, periods).await
+        }
+    }
+}
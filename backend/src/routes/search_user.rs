@@ -6,17 +6,19 @@ use sea_orm::{prelude::*, DatabaseConnection};
 
 use crate::{
     auth_utils,
+    clock::Clock,
     crypto_utils::sign_person_search_result,
     dtos::{SearchPersonDto, SignedPersonSearchResultDto},
     errors::AppError,
-    services::fenix::FenixService,
+    services::{fenix::FenixService, signing_keys::SigningKeyService},
 };
 
 pub async fn search_user(
     Extension(ref session_handle): Extension<SessionHandle>,
     State(ref fenix_service): State<FenixService>,
     State(ref conn): State<DatabaseConnection>,
-    State(ref signing_key): State<[u8; 64]>,
+    State(ref signing_key_service): State<SigningKeyService>,
+    State(ref clock): State<Clock>,
     Json(search_dto): Json<SearchPersonDto>,
 ) -> Result<Json<Vec<SignedPersonSearchResultDto>>, AppError> {
     let user = auth_utils::get_user(session_handle).await?;
@@ -35,14 +37,18 @@ pub async fn search_user(
 
     let results = fenix_service
         .search_user_in_degree(&tokens.access_token, &search_dto.query, &election.degree_id)
-        .await
-        .map_err(|_| AppError::FenixError)?;
+        .await?;
+
+    let keyring = signing_key_service.get_keyring().await?;
+    let now = clock.now();
 
     Ok(Json(
         results
             .items
             .into_iter()
-            .map(|result| sign_person_search_result(election.id, result, signing_key))
+            .map(|result| {
+                sign_person_search_result(election.id, result, &user.username, now, &keyring)
+            })
             .collect(),
     ))
 }
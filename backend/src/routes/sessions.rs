@@ -0,0 +1,61 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use axum_sessions::SessionHandle;
+
+use crate::{
+    auth_utils::{self, AdminUser, SuperAdmin},
+    dtos::SessionDto,
+    errors::AppError,
+    session_store::AppSessionStore,
+};
+
+pub async fn list_own_sessions(
+    Extension(ref session_handle): Extension<SessionHandle>,
+    State(ref session_store): State<AppSessionStore>,
+) -> Result<Json<Vec<SessionDto>>, AppError> {
+    let user = auth_utils::get_user(session_handle).await?;
+    let current_session_id = session_handle.read().await.id().to_string();
+
+    let sessions = session_store
+        .list_user_sessions(&user.username)
+        .await?
+        .into_iter()
+        .map(|session| SessionDto::from_entity(session, &current_session_id))
+        .collect();
+
+    Ok(Json(sessions))
+}
+
+pub async fn list_user_sessions(
+    Path(username): Path<String>,
+    Extension(ref session_handle): Extension<SessionHandle>,
+    _admin: AdminUser,
+    State(ref session_store): State<AppSessionStore>,
+) -> Result<Json<Vec<SessionDto>>, AppError> {
+    let current_session_id = session_handle.read().await.id().to_string();
+
+    let sessions = session_store
+        .list_user_sessions(&username)
+        .await?
+        .into_iter()
+        .map(|session| SessionDto::from_entity(session, &current_session_id))
+        .collect();
+
+    Ok(Json(sessions))
+}
+
+pub async fn revoke_session(
+    Path(session_id): Path<String>,
+    SuperAdmin(_admin): SuperAdmin,
+    State(ref session_store): State<AppSessionStore>,
+) -> Result<StatusCode, AppError> {
+    session_store
+        .revoke_session(&session_id)
+        .await?
+        .ok_or(AppError::UnknownSession)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,32 @@
+use axum::{extract::State, Json};
+use tracing::warn;
+
+use crate::{
+    auth_utils::{AdminUser, SuperAdmin},
+    clock::Clock,
+    dtos::{SetTimeTravelDto, TimeTravelDto},
+    errors::AppError,
+};
+
+pub async fn get_time_travel(
+    _admin: AdminUser,
+    State(ref clock): State<Clock>,
+) -> Result<Json<TimeTravelDto>, AppError> {
+    Ok(Json(TimeTravelDto::from_clock(clock)))
+}
+
+pub async fn set_time_travel(
+    SuperAdmin(admin): SuperAdmin,
+    State(ref clock): State<Clock>,
+    Json(time_travel_dto): Json<SetTimeTravelDto>,
+) -> Result<Json<TimeTravelDto>, AppError> {
+    clock.set_offset(chrono::Duration::seconds(
+        time_travel_dto.offset_seconds.into(),
+    ));
+    warn!(
+        "{} shifted the election clock by {} seconds",
+        admin.username, time_travel_dto.offset_seconds
+    );
+
+    Ok(Json(TimeTravelDto::from_clock(clock)))
+}
@@ -1,40 +1,73 @@
 use std::collections::HashMap;
 
 use crate::{
-    auth_utils::{self},
+    audit::{self, AuditAction},
+    auth_utils::AdminUser,
     dtos::{
-        BulkAddUserDegreeOverrideDto, BulkDeleteUserDegreeOverrideDto, DegreeWithUserOverridesDto,
+        AcademicYearQuery, BulkAddUserDegreeOverrideDto, BulkDeleteUserDegreeOverrideDto,
+        DegreeWithUserOverridesDto,
     },
     errors::AppError,
     services::fenix::FenixService,
 };
-use axum::{extract::State, http::StatusCode, Extension, Json};
-use axum_sessions::SessionHandle;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Json,
+};
 use entity::user_degree_override::{self, Entity as UserDegreeOverride};
 use migration::OnConflict;
-use sea_orm::{prelude::*, ActiveValue, Condition, QueryOrder};
+use sea_orm::{
+    prelude::*, ActiveValue, Condition, DatabaseTransaction, QueryOrder, TransactionTrait,
+};
+use serde_json::{json, Value};
 
 pub async fn bulk_add_user_degree_override(
     State(ref fenix_service): State<FenixService>,
     State(ref conn): State<DatabaseConnection>,
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
     Json(override_dto): Json<BulkAddUserDegreeOverrideDto>,
 ) -> Result<StatusCode, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
-    fenix_service
+    let degree = fenix_service
         .get_degree(&override_dto.degree_id)
         .await?
         .ok_or(AppError::InvalidDegree)?;
+    if !admin.can_manage_degree(&degree) {
+        return Err(AppError::Forbidden);
+    }
+
     let active_year = fenix_service.get_active_year().await?;
 
+    let txn = conn.begin().await?;
+    add_user_degree_overrides(&txn, &active_year, &admin.username, override_dto).await?;
+    txn.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Make the given users attend a degree in the given (active) academic year, which is also
+/// used to import overrides from the management CLI.
+pub async fn add_user_degree_overrides(
+    txn: &DatabaseTransaction,
+    active_year: &str,
+    actor: &str,
+    override_dto: BulkAddUserDegreeOverrideDto,
+) -> Result<(), AppError> {
+    let previous_overrides = find_overrides(
+        txn,
+        active_year,
+        &override_dto.degree_id,
+        &override_dto.usernames,
+    )
+    .await?;
+
     let override_models: Vec<_> = override_dto
         .usernames
-        .into_iter()
+        .iter()
+        .cloned()
         .map(|username| user_degree_override::ActiveModel {
             username: ActiveValue::set(username),
-            academic_year: ActiveValue::set(active_year.clone()),
+            academic_year: ActiveValue::set(active_year.to_string()),
             degree_id: ActiveValue::set(override_dto.degree_id.clone()),
             curricular_year: ActiveValue::set(override_dto.curricular_year.into()),
         })
@@ -51,24 +84,39 @@ pub async fn bulk_add_user_degree_override(
             .to_owned(),
         )
         .do_nothing()
-        .exec(conn)
+        .exec(txn)
         .await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    let overrides = find_overrides(
+        txn,
+        active_year,
+        &override_dto.degree_id,
+        &override_dto.usernames,
+    )
+    .await?;
+    audit::record(
+        txn,
+        actor,
+        AuditAction::AddUserDegreeOverrides,
+        &override_dto.degree_id,
+        Some(overrides_to_json(active_year, &previous_overrides)),
+        Some(overrides_to_json(active_year, &overrides)),
+    )
+    .await?;
+
+    Ok(())
 }
 
 pub async fn get_user_degree_overrides(
     State(ref fenix_service): State<FenixService>,
     State(ref conn): State<DatabaseConnection>,
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
+    Query(query): Query<AcademicYearQuery>,
 ) -> Result<Json<Vec<DegreeWithUserOverridesDto>>, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
-
-    let active_year = fenix_service.get_active_year().await?;
+    let academic_year = query.or_active_year(fenix_service).await?;
 
     let overrides = UserDegreeOverride::find()
-        .filter(user_degree_override::Column::AcademicYear.eq(active_year))
+        .filter(user_degree_override::Column::AcademicYear.eq(academic_year))
         .order_by_asc(user_degree_override::Column::Username)
         .all(conn)
         .await?;
@@ -76,10 +124,15 @@ pub async fn get_user_degree_overrides(
     let mut degree_overrides = HashMap::new();
 
     for override_model in overrides {
+        let degree = fenix_service.get_degree(&override_model.degree_id).await?;
+        if !admin.can_view_degree(degree.as_ref()) {
+            continue;
+        }
+
         degree_overrides
             .entry(override_model.degree_id.clone())
             .or_insert(DegreeWithUserOverridesDto {
-                degree: fenix_service.get_degree(&override_model.degree_id).await?,
+                degree,
                 users: Vec::new(),
             })
             .users
@@ -98,23 +151,77 @@ pub async fn get_user_degree_overrides(
 pub async fn bulk_delete_user_degree_override(
     State(ref fenix_service): State<FenixService>,
     State(ref conn): State<DatabaseConnection>,
-    Extension(ref session_handle): Extension<SessionHandle>,
+    admin: AdminUser,
     Json(override_dto): Json<BulkDeleteUserDegreeOverrideDto>,
 ) -> Result<StatusCode, AppError> {
-    // assert admin only
-    auth_utils::get_admin(session_handle, conn).await?;
+    admin
+        .assert_can_manage_degree(fenix_service, &override_dto.degree_id)
+        .await?;
 
     let active_year = fenix_service.get_active_year().await?;
 
+    let txn = conn.begin().await?;
+
+    let previous_overrides = find_overrides(
+        &txn,
+        &active_year,
+        &override_dto.degree_id,
+        &override_dto.usernames,
+    )
+    .await?;
+
     UserDegreeOverride::delete_many()
         .filter(
             Condition::all()
-                .add(user_degree_override::Column::AcademicYear.eq(active_year))
-                .add(user_degree_override::Column::DegreeId.eq(override_dto.degree_id))
+                .add(user_degree_override::Column::AcademicYear.eq(&active_year))
+                .add(user_degree_override::Column::DegreeId.eq(&override_dto.degree_id))
                 .add(user_degree_override::Column::Username.is_in(override_dto.usernames)),
         )
-        .exec(conn)
+        .exec(&txn)
         .await?;
 
+    audit::record(
+        &txn,
+        &admin.username,
+        AuditAction::DeleteUserDegreeOverrides,
+        &override_dto.degree_id,
+        Some(overrides_to_json(&active_year, &previous_overrides)),
+        None,
+    )
+    .await?;
+
+    txn.commit().await?;
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Get the overrides of the given users to the given degree.
+async fn find_overrides(
+    txn: &DatabaseTransaction,
+    academic_year: &str,
+    degree_id: &str,
+    usernames: &[String],
+) -> Result<Vec<user_degree_override::Model>, AppError> {
+    Ok(UserDegreeOverride::find()
+        .filter(
+            Condition::all()
+                .add(user_degree_override::Column::AcademicYear.eq(academic_year))
+                .add(user_degree_override::Column::DegreeId.eq(degree_id))
+                .add(user_degree_override::Column::Username.is_in(usernames)),
+        )
+        .order_by_asc(user_degree_override::Column::Username)
+        .all(txn)
+        .await?)
+}
+
+fn overrides_to_json(academic_year: &str, overrides: &[user_degree_override::Model]) -> Value {
+    json!({
+        "academicYear": academic_year,
+        "users": overrides
+            .iter()
+            .map(|override_model| json!({
+                "username": override_model.username,
+                "curricularYear": override_model.curricular_year,
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
@@ -1,20 +1,61 @@
-use std::{collections::HashMap, env, time::Duration};
+use std::{
+    collections::HashMap,
+    env, fmt,
+    time::{Duration, Instant},
+};
 
-use serde::{Deserialize, Serialize};
-use tracing::debug;
+use async_trait::async_trait;
+use entity::fenix_snapshot::{self, Entity as FenixSnapshot};
+use migration::OnConflict;
+use reqwest::{Client, Method, RequestBuilder, Response};
+use sea_orm::{prelude::*, DatabaseConnection, Set, TransactionTrait};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+use tracing::{debug, warn};
 
 use crate::{
-    cache::Cached,
-    dtos::{DegreeDto, DegreeEntryDto, FenixConfigDto, UserDto},
+    audit::{self, AuditAction},
+    cache::{Cached, CachedMap},
+    circuit_breaker::CircuitBreaker,
+    dtos::{
+        DegreeDto, DegreeEntryDto, FenixConfigDto, FenixDataCheckDto, FenixSnapshotDto,
+        HealthStatusDto, UserDto,
+    },
     errors::AppError,
+    metrics::metrics,
+    settings::{self, PINNED_ACADEMIC_YEAR},
 };
 
+use super::identity::{IdentityProvider, LoginState, OAuthResponse, LOGIN_START_PATH};
+
 const FENIX_DEFAULT_BASE_URL: &str = "https://fenix.tecnico.ulisboa.pt";
 const TECNICO_API_PREFIX: &str = "/tecnico-api/v2";
 const FENIX_OAUTH_PREFIX: &str = "/oauth";
 
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+// only GET requests are retried, since they are idempotent
+const MAX_RETRIES: u32 = 2;
+const RETRY_BACKOFF: Duration = Duration::from_millis(200);
+// stop sending requests for a while after this many consecutive failures
+const CIRCUIT_FAILURE_THRESHOLD: u32 = 5;
+const CIRCUIT_OPEN_DURATION: Duration = Duration::from_secs(30);
+
 const CACHE_DURATION: Duration = Duration::from_secs(60 * 10); // 10 minutes
 
+// degrees and the active year rarely change, so keep serving them if Fénix is down
+const CACHE_MAX_STALENESS: Duration = Duration::from_secs(60 * 60 * 24); // 1 day
+const SEARCH_CACHE_DURATION: Duration = Duration::from_secs(60 * 5); // 5 minutes
+const SEARCH_CACHE_CAPACITY: usize = 1000;
+// the pinned year is reloaded periodically so that changes made in other instances are picked up
+const PINNED_YEAR_CACHE_DURATION: Duration = Duration::from_secs(30);
+
+// readiness probes give up on Fénix long before its requests time out
+const READINESS_TIMEOUT: Duration = Duration::from_secs(2);
+
+const DEGREES_SNAPSHOT_KEY: &str = "degrees";
+const ACTIVE_YEAR_SNAPSHOT_KEY: &str = "active_year";
+
 #[derive(Clone)]
 pub struct FenixService {
     base_url: String,
@@ -22,8 +63,19 @@ pub struct FenixService {
     client_secret: String,
     redirect_url: String,
 
+    client: Client,
+    circuit_breaker: CircuitBreaker,
+
     cached_degrees: Cached<HashMap<String, DegreeDto>>,
     cached_academic_year: Cached<String>,
+    /// Search results by degree and query, which are the same regardless of who searches.
+    cached_person_search: CachedMap<(String, String), PersonSearchResponse>,
+
+    cached_pinned_year: Cached<Option<String>>,
+
+    /// Where to persist the degree catalogue and active year, if anywhere,
+    /// as well as where the pinned academic year is stored.
+    conn: Option<DatabaseConnection>,
 }
 
 impl FenixService {
@@ -33,17 +85,56 @@ impl FenixService {
     /// and FENIX_REDIRECT_URL must be defined, whereas FENIX_BASE_URL
     /// is optional.
     pub fn new() -> Result<FenixService, String> {
-        let service = FenixService {
-            base_url: env::var("FENIX_BASE_URL").unwrap_or(FENIX_DEFAULT_BASE_URL.to_string()),
-            client_id: env::var("FENIX_CLIENT_ID")
+        Ok(FenixService::with_config(
+            env::var("FENIX_BASE_URL").unwrap_or(FENIX_DEFAULT_BASE_URL.to_string()),
+            env::var("FENIX_CLIENT_ID")
                 .map_err(|_| "Environment variable FENIX_CLIENT_ID is not defined")?,
-            client_secret: env::var("FENIX_CLIENT_SECRET")
+            env::var("FENIX_CLIENT_SECRET")
                 .map_err(|_| "Environment variable FENIX_CLIENT_SECRET is not defined")?,
-            redirect_url: env::var("FENIX_REDIRECT_URL")
+            env::var("FENIX_REDIRECT_URL")
                 .map_err(|_| "Environment variable FENIX_REDIRECT_URL is not defined")?,
+        ))
+    }
 
-            cached_degrees: Cached::new(CACHE_DURATION),
-            cached_academic_year: Cached::new(CACHE_DURATION),
+    /// Create a new FenixService instance with the given configuration.
+    pub fn with_config(
+        base_url: String,
+        client_id: String,
+        client_secret: String,
+        redirect_url: String,
+    ) -> FenixService {
+        let service = FenixService {
+            base_url,
+            client_id,
+            client_secret,
+            redirect_url,
+
+            client: Client::builder()
+                .connect_timeout(CONNECT_TIMEOUT)
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .expect("failed to build HTTP client"),
+            circuit_breaker: CircuitBreaker::new(CIRCUIT_FAILURE_THRESHOLD, CIRCUIT_OPEN_DURATION),
+
+            cached_degrees: Cached::with_max_staleness(
+                "fenix_degrees",
+                CACHE_DURATION,
+                CACHE_MAX_STALENESS,
+            ),
+            cached_academic_year: Cached::with_max_staleness(
+                "fenix_academic_year",
+                CACHE_DURATION,
+                CACHE_MAX_STALENESS,
+            ),
+            cached_person_search: CachedMap::new(
+                "fenix_person_search",
+                SEARCH_CACHE_DURATION,
+                SEARCH_CACHE_CAPACITY,
+            ),
+
+            cached_pinned_year: Cached::new("pinned_academic_year", PINNED_YEAR_CACHE_DURATION),
+
+            conn: None,
         };
 
         debug!(
@@ -51,7 +142,15 @@ impl FenixService {
             service.base_url, service.client_id, service.redirect_url
         );
 
-        Ok(service)
+        service
+    }
+
+    /// Persist the last fetched degree catalogue and active year in the database,
+    /// and fall back to them whenever Fénix can't be reached.
+    /// This also allows admins to pin the academic year (see [`FenixService::set_pinned_year`]).
+    pub fn with_database(mut self, conn: DatabaseConnection) -> FenixService {
+        self.conn = Some(conn);
+        self
     }
 
     /// Export this service's configuration as a DTO
@@ -63,86 +162,99 @@ impl FenixService {
         }
     }
 
-    /// Given an OAuth code, authenticate with Fénix and fetch information about
-    /// the user.
-    ///
-    /// Fetched information includes the user's name, username and attending degrees.
-    pub async fn authenticate_from_code(
-        &self,
-        code: &str,
-    ) -> Result<(UserDto, OAuthResponse), AppError> {
-        let oauth_response = self
-            .authorize_fenix_oauth_code(code)
-            .await
-            .map_err(|_| AppError::Unauthorized)?;
-        let access_token = &oauth_response.access_token;
-        let person = self
-            .get_user_details(access_token)
-            .await
-            .map_err(|_| AppError::FenixError)?;
-        let curriculum_response = self
-            .get_curricular_details(access_token)
-            .await
-            .map_err(|_| AppError::FenixError)?;
-        let academic_year = self.get_active_year().await?;
+    /// Send a request to Fénix on behalf of the given method, recording its latency and
+    /// whether it failed in metrics.
+    async fn send(&self, method: &str, request: RequestBuilder) -> Result<Response, AppError> {
+        let start = Instant::now();
+        let result = self.send_with_retries(request).await;
+        metrics().record_fenix_request(method, start.elapsed(), result.is_ok());
 
-        let degree_entries: Vec<DegreeEntryDto> = curriculum_response
-            .into_iter()
-            .filter(|entry| match entry.state.as_str() {
-                "REGISTERED" => entry
-                    .academic_terms
-                    .iter()
-                    .any(|term| term.year.to_string() == academic_year),
-                "MOBILITY" => true,
-                _ => false,
-            })
-            .map(|entry| entry.into())
-            .collect();
+        result
+    }
 
-        Ok((
-            UserDto {
-                username: person.username,
-                name: person.name,
-                display_name: person.display_name,
-                degree_entries,
-            },
-            oauth_response,
-        ))
+    /// Send a request to Fénix, failing fast if Fénix has been failing recently.
+    /// GET requests are retried, with exponential backoff, on network and server errors.
+    /// The circuit breaker only sees the outcome of the request after all retries.
+    async fn send_with_retries(&self, request: RequestBuilder) -> Result<Response, AppError> {
+        if !self.circuit_breaker.allow_request() {
+            return Err(AppError::FenixError);
+        }
+
+        let is_get = request
+            .try_clone()
+            .and_then(|request| request.build().ok())
+            .is_some_and(|request| request.method() == Method::GET);
+        let max_retries = if is_get { MAX_RETRIES } else { 0 };
+
+        let mut retries = 0;
+        loop {
+            let attempt = request
+                .try_clone()
+                .expect("requests to Fénix must not have streaming bodies");
+            match attempt.send().await {
+                Ok(response) if !response.status().is_server_error() => {
+                    self.circuit_breaker.record_success();
+                    return Ok(response);
+                }
+                result => {
+                    if retries >= max_retries {
+                        debug!(
+                            "request to Fénix failed: {}",
+                            result
+                                .map_or_else(|err| err.to_string(), |res| res.status().to_string())
+                        );
+                        if self.circuit_breaker.record_failure() {
+                            warn!(
+                                "Fénix seems to be unavailable, failing requests for the next {} seconds",
+                                CIRCUIT_OPEN_DURATION.as_secs()
+                            );
+                        }
+                        return Err(AppError::FenixError);
+                    }
+                }
+            }
+
+            tokio::time::sleep(RETRY_BACKOFF * 2u32.pow(retries)).await;
+            retries += 1;
+        }
     }
 
     /// Validate a OAuth code with Fenix's OAuth endpoint, getting the access and refresh tokens.
-    async fn authorize_fenix_oauth_code(&self, code: &str) -> reqwest::Result<OAuthResponse> {
-        let client = reqwest::Client::new();
-
-        client
-            .post(format!(
-                "{}{}/access_token",
-                self.base_url, FENIX_OAUTH_PREFIX
-            ))
-            .query(&[
-                ("grant_type", "authorization_code"),
-                ("client_id", &self.client_id),
-                ("client_secret", &self.client_secret),
-                ("redirect_uri", &self.redirect_url),
-                ("code", code),
-            ])
-            .send()
-            .await?
-            .json()
-            .await
+    async fn authorize_fenix_oauth_code(&self, code: &str) -> Result<OAuthResponse, AppError> {
+        self.send(
+            "authorize_fenix_oauth_code",
+            self.client
+                .post(format!(
+                    "{}{}/access_token",
+                    self.base_url, FENIX_OAUTH_PREFIX
+                ))
+                .query(&[
+                    ("grant_type", "authorization_code"),
+                    ("client_id", &self.client_id),
+                    ("client_secret", &self.client_secret),
+                    ("redirect_uri", &self.redirect_url),
+                    ("code", code),
+                ]),
+        )
+        .await?
+        .json()
+        .await
+        // an invalid code results in an error response instead of the tokens
+        .map_err(|_| AppError::Unauthorized)
     }
 
     /// Get basic details about the user, such as their username and display name.
-    async fn get_user_details(&self, oauth_token: &str) -> reqwest::Result<PersonResponse> {
-        let client = reqwest::Client::new();
-
-        client
-            .get(format!("{}{}/person", self.base_url, TECNICO_API_PREFIX))
-            .header("Authorization", format!("Bearer {}", oauth_token))
-            .send()
-            .await?
-            .json()
-            .await
+    async fn get_user_details(&self, oauth_token: &str) -> Result<PersonResponse, AppError> {
+        self.send(
+            "get_user_details",
+            self.client
+                .get(format!("{}{}/person", self.base_url, TECNICO_API_PREFIX))
+                .header("Authorization", format!("Bearer {}", oauth_token)),
+        )
+        .await?
+        .json()
+        .await
+        .map_err(|_| AppError::FenixError)
     }
 
     /// Get curricular information about the user, such as the degrees they're in, as well as the
@@ -150,54 +262,70 @@ impl FenixService {
     async fn get_curricular_details(
         &self,
         oauth_token: &str,
-    ) -> reqwest::Result<Vec<CurriculumResponse>> {
-        let client = reqwest::Client::new();
-
-        client
-            .get(format!(
-                "{}{}/student/curriculum",
-                self.base_url, TECNICO_API_PREFIX
-            ))
-            .header("Authorization", format!("Bearer {}", oauth_token))
-            .send()
-            .await?
-            .json()
-            .await
+    ) -> Result<Vec<CurriculumResponse>, AppError> {
+        self.send(
+            "get_curricular_details",
+            self.client
+                .get(format!(
+                    "{}{}/student/curriculum",
+                    self.base_url, TECNICO_API_PREFIX
+                ))
+                .header("Authorization", format!("Bearer {}", oauth_token)),
+        )
+        .await?
+        .json()
+        .await
+        .map_err(|_| AppError::FenixError)
     }
 
     /// Search for Fénix user in specific degree.
+    /// Results are cached for a few minutes, since users usually search for the same people.
     pub async fn search_user_in_degree(
         &self,
         oauth_token: &str,
         query: &str,
         degree_id: &str,
-    ) -> reqwest::Result<PersonSearchResponse> {
-        let client = reqwest::Client::new();
-
-        client
-            .get(format!(
-                "{}{}/person/search",
-                self.base_url, TECNICO_API_PREFIX
-            ))
-            .query(&[("name", query), ("degree", degree_id), ("limit", "20")])
-            .header("Authorization", format!("Bearer {}", oauth_token))
-            .header("X-Requested-With", "XMLHttpRequest")
-            .send()
-            .await?
-            .json()
+    ) -> Result<PersonSearchResponse, AppError> {
+        self.cached_person_search
+            .get((degree_id.to_owned(), query.to_owned()), || {
+                self.fetch_person_search_from_fenix(oauth_token, query, degree_id)
+            })
             .await
     }
 
+    async fn fetch_person_search_from_fenix(
+        &self,
+        oauth_token: &str,
+        query: &str,
+        degree_id: &str,
+    ) -> Result<PersonSearchResponse, AppError> {
+        self.send(
+            "fetch_person_search_from_fenix",
+            self.client
+                .get(format!(
+                    "{}{}/person/search",
+                    self.base_url, TECNICO_API_PREFIX
+                ))
+                .query(&[("name", query), ("degree", degree_id), ("limit", "20")])
+                .header("Authorization", format!("Bearer {}", oauth_token))
+                .header("X-Requested-With", "XMLHttpRequest"),
+        )
+        .await?
+        .json()
+        .await
+        .map_err(|_| AppError::FenixError)
+    }
+
     /// Get cached degree list from Fénix
     /// If no degrees are cached, or if the cached list has been invalidated, they will be fetched
-    /// again.
+    /// again (in the background, if the cached list isn't too old).
     pub async fn get_degrees(&self) -> Result<impl Iterator<Item = DegreeDto>, AppError> {
         self.get_degrees_map().await.map(|map| map.into_values())
     }
 
     /// Get cached degree from Fénix
     /// If no degrees are cached, or if the cached list has been invalidated, they will be fetched
-    /// again.
+    /// again (in the background, if the cached list isn't too old).
     pub async fn get_degree(&self, degree_id: &str) -> Result<Option<DegreeDto>, AppError> {
         self.get_degrees_map()
             .await
@@ -205,59 +333,339 @@ impl FenixService {
     }
 
     async fn get_degrees_map(&self) -> Result<HashMap<String, DegreeDto>, AppError> {
+        let service = self.clone();
         self.cached_degrees
-            .get(|| async {
-                self.fetch_degrees_from_fenix()
-                    .await
-                    .map_err(|_| AppError::FenixError)
+            .get(|| async move {
+                match service.fetch_degrees_from_fenix().await {
+                    Ok(degrees) => {
+                        service.save_snapshot(DEGREES_SNAPSHOT_KEY, &degrees).await;
+                        Ok(degrees)
+                    }
+                    Err(_) => service.fallback_to_snapshot(DEGREES_SNAPSHOT_KEY).await,
+                }
             })
             .await
     }
 
-    async fn fetch_degrees_from_fenix(&self) -> reqwest::Result<HashMap<String, DegreeDto>> {
-        let client = reqwest::Client::new();
+    async fn fetch_degrees_from_fenix(&self) -> Result<HashMap<String, DegreeDto>, AppError> {
+        self.send(
+            "fetch_degrees_from_fenix",
+            self.client
+                .get(format!("{}{}/degrees", self.base_url, TECNICO_API_PREFIX)),
+        )
+        .await?
+        .json::<Vec<DegreeDto>>()
+        .await
+        .map(|degrees| {
+            degrees
+                .into_iter()
+                .map(|degree| (degree.id.clone(), degree))
+                .collect()
+        })
+        .map_err(|_| AppError::FenixError)
+    }
 
-        client
-            .get(format!("{}{}/degrees", self.base_url, TECNICO_API_PREFIX))
-            .send()
-            .await?
-            .json::<Vec<DegreeDto>>()
-            .await
-            .map(|degrees| {
-                degrees
-                    .into_iter()
-                    .map(|degree| (degree.id.clone(), degree))
-                    .collect()
+    /// Get the academic year used by elections, overrides and results: the one pinned by
+    /// an admin, if any, or otherwise the active academic year in Fénix.
+    pub async fn get_active_year(&self) -> Result<String, AppError> {
+        match self.get_pinned_year().await? {
+            Some(year) => Ok(year),
+            None => self.get_fenix_active_year().await,
+        }
+    }
+
+    /// Get the academic year pinned by an admin, if any.
+    pub async fn get_pinned_year(&self) -> Result<Option<String>, AppError> {
+        let Some(conn) = self.conn.clone() else {
+            return Ok(None);
+        };
+
+        self.cached_pinned_year
+            .get(|| async move {
+                settings::get_setting(&conn, PINNED_ACADEMIC_YEAR)
+                    .await
+                    .map_err(AppError::from)
             })
+            .await
+    }
+
+    /// Pin the academic year used by elections, overrides and results, ignoring
+    /// Fénix's active year, or unpin it if the year is None.
+    /// The change is recorded in the audit log as made by the given admin.
+    pub async fn set_pinned_year(&self, year: Option<String>, admin: &str) -> Result<(), AppError> {
+        let conn = self.conn.as_ref().ok_or(AppError::DbError(DbErr::Custom(
+            "cannot pin academic year without a database".to_string(),
+        )))?;
+
+        let txn = conn.begin().await?;
+        let previous_year = settings::get_setting(&txn, PINNED_ACADEMIC_YEAR).await?;
+        settings::set_setting(&txn, PINNED_ACADEMIC_YEAR, year.clone()).await?;
+        audit::record(
+            &txn,
+            admin,
+            AuditAction::PinAcademicYear,
+            PINNED_ACADEMIC_YEAR,
+            Some(json!(previous_year)),
+            Some(json!(year)),
+        )
+        .await?;
+        txn.commit().await?;
+
+        self.cached_pinned_year.set(year).await;
+
+        Ok(())
     }
 
     /// Get cached active academic year from Fénix
     /// If no year is cached, or if the cached year has been invalidated, it will be fetched
-    /// again.
-    pub async fn get_active_year(&self) -> Result<String, AppError> {
+    /// again (in the background, if the cached year isn't too old).
+    pub async fn get_fenix_active_year(&self) -> Result<String, AppError> {
+        let service = self.clone();
         self.cached_academic_year
-            .get(|| async {
-                self.fetch_active_year_from_fenix()
-                    .await
-                    .map_err(|_| AppError::FenixError)
+            .get(|| async move {
+                match service.fetch_active_year_from_fenix().await {
+                    Ok(year) => {
+                        service.save_snapshot(ACTIVE_YEAR_SNAPSHOT_KEY, &year).await;
+                        Ok(year)
+                    }
+                    Err(_) => service.fallback_to_snapshot(ACTIVE_YEAR_SNAPSHOT_KEY).await,
+                }
             })
             .await
     }
 
-    async fn fetch_active_year_from_fenix(&self) -> reqwest::Result<String> {
-        let client = reqwest::Client::new();
-
-        let response: AboutResponse = client
-            .get(format!("{}{}/about", self.base_url, TECNICO_API_PREFIX))
-            .send()
+    async fn fetch_active_year_from_fenix(&self) -> Result<String, AppError> {
+        let response: AboutResponse = self
+            .send(
+                "fetch_active_year_from_fenix",
+                self.client
+                    .get(format!("{}{}/about", self.base_url, TECNICO_API_PREFIX)),
+            )
             .await?
             .json()
-            .await?;
+            .await
+            .map_err(|_| AppError::FenixError)?;
 
         let year = response.active_semester.year;
 
         Ok(year.to_string())
     }
+
+    /// Load the last persisted degree catalogue and active year, which are served
+    /// (and refreshed in the background) until they're fetched from Fénix.
+    pub async fn load_snapshots(&self) -> Result<(), AppError> {
+        if let Some((degrees, _)) = self.load_snapshot(DEGREES_SNAPSHOT_KEY).await? {
+            self.cached_degrees.set_expired(degrees).await;
+        }
+        if let Some((year, _)) = self.load_snapshot(ACTIVE_YEAR_SNAPSHOT_KEY).await? {
+            self.cached_academic_year.set_expired(year).await;
+        }
+
+        Ok(())
+    }
+
+    /// Fetch the degree catalogue and active year from Fénix right away, bypassing the cache,
+    /// and persist them.
+    pub async fn refresh_snapshots(&self) -> Result<(), AppError> {
+        let degrees = self.fetch_degrees_from_fenix().await?;
+        let year = self.fetch_active_year_from_fenix().await?;
+
+        self.save_snapshot(DEGREES_SNAPSHOT_KEY, &degrees).await;
+        self.save_snapshot(ACTIVE_YEAR_SNAPSHOT_KEY, &year).await;
+        self.cached_degrees.set(degrees).await;
+        self.cached_academic_year.set(year).await;
+
+        Ok(())
+    }
+
+    /// Get the persisted active year, and when it and the degree catalogue were fetched.
+    pub async fn get_snapshots(&self) -> Result<FenixSnapshotDto, AppError> {
+        let Some(conn) = &self.conn else {
+            return Ok(FenixSnapshotDto::default());
+        };
+
+        let active_year = self
+            .load_snapshot::<String>(ACTIVE_YEAR_SNAPSHOT_KEY)
+            .await?;
+        let degrees_taken_at = FenixSnapshot::find_by_id(DEGREES_SNAPSHOT_KEY)
+            .one(conn)
+            .await?
+            .map(|snapshot| snapshot.taken_at.and_utc());
+
+        Ok(FenixSnapshotDto {
+            active_year_taken_at: active_year.as_ref().map(|(_, taken_at)| *taken_at),
+            active_year: active_year.map(|(year, _)| year),
+            degrees_taken_at,
+        })
+    }
+
+    /// Check whether the degree catalogue and the active year can be served, and whether they
+    /// are stale, i.e., whether they haven't been refreshed from Fénix for longer than usual.
+    /// This fetches them from Fénix if they aren't cached, but they are reported as unavailable
+    /// if that takes too long. The fetch carries on in the background, to be ready next time.
+    pub async fn check_readiness(&self) -> (FenixDataCheckDto, FenixDataCheckDto) {
+        let service = self.clone();
+        let degrees = tokio::spawn(async move { service.get_degrees_map().await.is_ok() });
+        let service = self.clone();
+        let active_year =
+            tokio::spawn(async move { service.get_fenix_active_year().await.is_ok() });
+
+        let (degrees_available, active_year_available) = tokio::join!(
+            tokio::time::timeout(READINESS_TIMEOUT, degrees),
+            tokio::time::timeout(READINESS_TIMEOUT, active_year),
+        );
+        let degrees_available = matches!(degrees_available, Ok(Ok(true)));
+        let active_year_available = matches!(active_year_available, Ok(Ok(true)));
+        let snapshots = self.get_snapshots().await.unwrap_or_else(|_| {
+            warn!("failed to load Fénix snapshots to check readiness");
+            FenixSnapshotDto::default()
+        });
+
+        let max_age = chrono::Duration::from_std(CACHE_DURATION).expect("cache duration is valid");
+        let check = |available: bool, fetched_at: Option<DateTimeUtc>| {
+            let status = match fetched_at {
+                _ if !available => HealthStatusDto::Down,
+                Some(fetched_at) if chrono::Utc::now() - fetched_at > max_age => {
+                    HealthStatusDto::Stale
+                }
+                _ => HealthStatusDto::Up,
+            };
+
+            FenixDataCheckDto { status, fetched_at }
+        };
+
+        (
+            check(degrees_available, snapshots.degrees_taken_at),
+            check(active_year_available, snapshots.active_year_taken_at),
+        )
+    }
+
+    async fn fallback_to_snapshot<T: DeserializeOwned>(&self, key: &str) -> Result<T, AppError> {
+        match self.load_snapshot(key).await? {
+            Some((value, taken_at)) => {
+                warn!(
+                    "failed to fetch from Fénix, using snapshot '{}' taken at {}",
+                    key, taken_at
+                );
+                Ok(value)
+            }
+            None => Err(AppError::FenixError),
+        }
+    }
+
+    /// Persist a value fetched from Fénix. Failing to do so doesn't prevent the value
+    /// from being used, so errors are only logged.
+    async fn save_snapshot<T: Serialize>(&self, key: &str, value: &T) {
+        let Some(conn) = &self.conn else {
+            return;
+        };
+
+        let Ok(value) = serde_json::to_string(value) else {
+            warn!("failed to serialize Fénix snapshot '{}'", key);
+            return;
+        };
+
+        let result = FenixSnapshot::insert(fenix_snapshot::ActiveModel {
+            key: Set(key.to_string()),
+            value: Set(value),
+            taken_at: Set(chrono::Utc::now().naive_utc()),
+        })
+        .on_conflict(
+            OnConflict::column(fenix_snapshot::Column::Key)
+                .update_columns([
+                    fenix_snapshot::Column::Value,
+                    fenix_snapshot::Column::TakenAt,
+                ])
+                .to_owned(),
+        )
+        .exec(conn)
+        .await;
+
+        if let Err(err) = result {
+            warn!("failed to save Fénix snapshot '{}': {}", key, err);
+        }
+    }
+
+    /// Get a persisted value, along with when it was fetched from Fénix.
+    async fn load_snapshot<T: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<(T, DateTimeUtc)>, AppError> {
+        let Some(conn) = &self.conn else {
+            return Ok(None);
+        };
+
+        let Some(snapshot) = FenixSnapshot::find_by_id(key).one(conn).await? else {
+            return Ok(None);
+        };
+
+        match serde_json::from_str(&snapshot.value) {
+            Ok(value) => Ok(Some((value, snapshot.taken_at.and_utc()))),
+            Err(_) => {
+                warn!("ignoring invalid Fénix snapshot '{}'", key);
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for FenixService {
+    async fn login_url(&self) -> Result<String, AppError> {
+        // the user dialog needs a state, which is generated when starting the login
+        Ok(LOGIN_START_PATH.to_string())
+    }
+
+    async fn authorization_url(&self, state: &str) -> Result<String, AppError> {
+        let url = reqwest::Url::parse_with_params(
+            &format!("{}{}/userdialog", self.base_url, FENIX_OAUTH_PREFIX),
+            &[
+                ("client_id", self.client_id.as_str()),
+                ("redirect_uri", &self.redirect_url),
+                ("state", state),
+            ],
+        )
+        .map_err(|_| AppError::FenixError)?;
+
+        Ok(url.to_string())
+    }
+
+    async fn authenticate_from_code(
+        &self,
+        code: &str,
+        state: LoginState<'_>,
+    ) -> Result<(UserDto, OAuthResponse), AppError> {
+        state.verify()?;
+
+        let oauth_response = self.authorize_fenix_oauth_code(code).await?;
+        let access_token = &oauth_response.access_token;
+        let person = self.get_user_details(access_token).await?;
+        let curriculum_response = self.get_curricular_details(access_token).await?;
+        let academic_year = self.get_active_year().await?;
+
+        let degree_entries: Vec<DegreeEntryDto> = curriculum_response
+            .into_iter()
+            .filter(|entry| match entry.state.as_str() {
+                "REGISTERED" => entry
+                    .academic_terms
+                    .iter()
+                    .any(|term| term.year.to_string() == academic_year),
+                "MOBILITY" => true,
+                _ => false,
+            })
+            .map(|entry| entry.into())
+            .collect();
+
+        Ok((
+            UserDto {
+                username: person.username,
+                name: person.name,
+                display_name: person.display_name,
+                degree_entries,
+            },
+            oauth_response,
+        ))
+    }
 }
 
 impl From<CurriculumResponse> for DegreeEntryDto {
@@ -270,12 +678,6 @@ impl From<CurriculumResponse> for DegreeEntryDto {
     }
 }
 
-#[derive(Deserialize, Serialize, Clone)]
-pub struct OAuthResponse {
-    pub access_token: String,
-    pub refresh_token: String,
-}
-
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PersonResponse {
@@ -316,19 +718,19 @@ struct ExecutionYear {
     end_year: u32,
 }
 
-impl ToString for ExecutionYear {
-    fn to_string(&self) -> String {
-        format!("{}/{}", self.begin_year, self.end_year)
+impl fmt::Display for ExecutionYear {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.begin_year, self.end_year)
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PersonSearchResponse {
     pub items: Vec<PersonSearchResult>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 pub struct PersonSearchResult {
     pub username: String,
     pub name: String,
@@ -0,0 +1,76 @@
+use std::{env, sync::Arc};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{dtos::UserDto, errors::AppError};
+
+use super::{fenix::FenixService, oidc::OidcIdentityProvider};
+
+/// Path of the endpoint that starts a login, for providers whose login page
+/// is only known after generating a state for it (e.g., to protect against login CSRF).
+pub const LOGIN_START_PATH: &str = "/api/login/start";
+
+/// A service that authenticates users through an external login page (e.g., OAuth),
+/// and which knows which degrees they are attending.
+#[async_trait]
+pub trait IdentityProvider: Send + Sync {
+    /// URL of the page where users should be redirected to in order to log in.
+    /// This must not depend on the provider being reachable, since it is part of the app's config.
+    async fn login_url(&self) -> Result<String, AppError>;
+
+    /// URL of the provider's login page, which sends the given state back alongside the code.
+    /// Providers that don't support a state just use their login URL.
+    async fn authorization_url(&self, _state: &str) -> Result<String, AppError> {
+        self.login_url().await
+    }
+
+    /// Given an OAuth code, authenticate with the provider and fetch information about
+    /// the user.
+    ///
+    /// Fetched information includes the user's name, username and attending degrees.
+    async fn authenticate_from_code(
+        &self,
+        code: &str,
+        state: LoginState<'_>,
+    ) -> Result<(UserDto, OAuthResponse), AppError>;
+}
+
+/// The state sent back by the provider alongside the code, along with the one generated
+/// when the login was started, which is kept in the session.
+pub struct LoginState<'a> {
+    pub received: Option<&'a str>,
+    pub expected: Option<&'a str>,
+}
+
+impl LoginState<'_> {
+    /// Check that the login was started by the same browser, to protect against login CSRF.
+    pub fn verify(&self) -> Result<(), AppError> {
+        match (self.received, self.expected) {
+            (Some(received), Some(expected)) if received == expected => Ok(()),
+            _ => Err(AppError::Unauthorized),
+        }
+    }
+}
+
+pub type SharedIdentityProvider = Arc<dyn IdentityProvider>;
+
+/// Create the identity provider according to the IDENTITY_PROVIDER env variable,
+/// which can either be "fenix" (default) or "oidc".
+pub fn from_env(fenix_service: &FenixService) -> Result<SharedIdentityProvider, String> {
+    match env::var("IDENTITY_PROVIDER").as_deref() {
+        Err(_) | Ok("fenix") => Ok(Arc::new(fenix_service.clone())),
+        Ok("oidc") => Ok(Arc::new(OidcIdentityProvider::new()?)),
+        Ok(other) => Err(format!(
+            "Invalid IDENTITY_PROVIDER '{}': must be either 'fenix' or 'oidc'",
+            other
+        )),
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct OAuthResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: String,
+}
@@ -1 +1,4 @@
 pub mod fenix;
+pub mod identity;
+pub mod oidc;
+pub mod signing_keys;
@@ -0,0 +1,423 @@
+use std::{env, time::Duration};
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::debug;
+
+use crate::{
+    cache::Cached,
+    dtos::{DegreeEntryDto, UserDto},
+    errors::AppError,
+};
+
+use super::identity::{IdentityProvider, LoginState, OAuthResponse, LOGIN_START_PATH};
+
+const DISCOVERY_PATH: &str = "/.well-known/openid-configuration";
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+const CACHE_DURATION: Duration = Duration::from_secs(60 * 60); // 1 hour
+
+// the discovery document rarely changes, so keep using it while the provider is unreachable
+const MAX_STALENESS: Duration = Duration::from_secs(24 * 60 * 60); // 1 day
+
+/// How the claims returned by the OpenID Connect provider are mapped into a user.
+///
+/// Claim names can refer to nested claims by separating each level with a dot
+/// (e.g., `student.degrees`).
+#[derive(Clone)]
+struct ClaimMapping {
+    username: String,
+    name: String,
+    display_name: String,
+    /// Claim containing a list of objects, each of them describing a degree the user is attending.
+    degrees: String,
+    degree_id_field: String,
+    curricular_year_field: String,
+    /// If this field is missing from a degree entry, the user is considered to be eligible.
+    nomination_eligible_field: String,
+}
+
+/// Identity provider that authenticates users with a generic OpenID Connect provider.
+#[derive(Clone)]
+pub struct OidcIdentityProvider {
+    issuer_url: String,
+    client_id: String,
+    client_secret: String,
+    redirect_url: String,
+    scopes: String,
+    claims: ClaimMapping,
+
+    client: reqwest::Client,
+    cached_discovery: Cached<DiscoveryResponse>,
+}
+
+impl OidcIdentityProvider {
+    /// Create a new OidcIdentityProvider instance from environment variables.
+    ///
+    /// At least the env variables OIDC_ISSUER_URL, OIDC_CLIENT_ID, OIDC_CLIENT_SECRET
+    /// and OIDC_REDIRECT_URL must be defined.
+    /// The requested scopes (OIDC_SCOPES) and how claims are mapped into the user
+    /// (OIDC_USERNAME_CLAIM, OIDC_NAME_CLAIM, OIDC_DISPLAY_NAME_CLAIM, OIDC_DEGREES_CLAIM,
+    /// OIDC_DEGREE_ID_FIELD, OIDC_CURRICULAR_YEAR_FIELD and OIDC_NOMINATION_ELIGIBLE_FIELD)
+    /// are optional.
+    pub fn new() -> Result<OidcIdentityProvider, String> {
+        let provider = OidcIdentityProvider {
+            issuer_url: env::var("OIDC_ISSUER_URL")
+                .map_err(|_| "Environment variable OIDC_ISSUER_URL is not defined")?
+                .trim_end_matches('/')
+                .to_string(),
+            client_id: env::var("OIDC_CLIENT_ID")
+                .map_err(|_| "Environment variable OIDC_CLIENT_ID is not defined")?,
+            client_secret: env::var("OIDC_CLIENT_SECRET")
+                .map_err(|_| "Environment variable OIDC_CLIENT_SECRET is not defined")?,
+            redirect_url: env::var("OIDC_REDIRECT_URL")
+                .map_err(|_| "Environment variable OIDC_REDIRECT_URL is not defined")?,
+            scopes: env::var("OIDC_SCOPES").unwrap_or("openid profile".to_string()),
+            claims: ClaimMapping {
+                username: env_or("OIDC_USERNAME_CLAIM", "preferred_username"),
+                name: env_or("OIDC_NAME_CLAIM", "name"),
+                display_name: env_or("OIDC_DISPLAY_NAME_CLAIM", "name"),
+                degrees: env_or("OIDC_DEGREES_CLAIM", "degrees"),
+                degree_id_field: env_or("OIDC_DEGREE_ID_FIELD", "degree_id"),
+                curricular_year_field: env_or("OIDC_CURRICULAR_YEAR_FIELD", "curricular_year"),
+                nomination_eligible_field: env_or(
+                    "OIDC_NOMINATION_ELIGIBLE_FIELD",
+                    "nomination_eligible",
+                ),
+            },
+
+            client: build_client(),
+            cached_discovery: Cached::with_max_staleness(
+                "oidc_discovery",
+                CACHE_DURATION,
+                MAX_STALENESS,
+            ),
+        };
+
+        debug!(
+            "initialized OidcIdentityProvider with issuer '{}', client id '{}' and redirect url '{}'",
+            provider.issuer_url, provider.client_id, provider.redirect_url
+        );
+
+        Ok(provider)
+    }
+
+    /// Get the provider's endpoints from its discovery document.
+    async fn get_discovery(&self) -> Result<DiscoveryResponse, AppError> {
+        let discovery_url = format!("{}{}", self.issuer_url, DISCOVERY_PATH);
+        let client = self.client.clone();
+        self.cached_discovery
+            .get(|| async move { client.get(discovery_url).send().await?.json().await })
+            .await
+            .map_err(|_| AppError::IdentityProviderError)
+    }
+
+    async fn exchange_code(
+        &self,
+        discovery: &DiscoveryResponse,
+        code: &str,
+    ) -> reqwest::Result<OAuthResponse> {
+        self.client
+            .post(&discovery.token_endpoint)
+            .form(&[
+                ("grant_type", "authorization_code"),
+                ("client_id", &self.client_id),
+                ("client_secret", &self.client_secret),
+                ("redirect_uri", &self.redirect_url),
+                ("code", code),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    async fn get_user_info(
+        &self,
+        discovery: &DiscoveryResponse,
+        access_token: &str,
+    ) -> reqwest::Result<Value> {
+        self.client
+            .get(&discovery.userinfo_endpoint)
+            .bearer_auth(access_token)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+#[async_trait]
+impl IdentityProvider for OidcIdentityProvider {
+    async fn login_url(&self) -> Result<String, AppError> {
+        // the provider's login page needs a state, which is generated when starting the login
+        Ok(LOGIN_START_PATH.to_string())
+    }
+
+    async fn authorization_url(&self, state: &str) -> Result<String, AppError> {
+        let discovery = self.get_discovery().await?;
+
+        let url = reqwest::Url::parse_with_params(
+            &discovery.authorization_endpoint,
+            &[
+                ("response_type", "code"),
+                ("client_id", &self.client_id),
+                ("redirect_uri", &self.redirect_url),
+                ("scope", &self.scopes),
+                ("state", state),
+            ],
+        )
+        .map_err(|_| AppError::IdentityProviderError)?;
+
+        Ok(url.to_string())
+    }
+
+    async fn authenticate_from_code(
+        &self,
+        code: &str,
+        state: LoginState<'_>,
+    ) -> Result<(UserDto, OAuthResponse), AppError> {
+        state.verify()?;
+
+        let discovery = self.get_discovery().await?;
+        let oauth_response = self
+            .exchange_code(&discovery, code)
+            .await
+            .map_err(|_| AppError::Unauthorized)?;
+        let claims = self
+            .get_user_info(&discovery, &oauth_response.access_token)
+            .await
+            .map_err(|_| AppError::IdentityProviderError)?;
+
+        let user = map_claims_to_user(&claims, &self.claims).ok_or(AppError::Unauthorized)?;
+
+        Ok((user, oauth_response))
+    }
+}
+
+/// Build a user from the claims returned by the provider.
+/// Returns `None` if the claim with the username is missing.
+fn map_claims_to_user(claims: &Value, mapping: &ClaimMapping) -> Option<UserDto> {
+    let username = get_claim(claims, &mapping.username)?.as_str()?.to_string();
+    let name = get_claim(claims, &mapping.name)
+        .and_then(Value::as_str)
+        .unwrap_or(&username)
+        .to_string();
+    let display_name = get_claim(claims, &mapping.display_name)
+        .and_then(Value::as_str)
+        .unwrap_or(&name)
+        .to_string();
+
+    let degree_entries = get_claim(claims, &mapping.degrees)
+        .and_then(Value::as_array)
+        .map(|degrees| {
+            degrees
+                .iter()
+                .filter_map(|degree| map_claim_to_degree_entry(degree, mapping))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(UserDto {
+        username,
+        name,
+        display_name,
+        degree_entries,
+    })
+}
+
+/// Build a degree entry from an element of the degrees claim.
+/// Entries without a degree id or a valid curricular year are ignored.
+fn map_claim_to_degree_entry(degree: &Value, mapping: &ClaimMapping) -> Option<DegreeEntryDto> {
+    let degree_id = match get_claim(degree, &mapping.degree_id_field)? {
+        Value::String(id) => id.clone(),
+        Value::Number(id) => id.to_string(),
+        _ => return None,
+    };
+    let curricular_year = match get_claim(degree, &mapping.curricular_year_field)? {
+        Value::String(year) => year.parse().ok()?,
+        Value::Number(year) => year.as_u64()?.try_into().ok()?,
+        _ => return None,
+    };
+    let nomination_elegible = get_claim(degree, &mapping.nomination_eligible_field)
+        .and_then(Value::as_bool)
+        .unwrap_or(true);
+
+    Some(DegreeEntryDto {
+        degree_id,
+        curricular_year,
+        nomination_elegible,
+    })
+}
+
+fn get_claim<'a>(claims: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .try_fold(claims, |value, segment| value.get(segment))
+}
+
+fn env_or(name: &str, default: &str) -> String {
+    env::var(name).unwrap_or(default.to_string())
+}
+
+/// Client shared by all requests to the provider, so that a slow one can't hold up logins forever.
+fn build_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .expect("failed to build HTTP client")
+}
+
+#[derive(Deserialize, Clone)]
+struct DiscoveryResponse {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn mapping() -> ClaimMapping {
+        ClaimMapping {
+            username: "preferred_username".to_string(),
+            name: "name".to_string(),
+            display_name: "nickname".to_string(),
+            degrees: "student.degrees".to_string(),
+            degree_id_field: "id".to_string(),
+            curricular_year_field: "year".to_string(),
+            nomination_eligible_field: "eligible".to_string(),
+        }
+    }
+
+    fn provider() -> OidcIdentityProvider {
+        OidcIdentityProvider {
+            // nothing listens here, so any request to the provider fails
+            issuer_url: "http://127.0.0.1:9".to_string(),
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_url: "http://localhost/login".to_string(),
+            scopes: "openid".to_string(),
+            claims: mapping(),
+            client: build_client(),
+            cached_discovery: Cached::new("oidc_discovery", CACHE_DURATION),
+        }
+    }
+
+    #[tokio::test]
+    async fn login_url_does_not_need_provider() {
+        assert_eq!(provider().login_url().await.unwrap(), LOGIN_START_PATH);
+    }
+
+    #[tokio::test]
+    async fn authorization_url_includes_state() {
+        let provider = provider();
+        provider
+            .cached_discovery
+            .set(DiscoveryResponse {
+                authorization_endpoint: "https://id.example.com/authorize".to_string(),
+                token_endpoint: "https://id.example.com/token".to_string(),
+                userinfo_endpoint: "https://id.example.com/userinfo".to_string(),
+            })
+            .await;
+
+        let url = provider.authorization_url("abc123").await.unwrap();
+        let url = reqwest::Url::parse(&url).unwrap();
+        assert_eq!(url.path(), "/authorize");
+        assert!(url
+            .query_pairs()
+            .any(|(key, value)| key == "state" && value == "abc123"));
+    }
+
+    #[tokio::test]
+    async fn invalid_states_are_rejected() {
+        let provider = provider();
+        for (received, expected) in [
+            (Some("abc"), Some("def")),
+            (Some("abc"), None),
+            (None, Some("abc")),
+            (None, None),
+        ] {
+            let state = LoginState { received, expected };
+            // rejected before ever reaching the provider
+            assert!(matches!(
+                provider.authenticate_from_code("code", state).await,
+                Err(AppError::Unauthorized)
+            ));
+        }
+    }
+
+    #[test]
+    fn map_claims() {
+        let claims = json!({
+            "preferred_username": "ist1123456",
+            "name": "John Doe",
+            "nickname": "John",
+            "student": {
+                "degrees": [
+                    { "id": "123456", "year": 2 },
+                    { "id": 654321, "year": "1", "eligible": false },
+                ]
+            }
+        });
+
+        let user = map_claims_to_user(&claims, &mapping()).unwrap();
+
+        assert_eq!(user.username, "ist1123456");
+        assert_eq!(user.name, "John Doe");
+        assert_eq!(user.display_name, "John");
+        assert_eq!(user.degree_entries.len(), 2);
+        assert_eq!(user.degree_entries[0].degree_id, "123456");
+        assert_eq!(user.degree_entries[0].curricular_year, 2);
+        assert!(user.degree_entries[0].nomination_elegible);
+        assert_eq!(user.degree_entries[1].degree_id, "654321");
+        assert_eq!(user.degree_entries[1].curricular_year, 1);
+        assert!(!user.degree_entries[1].nomination_elegible);
+    }
+
+    #[test]
+    fn map_claims_without_optional_claims() {
+        let claims = json!({ "preferred_username": "ist1123456" });
+
+        let user = map_claims_to_user(&claims, &mapping()).unwrap();
+
+        assert_eq!(user.name, "ist1123456");
+        assert_eq!(user.display_name, "ist1123456");
+        assert!(user.degree_entries.is_empty());
+    }
+
+    #[test]
+    fn map_claims_without_username() {
+        let claims = json!({ "name": "John Doe" });
+
+        assert!(map_claims_to_user(&claims, &mapping()).is_none());
+    }
+
+    #[test]
+    fn map_claims_with_invalid_degrees() {
+        let claims = json!({
+            "preferred_username": "ist1123456",
+            "student": {
+                "degrees": [
+                    { "year": 2 },
+                    { "id": "123456" },
+                    { "id": "123456", "year": 1000 },
+                    { "id": "654321", "year": 3 },
+                ]
+            }
+        });
+
+        let user = map_claims_to_user(&claims, &mapping()).unwrap();
+
+        assert_eq!(user.degree_entries.len(), 1);
+        assert_eq!(user.degree_entries[0].degree_id, "654321");
+    }
+}
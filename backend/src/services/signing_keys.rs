@@ -0,0 +1,392 @@
+use std::{env, time::Duration};
+
+use chrono::NaiveDateTime;
+
+use entity::signing_key::{self, Entity as SigningKeyEntity};
+use rand::Rng;
+use sea_orm::{
+    prelude::*, sea_query::Expr, DatabaseConnection, QueryOrder, Set, SqlErr, TransactionTrait,
+};
+use tracing::{debug, info};
+
+use crate::{
+    cache::Cached,
+    crypto_utils::{self, SigningKey, SigningKeyring},
+    errors::AppError,
+    settings::parse_env_number,
+};
+
+const KEY_LENGTH: usize = 64;
+const DEFAULT_ROTATION_INTERVAL_DAYS: i64 = 7;
+const DEFAULT_GRACE_PERIOD_HOURS: i64 = 24;
+
+// keys are reloaded periodically so that rotations made by other instances are picked up
+const CACHE_DURATION: Duration = Duration::from_secs(60); // 1 minute
+
+#[derive(Clone)]
+enum KeySource {
+    /// Keys given through configuration, which are never rotated automatically.
+    Static(SigningKeyring),
+    /// Keys stored in the database, shared by all instances and rotated periodically.
+    Database {
+        conn: DatabaseConnection,
+        rotation_interval: chrono::Duration,
+        grace_period: chrono::Duration,
+    },
+}
+
+/// Provides the keys used to sign person search results.
+#[derive(Clone)]
+pub struct SigningKeyService {
+    source: KeySource,
+    cached_keyring: Cached<SigningKeyring>,
+}
+
+impl SigningKeyService {
+    /// Create a new SigningKeyService instance from environment variables.
+    ///
+    /// If SIGNING_KEY is defined (as a hex string of at least 32 bytes), it is used
+    /// as the current key, and any keys in SIGNING_KEY_PREVIOUS (comma separated) are
+    /// still accepted when validating signatures.
+    /// Otherwise, keys are stored in the database and rotated every SIGNING_KEY_ROTATION_DAYS
+    /// days (7 by default), with the previous key being accepted for SIGNING_KEY_GRACE_PERIOD_HOURS
+    /// hours (24 by default) after it is replaced.
+    pub fn new(conn: DatabaseConnection) -> Result<SigningKeyService, String> {
+        let source = match env::var("SIGNING_KEY") {
+            Ok(key) => {
+                let current = parse_key(&key)?;
+                let previous = env::var("SIGNING_KEY_PREVIOUS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|key| !key.is_empty())
+                    .map(parse_key)
+                    .collect::<Result<_, _>>()?;
+
+                debug!("using signing key '{}' from configuration", current.id);
+                KeySource::Static(SigningKeyring { current, previous })
+            }
+            Err(_) => KeySource::Database {
+                conn,
+                rotation_interval: chrono::Duration::days(parse_env_number(
+                    "SIGNING_KEY_ROTATION_DAYS",
+                    DEFAULT_ROTATION_INTERVAL_DAYS,
+                )?),
+                grace_period: chrono::Duration::hours(parse_env_number(
+                    "SIGNING_KEY_GRACE_PERIOD_HOURS",
+                    DEFAULT_GRACE_PERIOD_HOURS,
+                )?),
+            },
+        };
+
+        Ok(SigningKeyService {
+            source,
+            cached_keyring: Cached::new("signing_keyring", CACHE_DURATION),
+        })
+    }
+
+    /// Get the current signing key, as well as the previous keys that are still valid.
+    /// When keys are stored in the database, this rotates the current key if it is too old.
+    pub async fn get_keyring(&self) -> Result<SigningKeyring, AppError> {
+        match &self.source {
+            KeySource::Static(keyring) => Ok(keyring.clone()),
+            KeySource::Database {
+                conn,
+                rotation_interval,
+                grace_period,
+            } => {
+                let conn = conn.clone();
+                let (rotation_interval, grace_period) = (*rotation_interval, *grace_period);
+                self.cached_keyring
+                    .get(move || async move {
+                        let now = chrono::Utc::now().naive_utc();
+                        load_or_rotate_keyring(&conn, now, rotation_interval, grace_period).await
+                    })
+                    .await
+            }
+        }
+    }
+
+    /// Get a keyring that can validate the given signature, if possible.
+    /// If the signature was made with a key that isn't in the cached keyring, the keys are
+    /// reloaded, since that key might have just been created by another instance.
+    pub async fn get_keyring_for(&self, signature: &str) -> Result<SigningKeyring, AppError> {
+        let keyring = self.get_keyring().await?;
+
+        let KeySource::Database {
+            conn,
+            rotation_interval,
+            grace_period,
+        } = &self.source
+        else {
+            return Ok(keyring);
+        };
+        match crypto_utils::get_signature_key_id(signature) {
+            Some(key_id) if !keyring.has_key(key_id) => {
+                let now = chrono::Utc::now().naive_utc();
+                let keyring =
+                    load_or_rotate_keyring(conn, now, *rotation_interval, *grace_period).await?;
+                self.cached_keyring.set(keyring.clone()).await;
+
+                Ok(keyring)
+            }
+            _ => Ok(keyring),
+        }
+    }
+}
+
+/// How many times rotating the key is attempted, in case the new key conflicts
+/// with one created at the same time by another instance.
+const MAX_ROTATION_ATTEMPTS: usize = 3;
+
+async fn load_or_rotate_keyring(
+    conn: &DatabaseConnection,
+    now: NaiveDateTime,
+    rotation_interval: chrono::Duration,
+    grace_period: chrono::Duration,
+) -> Result<SigningKeyring, AppError> {
+    // a key stops being current after the rotation interval,
+    // but is still accepted for the duration of the grace period after being replaced
+    SigningKeyEntity::delete_many()
+        .filter(signing_key::Column::RetiredAt.lt(now - grace_period))
+        .exec(conn)
+        .await?;
+
+    for _ in 0..MAX_ROTATION_ATTEMPTS {
+        let keys = SigningKeyEntity::find()
+            .order_by_desc(signing_key::Column::CreatedAt)
+            .all(conn)
+            .await?;
+
+        let latest = keys.first();
+        let is_current = latest.is_some_and(|latest| {
+            latest.retired_at.is_none() && latest.created_at > now - rotation_interval
+        });
+        if is_current {
+            let mut keys = keys.iter().filter_map(to_signing_key);
+            if let Some(current) = keys.next() {
+                return Ok(SigningKeyring {
+                    current,
+                    previous: keys.collect(),
+                });
+            }
+        }
+
+        rotate_key(conn, now, latest).await?;
+    }
+
+    Err(AppError::DbError(DbErr::Custom(
+        "failed to rotate the signing key".to_string(),
+    )))
+}
+
+/// Replace the latest key with a new one, retiring every key that hasn't been retired yet.
+/// Returns false if the latest key has already been replaced, by another instance.
+async fn rotate_key(
+    conn: &DatabaseConnection,
+    now: NaiveDateTime,
+    latest: Option<&signing_key::Model>,
+) -> Result<bool, AppError> {
+    let mut key = vec![0u8; KEY_LENGTH];
+    rand::thread_rng().fill(key.as_mut_slice());
+    let key = SigningKey {
+        id: hex::encode(rand::thread_rng().gen::<[u8; 4]>()),
+        key,
+    };
+
+    let txn = conn.begin().await?;
+
+    SigningKeyEntity::update_many()
+        .col_expr(signing_key::Column::RetiredAt, Expr::value(now))
+        .filter(signing_key::Column::RetiredAt.is_null())
+        .exec(&txn)
+        .await?;
+
+    let inserted = signing_key::ActiveModel {
+        id: Set(key.id.clone()),
+        key: Set(hex::encode(&key.key)),
+        created_at: Set(now),
+        retired_at: Set(None),
+        // the first key doesn't replace any other, but must still be unique
+        replaces: Set(Some(
+            latest.map(|latest| latest.id.clone()).unwrap_or_default(),
+        )),
+    }
+    .insert(&txn)
+    .await;
+
+    match inserted {
+        Ok(_) => {
+            txn.commit().await?;
+            info!("rotated person search signing key, new key is '{}'", key.id);
+            Ok(true)
+        }
+        Err(err) if matches!(err.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) => {
+            debug!("signing key was rotated concurrently");
+            Ok(false)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+fn to_signing_key(model: &signing_key::Model) -> Option<SigningKey> {
+    Some(SigningKey {
+        key: hex::decode(&model.key).ok()?,
+        id: model.id.clone(),
+    })
+}
+
+fn parse_key(key: &str) -> Result<SigningKey, String> {
+    let key = hex::decode(key).map_err(|_| "Invalid signing key: not a hex string")?;
+    if key.len() < 32 {
+        return Err("Invalid signing key: must be at least 32 bytes long".to_string());
+    }
+
+    Ok(SigningKey::from_key(key))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use migration::{MigratorTrait, SqliteMigrator};
+    use sea_orm::Database;
+
+    use super::*;
+
+    const ROTATION_INTERVAL: chrono::Duration = chrono::Duration::days(7);
+    const GRACE_PERIOD: chrono::Duration = chrono::Duration::hours(24);
+
+    async fn get_conn() -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+        SqliteMigrator::up(&conn, None).await.unwrap();
+
+        conn
+    }
+
+    async fn get_keyring(conn: &DatabaseConnection, now: NaiveDateTime) -> SigningKeyring {
+        load_or_rotate_keyring(conn, now, ROTATION_INTERVAL, GRACE_PERIOD)
+            .await
+            .unwrap()
+    }
+
+    fn previous_ids(keyring: &SigningKeyring) -> Vec<&str> {
+        keyring.previous.iter().map(|key| key.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_rotation() {
+        let conn = get_conn().await;
+        let now = Utc::now().naive_utc();
+
+        let keyring = get_keyring(&conn, now).await;
+        assert!(keyring.previous.is_empty());
+        let first = keyring.current.id;
+
+        let keyring = get_keyring(&conn, now + chrono::Duration::days(6)).await;
+        assert_eq!(keyring.current.id, first);
+
+        let rotated_at = now + ROTATION_INTERVAL + chrono::Duration::minutes(1);
+        let keyring = get_keyring(&conn, rotated_at).await;
+        assert_ne!(keyring.current.id, first);
+        assert_eq!(previous_ids(&keyring), [first.as_str()]);
+
+        let retired = SigningKeyEntity::find_by_id(&first)
+            .one(&conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(retired.retired_at, Some(rotated_at));
+    }
+
+    #[tokio::test]
+    async fn test_grace_period_starts_when_replaced() {
+        let conn = get_conn().await;
+        let now = Utc::now().naive_utc();
+
+        let first = get_keyring(&conn, now).await.current.id;
+
+        // rotating late still accepts the previous key for the whole grace period
+        let rotated_at = now + ROTATION_INTERVAL + GRACE_PERIOD + chrono::Duration::hours(1);
+        let keyring = get_keyring(&conn, rotated_at).await;
+        assert_eq!(previous_ids(&keyring), [first.as_str()]);
+
+        let keyring = get_keyring(&conn, rotated_at + GRACE_PERIOD).await;
+        assert_eq!(previous_ids(&keyring), [first.as_str()]);
+
+        // and deletes it afterwards
+        let keyring = get_keyring(
+            &conn,
+            rotated_at + GRACE_PERIOD + chrono::Duration::seconds(1),
+        )
+        .await;
+        assert!(keyring.previous.is_empty());
+        assert!(SigningKeyEntity::find_by_id(&first)
+            .one(&conn)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_rotations() {
+        let conn = get_conn().await;
+        let now = Utc::now().naive_utc();
+
+        assert!(rotate_key(&conn, now, None).await.unwrap());
+        assert!(!rotate_key(&conn, now, None).await.unwrap());
+
+        let first = SigningKeyEntity::find().one(&conn).await.unwrap().unwrap();
+        let later = now + ROTATION_INTERVAL;
+        assert!(rotate_key(&conn, later, Some(&first)).await.unwrap());
+        assert!(!rotate_key(&conn, later, Some(&first)).await.unwrap());
+
+        // every instance ends up with the same keyring
+        let keyring = get_keyring(&conn, later).await;
+        assert_eq!(previous_ids(&keyring), [first.id.as_str()]);
+        assert_eq!(SigningKeyEntity::find().count(&conn).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_keys_are_reloaded() {
+        let conn = get_conn().await;
+        let service = SigningKeyService {
+            source: KeySource::Database {
+                conn: conn.clone(),
+                rotation_interval: ROTATION_INTERVAL,
+                grace_period: GRACE_PERIOD,
+            },
+            cached_keyring: Cached::new("signing_keyring", CACHE_DURATION),
+        };
+
+        let first = service.get_keyring().await.unwrap().current;
+
+        // another instance rotates the key
+        let model = SigningKeyEntity::find_by_id(&first.id)
+            .one(&conn)
+            .await
+            .unwrap()
+            .unwrap();
+        rotate_key(&conn, Utc::now().naive_utc(), Some(&model))
+            .await
+            .unwrap();
+        let new_key = SigningKeyEntity::find()
+            .filter(signing_key::Column::RetiredAt.is_null())
+            .one(&conn)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let keyring = service
+            .get_keyring_for(&format!("{}:00", first.id))
+            .await
+            .unwrap();
+        assert_eq!(keyring.current.id, first.id);
+
+        let keyring = service
+            .get_keyring_for(&format!("{}:00", new_key.id))
+            .await
+            .unwrap();
+        assert_eq!(keyring.current.id, new_key.id);
+        assert_eq!(service.get_keyring().await.unwrap().current.id, new_key.id);
+    }
+}
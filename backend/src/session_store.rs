@@ -0,0 +1,528 @@
+use std::{env, time::Duration};
+
+use async_session::{async_trait, serde_json, MemoryStore, Session, SessionStore};
+use axum::{http::Request, middleware::Next, response::Response};
+use axum_sessions::SessionHandle;
+use entity::session::{self, Entity as SessionEntity};
+use migration::OnConflict;
+use sea_orm::{
+    prelude::*, sea_query::Expr, ActiveValue, Condition, DatabaseConnection, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::errors::AppError;
+
+/// How often expired sessions are purged from the store.
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60 * 60); // 1 hour
+
+/// How old the last activity of a session must be for it to be updated.
+const ACTIVITY_UPDATE_INTERVAL: chrono::Duration = chrono::Duration::minutes(5);
+
+/// Key of the session entry that holds the [`SessionMetadata`].
+pub const SESSION_METADATA_KEY: &str = "metadata";
+
+/// Information about a logged in session, used to let users and admins manage sessions.
+/// It is stored in the session itself, and mirrored to dedicated columns in the database.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SessionMetadata {
+    pub username: String,
+    pub user_agent: Option<String>,
+    pub login_time: DateTimeUtc,
+    pub last_activity: DateTimeUtc,
+}
+
+/// Keep track of when each logged in session was last used.
+/// The activity is only updated once in a while, since changing the session makes it be
+/// stored again (and its cookie sent again) at the end of the request. This is also what
+/// extends the expiry of sessions that are in use.
+pub async fn track_session_activity<B>(request: Request<B>, next: Next<B>) -> Response {
+    if let Some(session_handle) = request.extensions().get::<SessionHandle>() {
+        let now = chrono::Utc::now();
+        let is_stale =
+            |metadata: &SessionMetadata| now - metadata.last_activity >= ACTIVITY_UPDATE_INTERVAL;
+
+        let metadata = session_handle
+            .read()
+            .await
+            .get::<SessionMetadata>(SESSION_METADATA_KEY);
+        if metadata.as_ref().is_some_and(is_stale) {
+            let mut session = session_handle.write().await;
+            if let Some(mut metadata) = session.get::<SessionMetadata>(SESSION_METADATA_KEY) {
+                metadata.last_activity = now;
+                if let Err(err) = session.insert(SESSION_METADATA_KEY, metadata) {
+                    warn!("failed to update session activity: {}", err);
+                }
+            }
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Session ids are base64 encoded, so convert them to the URL-safe alphabet
+/// in order to be used in paths.
+pub fn to_public_session_id(id: &str) -> String {
+    id.replace('+', "-").replace('/', "_")
+}
+
+fn from_public_session_id(id: &str) -> String {
+    id.replace('-', "+").replace('_', "/")
+}
+
+/// Session store used by the session layer.
+///
+/// Defaults to storing sessions in the database, so that they survive restarts
+/// and can be shared between multiple instances. The in-memory store can be
+/// selected with `SESSION_STORE=memory`, which is useful for development.
+#[derive(Clone, Debug)]
+pub enum AppSessionStore {
+    Memory(MemoryStore),
+    Database(DatabaseSessionStore),
+}
+
+impl AppSessionStore {
+    /// Create a new session store according to the SESSION_STORE env variable,
+    /// which can either be "database" (default) or "memory".
+    pub fn from_env(conn: &DatabaseConnection) -> Result<Self, String> {
+        match env::var("SESSION_STORE").as_deref() {
+            Err(_) | Ok("database") => Ok(Self::Database(DatabaseSessionStore::new(conn.clone()))),
+            Ok("memory") => {
+                warn!("using in-memory session store, sessions will be lost on restart");
+                Ok(Self::Memory(MemoryStore::new()))
+            }
+            Ok(other) => Err(format!(
+                "Invalid SESSION_STORE '{}': must be either 'database' or 'memory'",
+                other
+            )),
+        }
+    }
+
+    /// Name of the kind of store, as accepted by SESSION_STORE.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Memory(_) => "memory",
+            Self::Database(_) => "database",
+        }
+    }
+
+    /// Check whether sessions can be loaded from the store.
+    pub async fn ping(&self) -> Result<(), AppError> {
+        match self {
+            Self::Memory(_) => Ok(()),
+            Self::Database(store) => {
+                SessionEntity::find().one(&store.conn).await?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Remove all expired sessions from the store.
+    pub async fn cleanup(&self) -> async_session::Result {
+        match self {
+            Self::Memory(store) => store.cleanup().await,
+            Self::Database(store) => store.cleanup().await,
+        }
+    }
+
+    /// List all active sessions of the given user, most recently used first.
+    /// This is not supported by the in-memory store.
+    pub async fn list_user_sessions(
+        &self,
+        username: &str,
+    ) -> Result<Vec<session::Model>, AppError> {
+        match self {
+            Self::Memory(_) => Err(AppError::UnsupportedSessionStore),
+            Self::Database(store) => store.list_user_sessions(username).await,
+        }
+    }
+
+    /// Revoke the session with the given public id, returning the session that was revoked,
+    /// if any. This is not supported by the in-memory store.
+    pub async fn revoke_session(
+        &self,
+        public_id: &str,
+    ) -> Result<Option<session::Model>, AppError> {
+        match self {
+            Self::Memory(_) => Err(AppError::UnsupportedSessionStore),
+            Self::Database(store) => store.revoke_session(public_id).await,
+        }
+    }
+
+    /// Periodically remove expired sessions from the store in the background.
+    pub fn spawn_cleanup_task(&self) {
+        let store = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CLEANUP_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(err) = store.cleanup().await {
+                    warn!("failed to clean up expired sessions: {}", err);
+                }
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl SessionStore for AppSessionStore {
+    async fn load_session(&self, cookie_value: String) -> async_session::Result<Option<Session>> {
+        match self {
+            Self::Memory(store) => store.load_session(cookie_value).await,
+            Self::Database(store) => store.load_session(cookie_value).await,
+        }
+    }
+
+    async fn store_session(&self, session: Session) -> async_session::Result<Option<String>> {
+        match self {
+            Self::Memory(store) => store.store_session(session).await,
+            Self::Database(store) => store.store_session(session).await,
+        }
+    }
+
+    async fn destroy_session(&self, session: Session) -> async_session::Result {
+        match self {
+            Self::Memory(store) => store.destroy_session(session).await,
+            Self::Database(store) => store.destroy_session(session).await,
+        }
+    }
+
+    async fn clear_store(&self) -> async_session::Result {
+        match self {
+            Self::Memory(store) => store.clear_store().await,
+            Self::Database(store) => store.clear_store().await,
+        }
+    }
+}
+
+/// Session store backed by the `session` table.
+/// Sessions are serialized as JSON, alongside their expiry date.
+#[derive(Clone, Debug)]
+pub struct DatabaseSessionStore {
+    conn: DatabaseConnection,
+}
+
+impl DatabaseSessionStore {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self { conn }
+    }
+
+    /// Remove all expired sessions from the database.
+    pub async fn cleanup(&self) -> async_session::Result {
+        let now = chrono::Utc::now().naive_utc();
+
+        let res = SessionEntity::delete_many()
+            .filter(session::Column::ExpiresAt.lt(now))
+            .exec(&self.conn)
+            .await?;
+        debug!("cleaned up {} expired sessions", res.rows_affected);
+
+        Ok(())
+    }
+
+    async fn list_user_sessions(&self, username: &str) -> Result<Vec<session::Model>, AppError> {
+        let now = chrono::Utc::now().naive_utc();
+
+        Ok(SessionEntity::find()
+            .filter(
+                Condition::all()
+                    .add(session::Column::Username.eq(username))
+                    .add(session::Column::RevokedAt.is_null())
+                    .add(
+                        Condition::any()
+                            .add(session::Column::ExpiresAt.is_null())
+                            .add(session::Column::ExpiresAt.gt(now)),
+                    ),
+            )
+            .order_by_desc(session::Column::LastActivity)
+            .all(&self.conn)
+            .await?)
+    }
+
+    /// Revoke a session, keeping it until it expires so that it can't be stored again.
+    async fn revoke_session(&self, public_id: &str) -> Result<Option<session::Model>, AppError> {
+        let id = from_public_session_id(public_id);
+
+        let session = SessionEntity::find_by_id(id)
+            .filter(session::Column::RevokedAt.is_null())
+            .one(&self.conn)
+            .await?;
+        if let Some(session) = &session {
+            SessionEntity::update_many()
+                .col_expr(
+                    session::Column::RevokedAt,
+                    Expr::value(chrono::Utc::now().naive_utc()),
+                )
+                .filter(session::Column::Id.eq(&session.id))
+                .exec(&self.conn)
+                .await?;
+        }
+
+        Ok(session)
+    }
+}
+
+#[async_trait]
+impl SessionStore for DatabaseSessionStore {
+    async fn load_session(&self, cookie_value: String) -> async_session::Result<Option<Session>> {
+        let id = Session::id_from_cookie_value(&cookie_value)?;
+        let now = chrono::Utc::now().naive_utc();
+
+        let model = SessionEntity::find_by_id(id)
+            .filter(
+                Condition::all()
+                    .add(session::Column::RevokedAt.is_null())
+                    .add(
+                        Condition::any()
+                            .add(session::Column::ExpiresAt.is_null())
+                            .add(session::Column::ExpiresAt.gt(now)),
+                    ),
+            )
+            .one(&self.conn)
+            .await?;
+
+        match model {
+            Some(model) => {
+                let session: Session = serde_json::from_str(&model.data)?;
+                Ok(session.validate())
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn store_session(&self, session: Session) -> async_session::Result<Option<String>> {
+        let metadata = session.get::<SessionMetadata>(SESSION_METADATA_KEY);
+        let model = session::ActiveModel {
+            id: ActiveValue::set(session.id().to_string()),
+            expires_at: ActiveValue::set(session.expiry().map(|expiry| expiry.naive_utc())),
+            data: ActiveValue::set(serde_json::to_string(&session)?),
+            username: ActiveValue::set(metadata.as_ref().map(|m| m.username.clone())),
+            user_agent: ActiveValue::set(metadata.as_ref().and_then(|m| m.user_agent.clone())),
+            login_time: ActiveValue::set(metadata.as_ref().map(|m| m.login_time.naive_utc())),
+            last_activity: ActiveValue::set(metadata.as_ref().map(|m| m.last_activity.naive_utc())),
+            revoked_at: ActiveValue::set(None),
+        };
+
+        // revoked sessions are never updated, since that would undo their revocation
+        let rows_affected = SessionEntity::insert(model)
+            .on_conflict(
+                OnConflict::column(session::Column::Id)
+                    .update_columns([
+                        session::Column::ExpiresAt,
+                        session::Column::Data,
+                        session::Column::Username,
+                        session::Column::UserAgent,
+                        session::Column::LoginTime,
+                        session::Column::LastActivity,
+                    ])
+                    .action_and_where(
+                        Expr::col((SessionEntity, session::Column::RevokedAt)).is_null(),
+                    )
+                    .to_owned(),
+            )
+            .exec_without_returning(&self.conn)
+            .await?;
+        if rows_affected == 0 {
+            return Ok(None);
+        }
+
+        session.reset_data_changed();
+        Ok(session.into_cookie_value())
+    }
+
+    async fn destroy_session(&self, session: Session) -> async_session::Result {
+        SessionEntity::delete_by_id(session.id())
+            .exec(&self.conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn clear_store(&self) -> async_session::Result {
+        SessionEntity::delete_many().exec(&self.conn).await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use migration::{MigratorTrait, SqliteMigrator};
+    use sea_orm::Database;
+
+    use super::*;
+
+    async fn get_store() -> DatabaseSessionStore {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+        SqliteMigrator::up(&conn, None).await.unwrap();
+
+        DatabaseSessionStore::new(conn)
+    }
+
+    fn get_session(username: &str) -> Session {
+        let now = Utc::now();
+        let mut session = Session::new();
+        session
+            .insert(
+                SESSION_METADATA_KEY,
+                SessionMetadata {
+                    username: username.to_string(),
+                    user_agent: Some("test".to_string()),
+                    login_time: now,
+                    last_activity: now,
+                },
+            )
+            .unwrap();
+        session.expire_in(Duration::from_secs(60 * 60));
+
+        session
+    }
+
+    fn get_expired_session() -> Session {
+        let mut session = get_session("ist1100001");
+        session.set_expiry(Utc::now() - chrono::Duration::hours(1));
+
+        session
+    }
+
+    async fn count_sessions(store: &DatabaseSessionStore) -> u64 {
+        SessionEntity::find().count(&store.conn).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_store_and_load_session() {
+        let store = get_store().await;
+
+        let mut session = get_session("ist1100000");
+        session.insert("answer", 42).unwrap();
+        let cookie_value = store.store_session(session).await.unwrap().unwrap();
+
+        let mut session = store
+            .load_session(cookie_value.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(session.get::<u32>("answer"), Some(42));
+
+        let model = SessionEntity::find_by_id(session.id())
+            .one(&store.conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(model.username.as_deref(), Some("ist1100000"));
+        assert_eq!(model.user_agent.as_deref(), Some("test"));
+
+        // storing a session again updates it
+        session.insert("answer", 43).unwrap();
+        store.store_session(session).await.unwrap();
+        let session = store.load_session(cookie_value).await.unwrap().unwrap();
+        assert_eq!(session.get::<u32>("answer"), Some(43));
+        assert_eq!(count_sessions(&store).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_sessions_are_not_loaded() {
+        let store = get_store().await;
+
+        let cookie_value = store
+            .store_session(get_expired_session())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(store.load_session(cookie_value).await.unwrap().is_none());
+
+        let unknown = Session::new().into_cookie_value().unwrap();
+        assert!(store.load_session(unknown).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup() {
+        let store = get_store().await;
+
+        store.store_session(get_expired_session()).await.unwrap();
+        let cookie_value = store
+            .store_session(get_session("ist1100000"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        store.cleanup().await.unwrap();
+        assert_eq!(count_sessions(&store).await, 1);
+        assert!(store.load_session(cookie_value).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_task() {
+        let store = get_store().await;
+        store.store_session(get_expired_session()).await.unwrap();
+        store
+            .store_session(get_session("ist1100000"))
+            .await
+            .unwrap();
+
+        // the first cleanup happens right away
+        AppSessionStore::Database(store.clone()).spawn_cleanup_task();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while count_sessions(&store).await > 1 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("expired session to be cleaned up");
+    }
+
+    #[tokio::test]
+    async fn test_revoked_sessions_are_not_stored_again() {
+        let store = get_store().await;
+
+        let cookie_value = store
+            .store_session(get_session("ist1100000"))
+            .await
+            .unwrap()
+            .unwrap();
+
+        // a request that was in flight when the session was revoked stores it afterwards
+        let mut in_flight = store
+            .load_session(cookie_value.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        let public_id = to_public_session_id(in_flight.id());
+        assert!(store.revoke_session(&public_id).await.unwrap().is_some());
+        in_flight.insert("answer", 42).unwrap();
+        assert!(store.store_session(in_flight).await.unwrap().is_none());
+
+        assert!(store.load_session(cookie_value).await.unwrap().is_none());
+        assert!(store
+            .list_user_sessions("ist1100000")
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(store.revoke_session(&public_id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_destroy_and_clear() {
+        let store = get_store().await;
+
+        let session = get_session("ist1100000");
+        let cookie_value = store.store_session(session).await.unwrap().unwrap();
+        store
+            .store_session(get_session("ist1100001"))
+            .await
+            .unwrap();
+
+        let session = store
+            .load_session(cookie_value.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        store.destroy_session(session).await.unwrap();
+        assert!(store.load_session(cookie_value).await.unwrap().is_none());
+        assert_eq!(count_sessions(&store).await, 1);
+
+        store.clear_store().await.unwrap();
+        assert_eq!(count_sessions(&store).await, 0);
+    }
+}
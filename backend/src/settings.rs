@@ -0,0 +1,52 @@
+use std::env;
+
+use entity::setting::{self, Entity as Setting};
+use migration::OnConflict;
+use sea_orm::{prelude::*, ConnectionTrait, Set};
+
+/// Academic year used by elections, overrides and results instead of Fénix's active year.
+pub const PINNED_ACADEMIC_YEAR: &str = "pinned_academic_year";
+
+/// Get the value of a setting changed by admins, if it has been set.
+pub async fn get_setting<C: ConnectionTrait>(conn: &C, key: &str) -> Result<Option<String>, DbErr> {
+    Ok(Setting::find_by_id(key)
+        .one(conn)
+        .await?
+        .map(|setting| setting.value))
+}
+
+/// Set the value of a setting, or reset it to its default if the value is None.
+pub async fn set_setting<C: ConnectionTrait>(
+    conn: &C,
+    key: &str,
+    value: Option<String>,
+) -> Result<(), DbErr> {
+    let Some(value) = value else {
+        Setting::delete_by_id(key).exec(conn).await?;
+        return Ok(());
+    };
+
+    Setting::insert(setting::ActiveModel {
+        key: Set(key.to_string()),
+        value: Set(value),
+        updated_at: Set(chrono::Utc::now().naive_utc()),
+    })
+    .on_conflict(
+        OnConflict::column(setting::Column::Key)
+            .update_columns([setting::Column::Value, setting::Column::UpdatedAt])
+            .to_owned(),
+    )
+    .exec(conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Parse a number from the given env variable, or use the default if it isn't defined.
+pub(crate) fn parse_env_number(name: &str, default: i64) -> Result<i64, String> {
+    env::var(name).map_or(Ok(default), |value| {
+        value
+            .parse()
+            .map_err(|_| format!("Environment variable {} must be a number", name))
+    })
+}
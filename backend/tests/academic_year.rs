@@ -0,0 +1,172 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A};
+use serde_json::{json, Value};
+
+/// Get the academic years of the elections of LEIC-A listed to admins.
+async fn listed_election_years(admin: &common::TestClient) -> Vec<Value> {
+    let degrees = admin.get("/api/degrees/elections").await.json();
+    degrees
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|degree| degree["degree"]["id"] == LEIC_A)
+        .unwrap()["elections"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|election| election["academicYear"].clone())
+        .collect()
+}
+
+#[tokio::test]
+async fn pin_academic_year() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    app.login("ist1100000")
+        .await
+        .put("/api/academic-year", json!({ "academicYear": "2022/2023" }))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    let academic_year = admin.get("/api/academic-year").await.json();
+    assert_eq!(
+        academic_year,
+        json!({ "academicYear": "2023/2024", "fenixAcademicYear": "2023/2024", "pinned": false })
+    );
+
+    for invalid in ["2022/2024", "2022-2023", "last year"] {
+        admin
+            .put("/api/academic-year", json!({ "academicYear": invalid }))
+            .await
+            .assert_error(StatusCode::BAD_REQUEST, "error.academic-year.invalid");
+    }
+
+    let academic_year = admin
+        .put("/api/academic-year", json!({ "academicYear": "2022/2023" }))
+        .await
+        .json();
+    assert_eq!(
+        academic_year,
+        json!({ "academicYear": "2022/2023", "fenixAcademicYear": "2023/2024", "pinned": true })
+    );
+
+    let config = app.anonymous().get("/api/config").await.json();
+    assert_eq!(config["pinnedAcademicYear"], "2022/2023");
+
+    // elections are created in, and listed from, the pinned year
+    let now = Utc::now();
+    admin
+        .post(
+            "/api/elections/bulk",
+            json!({
+                "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+                "round": 1,
+                "degrees": [{ "degreeId": LEIC_A, "curricularYear": 1 }],
+            }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(
+        listed_election_years(&admin).await,
+        vec![json!("2022/2023")]
+    );
+
+    admin
+        .put("/api/academic-year", json!({ "academicYear": null }))
+        .await
+        .assert_status(StatusCode::OK);
+
+    let config = app.anonymous().get("/api/config").await.json();
+    assert!(config["pinnedAcademicYear"].is_null());
+    assert!(listed_election_years(&admin).await.is_empty());
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn past_academic_years() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    let now = Utc::now();
+    let elections = json!({
+        "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+        "round": 1,
+        "degrees": [{ "degreeId": LEIC_A, "curricularYear": 1 }],
+    });
+    let overrides = json!({ "degreeId": LEIC_A, "curricularYear": 2, "usernames": ["ist1100000"] });
+
+    // create elections and overrides in the previous year, then in the active one
+    admin
+        .put("/api/academic-year", json!({ "academicYear": "2022/2023" }))
+        .await
+        .assert_status(StatusCode::OK);
+    admin
+        .post("/api/elections/bulk", elections.clone())
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    admin
+        .post("/api/user-degree-overrides", overrides)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    admin
+        .put("/api/academic-year", json!({ "academicYear": null }))
+        .await
+        .assert_status(StatusCode::OK);
+    admin
+        .post("/api/elections/bulk", elections)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    app.login("ist1100000")
+        .await
+        .get("/api/academic-years")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    assert_eq!(
+        admin.get("/api/academic-years").await.json(),
+        json!(["2023/2024", "2022/2023"])
+    );
+
+    let elections = admin
+        .get("/api/degrees/elections?academic_year=2022/2023")
+        .await
+        .json();
+    let years: Vec<_> = elections
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|degree| degree["elections"].as_array().unwrap())
+        .map(|election| election["academicYear"].clone())
+        .collect();
+    assert_eq!(years, vec![json!("2022/2023")]);
+    assert_eq!(
+        listed_election_years(&admin).await,
+        vec![json!("2023/2024")]
+    );
+
+    assert_eq!(
+        admin.get("/api/user-degree-overrides").await.json(),
+        json!([])
+    );
+    let overrides = admin
+        .get("/api/user-degree-overrides?academic_year=2022/2023")
+        .await
+        .json();
+    assert_eq!(overrides[0]["users"][0]["username"], "ist1100000");
+
+    admin
+        .get("/api/elections/nominations/unverified?academic_year=2022/2023")
+        .await
+        .assert_status(StatusCode::OK);
+    admin
+        .get("/api/elections/results/download/csv?academic_year=2022/2023")
+        .await
+        .assert_status(StatusCode::OK);
+
+    app.teardown().await;
+}
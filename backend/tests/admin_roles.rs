@@ -0,0 +1,241 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A, MEIC_A};
+use entity::{
+    election::{self, Entity as Election},
+    nomination,
+};
+use sea_orm::{prelude::*, ActiveValue::Set};
+use serde_json::{json, Value};
+
+fn elections_for(degree_ids: &[&str]) -> Value {
+    let now = Utc::now();
+
+    json!({
+        "candidacyPeriod": { "start": now - Duration::hours(1), "end": now + Duration::days(1) },
+        "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+        "round": 1,
+        "degrees": degree_ids
+            .iter()
+            .map(|degree_id| json!({ "degreeId": degree_id, "curricularYear": null }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+#[tokio::test]
+async fn roles_are_scoped() {
+    let app = TestApp::new().await;
+    let super_admin = app.setup_admin("ist1100005").await;
+
+    for admin in [
+        json!({ "username": "ist1100000", "role": "DEGREE_COORDINATOR", "scope": LEIC_A }),
+        json!({ "username": "ist1100003", "role": "DEGREE_TYPE_COORDINATOR", "scope": "Mestrado Bolonha" }),
+        json!({ "username": "ist1100001", "role": "AUDITOR" }),
+    ] {
+        super_admin
+            .post("/api/admin", admin)
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    for invalid in [
+        json!({ "username": "ist1100002", "role": "DEGREE_COORDINATOR" }),
+        json!({ "username": "ist1100002", "role": "DEGREE_COORDINATOR", "scope": "123" }),
+        json!({ "username": "ist1100002", "role": "DEGREE_TYPE_COORDINATOR", "scope": "Doutoramento" }),
+        json!({ "username": "ist1100002", "role": "AUDITOR", "scope": LEIC_A }),
+    ] {
+        super_admin
+            .post("/api/admin", invalid)
+            .await
+            .assert_error(StatusCode::BAD_REQUEST, "error.admin.scope.invalid");
+    }
+
+    let admins = super_admin.get("/api/admins").await.json();
+    assert_eq!(admins[0]["role"], "SUPER_ADMIN");
+    assert_eq!(admins[1]["role"], "DEGREE_COORDINATOR");
+    assert_eq!(admins[1]["scope"], LEIC_A);
+
+    let leic_coordinator = app.login("ist1100000").await;
+    let masters_coordinator = app.login("ist1100003").await;
+    let auditor = app.login("ist1100001").await;
+
+    let whoami = leic_coordinator.get("/api/whoami").await.json();
+    assert_eq!(whoami["isAdmin"], true);
+    assert_eq!(whoami["adminRole"], "DEGREE_COORDINATOR");
+
+    // elections can only be created for the degrees in the admin's scope
+    leic_coordinator
+        .post("/api/elections/bulk", elections_for(&[LEIC_A, MEIC_A]))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    auditor
+        .post("/api/elections/bulk", elections_for(&[LEIC_A]))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    leic_coordinator
+        .post("/api/elections/bulk", elections_for(&[LEIC_A]))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    masters_coordinator
+        .post("/api/elections/bulk", elections_for(&[MEIC_A]))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    // and so can nominations
+    let meic_election = Election::find()
+        .filter(election::Column::DegreeId.eq(MEIC_A))
+        .one(&app.conn)
+        .await
+        .unwrap()
+        .unwrap();
+    let edit_uri = format!("/api/election/{}/nomination", meic_election.id);
+    let edit = json!({ "username": "ist1100004", "valid": true });
+    leic_coordinator
+        .patch(&edit_uri, edit.clone())
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    auditor
+        .patch(&edit_uri, edit.clone())
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    masters_coordinator
+        .patch(&edit_uri, edit)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "error.unknown.nomination");
+
+    // everyone can see the admins, but only super admins can manage them
+    for admin in [&leic_coordinator, &masters_coordinator, &auditor] {
+        admin.get("/api/admins").await.assert_status(StatusCode::OK);
+        admin
+            .post("/api/admin", json!({ "username": "ist1100002" }))
+            .await
+            .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    }
+
+    // there must always be a super admin left
+    super_admin
+        .delete("/api/admin/ist1100001", Value::Null)
+        .await
+        .assert_status(StatusCode::ACCEPTED);
+    super_admin
+        .delete("/api/admin/ist1100005", Value::Null)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "error.not.enough.admins");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn reads_are_scoped() {
+    let app = TestApp::new().await;
+    let super_admin = app.setup_admin("ist1100005").await;
+    super_admin
+        .post(
+            "/api/admin",
+            json!({ "username": "ist1100000", "role": "DEGREE_COORDINATOR", "scope": LEIC_A }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let leic_coordinator = app.login("ist1100000").await;
+
+    super_admin
+        .post("/api/elections/bulk", elections_for(&[LEIC_A, MEIC_A]))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let elections = Election::find().all(&app.conn).await.unwrap();
+    let election_of = |degree_id| {
+        elections
+            .iter()
+            .find(|election| election.degree_id == degree_id)
+            .unwrap()
+            .id
+    };
+    for election in &elections {
+        nomination::ActiveModel {
+            election: Set(election.id),
+            username: Set("ist1100004".to_string()),
+            display_name: Set("Rui".to_string()),
+            valid: Set(None),
+        }
+        .insert(&app.conn)
+        .await
+        .unwrap();
+    }
+    for degree_id in [LEIC_A, MEIC_A] {
+        super_admin
+            .post(
+                "/api/user-degree-overrides",
+                json!({ "degreeId": degree_id, "curricularYear": 1, "usernames": ["ist1100002"] }),
+            )
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    // coordinators can only see the data of the degrees they can manage
+    leic_coordinator
+        .get(&format!("/api/election/{}/details", election_of(MEIC_A)))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    leic_coordinator
+        .get(&format!("/api/election/{}/details", election_of(LEIC_A)))
+        .await
+        .assert_status(StatusCode::OK);
+
+    let degrees = leic_coordinator.get("/api/degrees/elections").await.json();
+    let degrees = degrees.as_array().unwrap();
+    assert_eq!(degrees.len(), 1);
+    assert_eq!(degrees[0]["degree"]["id"], LEIC_A);
+
+    let unverified = leic_coordinator
+        .get("/api/elections/nominations/unverified")
+        .await
+        .json();
+    let unverified = unverified.as_array().unwrap();
+    assert_eq!(unverified.len(), 1);
+    assert_eq!(unverified[0]["id"], election_of(LEIC_A));
+
+    let overrides = leic_coordinator
+        .get("/api/user-degree-overrides")
+        .await
+        .json();
+    let overrides = overrides.as_array().unwrap();
+    assert_eq!(overrides.len(), 1);
+    assert_eq!(overrides[0]["degree"]["id"], LEIC_A);
+
+    for election in &elections {
+        app.time_travel(election.id, Duration::days(4)).await;
+    }
+    let results = leic_coordinator
+        .get("/api/elections/results/download/csv")
+        .await
+        .body;
+    assert!(results.contains("LEIC-A"));
+    assert!(!results.contains("MEIC-A"));
+
+    // while super admins see everything
+    assert_eq!(
+        super_admin
+            .get("/api/degrees/elections")
+            .await
+            .json()
+            .as_array()
+            .unwrap()
+            .len(),
+        super_admin
+            .get("/api/degrees")
+            .await
+            .json()
+            .as_array()
+            .unwrap()
+            .len()
+    );
+    let results = super_admin
+        .get("/api/elections/results/download/csv")
+        .await
+        .body;
+    assert!(results.contains("LEIC-A") && results.contains("MEIC-A"));
+
+    app.teardown().await;
+}
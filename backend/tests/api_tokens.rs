@@ -0,0 +1,226 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A};
+use entity::api_token::{self, Entity as ApiToken};
+use sea_orm::{prelude::*, sea_query::Expr};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn tokens_act_as_their_admin() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+    let expires_at = Utc::now() + Duration::days(30);
+
+    for (token, error) in [
+        (
+            json!({ "name": " ", "scopes": ["READ"], "expiresAt": expires_at }),
+            "error.api-token.name.empty",
+        ),
+        (
+            json!({ "name": "results", "scopes": [], "expiresAt": expires_at }),
+            "error.api-token.scopes.empty",
+        ),
+        (
+            json!({ "name": "results", "scopes": ["READ"], "expiresAt": Utc::now() - Duration::hours(1) }),
+            "error.api-token.expiry.invalid",
+        ),
+    ] {
+        admin
+            .post("/api/api-token", token)
+            .await
+            .assert_error(StatusCode::BAD_REQUEST, error);
+    }
+
+    let created = admin
+        .post(
+            "/api/api-token",
+            json!({ "name": "results", "scopes": ["READ"], "expiresAt": expires_at }),
+        )
+        .await
+        .json();
+    assert_eq!(created["token"]["username"], "ist1100005");
+    assert_eq!(created["token"]["scopes"], json!(["READ"]));
+    assert!(created["token"]["lastUsedAt"].is_null());
+    let reader = app.with_token(created["secret"].as_str().unwrap());
+
+    reader
+        .get("/api/elections/results/download/csv")
+        .await
+        .assert_status(StatusCode::OK);
+    reader
+        .get("/api/degrees/elections")
+        .await
+        .assert_status(StatusCode::OK);
+    reader
+        .post(
+            "/api/user-degree-overrides",
+            json!({ "degreeId": LEIC_A, "curricularYear": 1, "usernames": ["ist1100004"] }),
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.api-token.scope");
+
+    let tokens = admin.get("/api/api-tokens").await.json();
+    assert_eq!(tokens[0]["name"], "results");
+    assert!(tokens[0]["lastUsedAt"].is_string());
+
+    let writer = admin
+        .post(
+            "/api/api-token",
+            json!({ "name": "overrides", "scopes": ["WRITE", "READ", "WRITE"], "expiresAt": expires_at }),
+        )
+        .await
+        .json();
+    assert_eq!(writer["token"]["scopes"], json!(["READ", "WRITE"]));
+    let writer = app.with_token(writer["secret"].as_str().unwrap());
+    writer
+        .post(
+            "/api/user-degree-overrides",
+            json!({ "degreeId": LEIC_A, "curricularYear": 1, "usernames": ["ist1100004"] }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    // changes are recorded as made by the admin
+    let entries = admin.get("/api/audit-log").await.json();
+    assert_eq!(entries[0]["action"], "user-degree-overrides.add");
+    assert_eq!(entries[0]["actor"], "ist1100005");
+
+    // tokens can't create other tokens
+    writer
+        .post(
+            "/api/api-token",
+            json!({ "name": "another", "scopes": ["READ"], "expiresAt": expires_at }),
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.session-required");
+
+    app.anonymous()
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+    app.with_token("ide_invalid")
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn tokens_expire_and_can_be_revoked() {
+    let app = TestApp::new().await;
+    let super_admin = app.setup_admin("ist1100005").await;
+    super_admin
+        .post(
+            "/api/admin",
+            json!({ "username": "ist1100000", "role": "DEGREE_COORDINATOR", "scope": LEIC_A }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let coordinator = app.login("ist1100000").await;
+
+    let expires_at = Utc::now() + Duration::days(30);
+    let created = coordinator
+        .post(
+            "/api/api-token",
+            json!({ "name": "script", "scopes": ["READ", "WRITE"], "expiresAt": expires_at }),
+        )
+        .await
+        .json();
+    let token_id = created["token"]["id"].as_i64().unwrap() as i32;
+    let token = app.with_token(created["secret"].as_str().unwrap());
+
+    // tokens have the role of their admin
+    token
+        .post("/api/admin", json!({ "username": "ist1100001" }))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    // admins only see their own tokens, but super admins see all of them
+    super_admin
+        .post(
+            "/api/api-token",
+            json!({ "name": "other", "scopes": ["READ"], "expiresAt": expires_at }),
+        )
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(
+        coordinator
+            .get("/api/api-tokens")
+            .await
+            .json()
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        super_admin
+            .get("/api/api-tokens")
+            .await
+            .json()
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // expired token
+    ApiToken::update_many()
+        .col_expr(
+            api_token::Column::ExpiresAt,
+            Expr::value((Utc::now() - Duration::hours(1)).naive_utc()),
+        )
+        .filter(api_token::Column::Id.eq(token_id))
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    token
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+
+    // revoked token
+    coordinator
+        .delete(&format!("/api/api-token/{token_id}"), Value::Null)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    coordinator
+        .delete(&format!("/api/api-token/{token_id}"), Value::Null)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "error.unknown.api-token");
+
+    // removing an admin removes their tokens
+    let created = coordinator
+        .post(
+            "/api/api-token",
+            json!({ "name": "script", "scopes": ["READ"], "expiresAt": expires_at }),
+        )
+        .await
+        .json();
+    let token = app.with_token(created["secret"].as_str().unwrap());
+    let removal = super_admin
+        .delete("/api/admin/ist1100000", Value::Null)
+        .await
+        .json();
+    super_admin
+        .post("/api/admin", json!({ "username": "ist1100001" }))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.login("ist1100001")
+        .await
+        .post(
+            &format!("/api/pending-action/{}/approve", removal["id"]),
+            Value::Null,
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    token
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+
+    app.teardown().await;
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{TestApp, LEIC_A};
+use entity::audit_log::{self, Entity as AuditLog};
+use ist_delegate_election::audit;
+use sea_orm::{prelude::*, sea_query::Expr};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn admin_actions_are_audited() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    admin
+        .post("/api/admin", json!({ "username": "ist1100001" }))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    admin
+        .post(
+            "/api/user-degree-overrides",
+            json!({ "degreeId": LEIC_A, "curricularYear": 2, "usernames": ["ist1100000"] }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let removal = admin
+        .delete("/api/admin/ist1100001", Value::Null)
+        .await
+        .json();
+    app.login("ist1100001")
+        .await
+        .post(
+            &format!("/api/pending-action/{}/approve", removal["id"]),
+            Value::Null,
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    app.login("ist1100000")
+        .await
+        .get("/api/audit-log")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    let entries = admin.get("/api/audit-log").await.json();
+    let entries = entries.as_array().unwrap();
+    let actions: Vec<_> = entries
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "admin.remove",
+            "pending-action.approve",
+            "pending-action.propose",
+            "user-degree-overrides.add",
+            "admin.add",
+            "admin.setup"
+        ]
+    );
+
+    let removal = &entries[0];
+    assert_eq!(removal["actor"], "ist1100001");
+    assert_eq!(removal["target"], "ist1100001");
+    assert_eq!(removal["before"]["username"], "ist1100001");
+    assert!(removal["after"].is_null());
+
+    assert_eq!(entries[2]["actor"], "ist1100005");
+    assert_eq!(entries[2]["after"]["action"]["username"], "ist1100001");
+
+    let overrides = &entries[3];
+    assert_eq!(overrides["target"], LEIC_A);
+    assert_eq!(overrides["before"]["users"], json!([]));
+    assert_eq!(
+        overrides["after"]["users"],
+        json!([{ "username": "ist1100000", "curricularYear": 2 }])
+    );
+
+    // paging
+    let page = admin.get("/api/audit-log?before=3&limit=1").await.json();
+    assert_eq!(page.as_array().unwrap().len(), 1);
+    assert_eq!(page[0]["action"], "admin.add");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn tampering_is_detected() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+    for username in ["ist1100000", "ist1100001", "ist1100002"] {
+        admin
+            .post("/api/admin", json!({ "username": username }))
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+
+    let verification = admin.get("/api/audit-log/verify").await.json();
+    assert_eq!(verification["valid"], true);
+    assert_eq!(verification["entries"], 4);
+    assert!(verification["lastHash"].is_string());
+
+    // editing an entry
+    AuditLog::update_many()
+        .col_expr(audit_log::Column::Actor, Expr::value("ist1100000"))
+        .filter(audit_log::Column::Id.eq(2))
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    let verification = admin.get("/api/audit-log/verify").await.json();
+    assert_eq!(verification["valid"], false);
+    assert_eq!(verification["entries"], 1);
+    assert_eq!(
+        verification["issue"],
+        json!({ "id": 2, "kind": "HASH_MISMATCH" })
+    );
+
+    // editing an entry along with its hash
+    let mut entry = AuditLog::find_by_id(2)
+        .one(&app.conn)
+        .await
+        .unwrap()
+        .unwrap();
+    entry.hash = audit::compute_hash(&entry);
+    AuditLog::update_many()
+        .col_expr(audit_log::Column::Hash, Expr::value(entry.hash))
+        .filter(audit_log::Column::Id.eq(2))
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    let verification = admin.get("/api/audit-log/verify").await.json();
+    assert_eq!(
+        verification["issue"],
+        json!({ "id": 3, "kind": "BROKEN_CHAIN" })
+    );
+
+    // removing an entry
+    AuditLog::delete_by_id(3).exec(&app.conn).await.unwrap();
+    let verification = admin.get("/api/audit-log/verify").await.json();
+    assert_eq!(verification["issue"], json!({ "id": 4, "kind": "GAP" }));
+
+    app.teardown().await;
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use std::env;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use ist_delegate_election::routes::admin;
+use serde_json::Value;
+
+// this is the only test in this file, since env variables are shared by the whole process
+#[tokio::test]
+async fn bootstrap_admin_disables_setup() {
+    env::set_var("BOOTSTRAP_ADMIN", "ist1100004");
+    let app = TestApp::new().await;
+
+    assert_eq!(
+        app.anonymous().get("/api/config").await.json()["isSetup"],
+        true
+    );
+    app.login("ist1100000")
+        .await
+        .post("/api/setup/admin", Value::Null)
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    assert!(admin::bootstrap_admin(&app.conn).await.unwrap());
+    // only done while there are no admins
+    assert!(!admin::bootstrap_admin(&app.conn).await.unwrap());
+
+    let admins = app
+        .login("ist1100004")
+        .await
+        .get("/api/admins")
+        .await
+        .json();
+    assert_eq!(admins.as_array().unwrap().len(), 1);
+    assert_eq!(admins[0]["role"], "SUPER_ADMIN");
+
+    app.teardown().await;
+}
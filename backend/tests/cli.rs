@@ -0,0 +1,309 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A};
+use entity::{
+    audit_log::{self, Entity as AuditLog},
+    election::{self, Entity as Election},
+    election_vote::{self, Entity as ElectionVote},
+    nomination, vote_log,
+};
+use ist_delegate_election::{
+    cli::{self, AdminCommand, ManageCommand, MigrateCommand, ResultsFormat},
+    dtos::AdminRoleDto,
+    errors::AppError,
+};
+use sea_orm::{prelude::*, sea_query::Expr, ActiveValue::Set};
+
+async fn run(app: &TestApp, command: AdminCommand) -> Result<String, AppError> {
+    let mut out = Vec::new();
+    cli::run_admin_command(&app.conn, &app.state.fenix_service, command, &mut out).await?;
+
+    Ok(String::from_utf8(out).unwrap())
+}
+
+async fn run_command(app: &TestApp, command: ManageCommand) -> Result<(bool, String), AppError> {
+    let mut out = Vec::new();
+    let succeeded = cli::run_command(&app.state, command, &mut out).await?;
+
+    Ok((succeeded, String::from_utf8(out).unwrap()))
+}
+
+async fn run_migrate(app: &TestApp, command: MigrateCommand) -> String {
+    let mut out = Vec::new();
+    cli::run_migrate_command(&app.conn, command, &mut out)
+        .await
+        .unwrap();
+
+    String::from_utf8(out).unwrap()
+}
+
+fn add(username: &str, role: AdminRoleDto, scope: Option<&str>) -> AdminCommand {
+    AdminCommand::Add {
+        username: username.to_string(),
+        role,
+        scope: scope.map(str::to_string),
+    }
+}
+
+#[tokio::test]
+async fn admins_are_managed_from_the_cli() {
+    let app = TestApp::new().await;
+
+    let output = run(&app, add("ist1100005", AdminRoleDto::SuperAdmin, None))
+        .await
+        .unwrap();
+    assert_eq!(output, "added ist1100005 as SUPER_ADMIN\n");
+    run(
+        &app,
+        add("ist1100000", AdminRoleDto::DegreeCoordinator, Some(LEIC_A)),
+    )
+    .await
+    .unwrap();
+
+    let error = run(
+        &app,
+        add("ist1100001", AdminRoleDto::DegreeCoordinator, None),
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.to_string(), "error.admin.scope.invalid");
+    let error = run(&app, add("ist1100005", AdminRoleDto::Auditor, None))
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "error.duplicate.admin");
+
+    let output = run(&app, AdminCommand::List).await.unwrap();
+    let admins: Vec<Vec<_>> = output
+        .lines()
+        .map(|line| line.split('\t').take(3).collect())
+        .collect();
+    assert_eq!(
+        admins,
+        [
+            ["ist1100005", "SUPER_ADMIN", "-"],
+            ["ist1100000", "DEGREE_COORDINATOR", LEIC_A],
+        ]
+    );
+
+    // the web app can't be set up by someone else anymore
+    let admin = app.login("ist1100005").await;
+    admin.get("/api/admins").await.assert_status(StatusCode::OK);
+    app.login("ist1100001")
+        .await
+        .post("/api/setup/admin", serde_json::Value::Null)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    // removals don't need approval, but there must still be a super admin
+    let error = run(
+        &app,
+        AdminCommand::Remove {
+            username: "ist1100005".to_string(),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.to_string(), "error.not.enough.admins");
+    run(
+        &app,
+        AdminCommand::Remove {
+            username: "ist1100000".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+
+    let entries = admin.get("/api/audit-log").await.json();
+    assert_eq!(entries[0]["action"], "admin.remove");
+    assert_eq!(entries[0]["actor"], "system");
+    assert_eq!(entries[0]["target"], "ist1100000");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn migrations_are_managed_from_the_cli() {
+    let app = TestApp::new().await;
+
+    let status = run_migrate(&app, MigrateCommand::Status).await;
+    assert!(status.lines().all(|line| line.starts_with("Applied\t")));
+    let last = status.lines().last().unwrap().to_string();
+
+    let status = run_migrate(&app, MigrateCommand::Down { steps: 1 }).await;
+    assert_eq!(
+        status.lines().last().unwrap(),
+        last.replace("Applied", "Pending")
+    );
+    let status = run_migrate(&app, MigrateCommand::Up).await;
+    assert_eq!(status.lines().last().unwrap(), last);
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn elections_are_managed_from_the_cli() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    let (_, output) = run_command(&app, ManageCommand::SeedDemo).await.unwrap();
+    assert!(output.starts_with("created 2 demo elections for "));
+    let error = run_command(&app, ManageCommand::SeedDemo)
+        .await
+        .unwrap_err();
+    assert_eq!(error.to_string(), "error.duplicate.election");
+
+    // overrides are only imported if all of their degrees exist
+    let csv_path = std::env::temp_dir().join(format!("overrides-{}.csv", std::process::id()));
+    std::fs::write(
+        &csv_path,
+        "username,degree,curricular_year\nist1100004,leic-a,2\nist1100003,UNKNOWN,1\n",
+    )
+    .unwrap();
+    let error = run_command(
+        &app,
+        ManageCommand::ImportOverrides {
+            csv: csv_path.clone(),
+        },
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error.to_string(), "error.degree.invalid");
+
+    // internal errors explain what went wrong
+    std::fs::write(
+        &csv_path,
+        "username,degree,curricular_year\nist1100004,leic-a,second\n",
+    )
+    .unwrap();
+    let error = run_command(
+        &app,
+        ManageCommand::ImportOverrides {
+            csv: csv_path.clone(),
+        },
+    )
+    .await
+    .unwrap_err();
+    let error = error.to_string();
+    assert!(error.starts_with("error.internal: "), "{}", error);
+    assert!(error.contains("invalid digit"), "{}", error);
+
+    std::fs::write(
+        &csv_path,
+        format!("username,degree,curricular_year\nist1100004,leic-a,2\nist1100003,{LEIC_A},2\n"),
+    )
+    .unwrap();
+    let (_, output) = run_command(
+        &app,
+        ManageCommand::ImportOverrides {
+            csv: csv_path.clone(),
+        },
+    )
+    .await
+    .unwrap();
+    std::fs::remove_file(&csv_path).unwrap();
+    assert_eq!(output, "imported 2 overrides to LEIC-A, year 2\n");
+    let overrides = admin.get("/api/user-degree-overrides").await.json();
+    assert_eq!(overrides[0]["users"].as_array().unwrap().len(), 2);
+
+    // end an election with a single vote
+    let election = Election::find()
+        .filter(election::Column::DegreeId.eq(LEIC_A))
+        .one(&app.conn)
+        .await
+        .unwrap()
+        .unwrap();
+    Election::update_many()
+        .col_expr(
+            election::Column::VotingPeriodEnd,
+            Expr::value((Utc::now() - Duration::hours(1)).naive_utc()),
+        )
+        .filter(election::Column::Id.eq(election.id))
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    nomination::ActiveModel {
+        election: Set(election.id),
+        username: Set("ist1100002".to_string()),
+        display_name: Set("Ana Ferreira".to_string()),
+        valid: Set(Some(true)),
+    }
+    .insert(&app.conn)
+    .await
+    .unwrap();
+    election_vote::ActiveModel {
+        election: Set(election.id),
+        nomination_username: Set("ist1100002".to_string()),
+        count: Set(1),
+    }
+    .insert(&app.conn)
+    .await
+    .unwrap();
+    vote_log::ActiveModel {
+        election: Set(election.id),
+        voter: Set("ist1100000".to_string()),
+    }
+    .insert(&app.conn)
+    .await
+    .unwrap();
+
+    let (_, csv) = run_command(
+        &app,
+        ManageCommand::ExportResults {
+            year: None,
+            format: ResultsFormat::Csv,
+        },
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        csv,
+        admin.get("/api/elections/results/download/csv").await.body
+    );
+    let (_, json) = run_command(
+        &app,
+        ManageCommand::ExportResults {
+            year: None,
+            format: ResultsFormat::Json,
+        },
+    )
+    .await
+    .unwrap();
+    let results: serde_json::Value = serde_json::from_str(&json).unwrap();
+    assert_eq!(results[0]["degree"], "LEIC-A");
+    assert_eq!(results[0]["username"], "ist1100002");
+    assert_eq!(results[0]["vote_count"], 1);
+
+    let (valid, output) = run_command(&app, ManageCommand::CheckIntegrity)
+        .await
+        .unwrap();
+    assert!(valid);
+    assert!(output.ends_with("votes: 1 elections checked\n"));
+
+    ElectionVote::update_many()
+        .col_expr(election_vote::Column::Count, Expr::value(2))
+        .filter(election_vote::Column::Election.eq(election.id))
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    AuditLog::update_many()
+        .col_expr(audit_log::Column::Actor, Expr::value("ist1100000"))
+        .filter(audit_log::Column::Id.eq(2))
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    let (valid, output) = run_command(&app, ManageCommand::CheckIntegrity)
+        .await
+        .unwrap();
+    assert!(!valid);
+    assert_eq!(
+        output,
+        format!(
+            "audit log: HashMismatch at entry 2\nelection {}: 2 votes, but only 1 voters\nvotes: 1 elections checked\n",
+            election.id
+        )
+    );
+
+    app.teardown().await;
+}
@@ -0,0 +1,347 @@
+//! Test harness that boots the whole application router against a fresh database
+//! and a mock Fénix server.
+//!
+//! By default, each test gets its own in-memory SQLite database. To run the same tests
+//! against Postgres, set TEST_DATABASE_URL to a database the tests can connect to:
+//! a new database is created (and dropped afterwards) for each test.
+
+#![allow(dead_code)] // not all helpers are used by every test file
+
+use std::{
+    env,
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+};
+
+use async_session::MemoryStore;
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, Method, Request, StatusCode},
+    Router,
+};
+use entity::election::{self, Entity as Election};
+use ist_delegate_election::{
+    clock::Clock,
+    pending_actions::ApprovalDeadline,
+    services::{fenix::FenixService, signing_keys::SigningKeyService},
+    session_store::{AppSessionStore, DatabaseSessionStore},
+    AppState,
+};
+use migration::{Migrator, MigratorTrait, SqliteMigrator};
+use mock_fenix::Fixtures;
+use rand::Rng;
+use sea_orm::{
+    prelude::*, ActiveValue, ConnectOptions, ConnectionTrait, Database, DatabaseConnection,
+    DbBackend, Statement,
+};
+use serde_json::Value;
+use tower::ServiceExt;
+
+pub const LEIC_A: &str = "2761663971475";
+pub const MEIC_A: &str = "2761663971585";
+
+/// The application under test, along with direct access to its database.
+pub struct TestApp {
+    pub router: Router,
+    pub conn: DatabaseConnection,
+    pub state: AppState,
+    postgres_database: Option<(String, String)>,
+}
+
+impl TestApp {
+    /// Boot the application with the default mock Fénix fixtures.
+    pub async fn new() -> TestApp {
+        TestApp::with_fixtures(Fixtures::default()).await
+    }
+
+    pub async fn with_fixtures(fixtures: Fixtures) -> TestApp {
+        TestApp::boot(fixtures, false).await
+    }
+
+    /// Boot the application keeping sessions in memory, as with SESSION_STORE=memory.
+    pub async fn with_memory_sessions() -> TestApp {
+        TestApp::boot(Fixtures::default(), true).await
+    }
+
+    async fn boot(fixtures: Fixtures, memory_sessions: bool) -> TestApp {
+        let fenix_url = spawn_mock_fenix(fixtures).await;
+        let (conn, postgres_database) = connect_database().await;
+        match conn.get_database_backend() {
+            DbBackend::Sqlite => SqliteMigrator::up(&conn, None).await,
+            _ => Migrator::up(&conn, None).await,
+        }
+        .expect("Migration failed");
+
+        let fenix_service = FenixService::with_config(
+            fenix_url,
+            "client-id".to_string(),
+            "client-secret".to_string(),
+            "http://localhost/login".to_string(),
+        )
+        .with_database(conn.clone());
+        let signing_key_service =
+            SigningKeyService::new(conn.clone()).expect("Failed to initialize SigningKeyService");
+        // generate the first signing key before any request, instead of in the middle of one
+        assert!(
+            signing_key_service.get_keyring().await.is_ok(),
+            "Failed to generate signing key"
+        );
+
+        let state = AppState {
+            identity_provider: Arc::new(fenix_service.clone()),
+            fenix_service,
+            conn: conn.clone(),
+            signing_key_service,
+            session_store: if memory_sessions {
+                AppSessionStore::Memory(MemoryStore::new())
+            } else {
+                AppSessionStore::Database(DatabaseSessionStore::new(conn.clone()))
+            },
+            clock: Clock::default(),
+            approval_deadline: ApprovalDeadline::default(),
+        };
+
+        let mut session_secret = [0u8; 64];
+        rand::thread_rng().fill(&mut session_secret);
+
+        TestApp {
+            router: ist_delegate_election::build_router(state.clone(), &session_secret),
+            conn,
+            state,
+            postgres_database,
+        }
+    }
+
+    /// A client without any session.
+    pub fn anonymous(&self) -> TestClient {
+        TestClient {
+            router: self.router.clone(),
+            cookie: Arc::new(Mutex::new(None)),
+            authorization: None,
+        }
+    }
+
+    /// A client that authenticates with the given API token, instead of a session.
+    pub fn with_token(&self, secret: &str) -> TestClient {
+        TestClient {
+            authorization: Some(format!("Bearer {secret}")),
+            ..self.anonymous()
+        }
+    }
+
+    /// A client logged in as the given fixture user.
+    pub async fn login(&self, username: &str) -> TestClient {
+        let client = self.anonymous();
+        let start = client.get("/api/login/start").await;
+        let authorization_url = start.headers[header::LOCATION].to_str().unwrap();
+        let state = reqwest::Url::parse(authorization_url)
+            .unwrap()
+            .query_pairs()
+            .find(|(name, _)| name == "state")
+            .map(|(_, state)| state.into_owned());
+        let response = client
+            .post(
+                "/api/login",
+                serde_json::json!({ "code": username, "state": state }),
+            )
+            .await;
+        assert_eq!(
+            response.status,
+            StatusCode::OK,
+            "login as {username} failed"
+        );
+
+        client
+    }
+
+    /// Log in as the given user, and make them the first admin.
+    pub async fn setup_admin(&self, username: &str) -> TestClient {
+        let client = self.login(username).await;
+        client
+            .post("/api/setup/admin", Value::Null)
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+
+        client
+    }
+
+    /// Move the periods of an election, as if the given amount of time had passed.
+    pub async fn time_travel(&self, election_id: i32, duration: chrono::Duration) {
+        let election = Election::find_by_id(election_id)
+            .one(&self.conn)
+            .await
+            .unwrap()
+            .expect("election must exist");
+
+        let shift = |date: DateTime| date - duration;
+        election::ActiveModel {
+            id: ActiveValue::Unchanged(election.id),
+            candidacy_period_start: ActiveValue::Set(election.candidacy_period_start.map(shift)),
+            candidacy_period_end: ActiveValue::Set(election.candidacy_period_end.map(shift)),
+            voting_period_start: ActiveValue::Set(shift(election.voting_period_start)),
+            voting_period_end: ActiveValue::Set(shift(election.voting_period_end)),
+            ..Default::default()
+        }
+        .update(&self.conn)
+        .await
+        .unwrap();
+    }
+
+    /// Drop the per-test Postgres database, if any.
+    /// This is not done when a test fails, so that the database can be inspected.
+    pub async fn teardown(self) {
+        drop(self.router);
+        self.conn.close().await.unwrap();
+
+        if let Some((admin_url, database)) = self.postgres_database {
+            let admin_conn = Database::connect(admin_url).await.unwrap();
+            admin_conn
+                .execute_unprepared(&format!("DROP DATABASE \"{}\" WITH (FORCE)", database))
+                .await
+                .unwrap();
+        }
+    }
+}
+
+/// A client that keeps the session cookie between requests, like a browser.
+#[derive(Clone)]
+pub struct TestClient {
+    router: Router,
+    cookie: Arc<Mutex<Option<String>>>,
+    authorization: Option<String>,
+}
+
+impl TestClient {
+    pub async fn get(&self, uri: &str) -> TestResponse {
+        self.request(Method::GET, uri, None).await
+    }
+
+    pub async fn post(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::POST, uri, Some(body)).await
+    }
+
+    pub async fn put(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::PUT, uri, Some(body)).await
+    }
+
+    pub async fn patch(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::PATCH, uri, Some(body)).await
+    }
+
+    pub async fn delete(&self, uri: &str, body: Value) -> TestResponse {
+        self.request(Method::DELETE, uri, Some(body)).await
+    }
+
+    async fn request(&self, method: Method, uri: &str, body: Option<Value>) -> TestResponse {
+        let mut request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::USER_AGENT, "integration-tests");
+        if let Some(cookie) = self.cookie.lock().unwrap().as_ref() {
+            request = request.header(header::COOKIE, cookie);
+        }
+        if let Some(authorization) = &self.authorization {
+            request = request.header(header::AUTHORIZATION, authorization);
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = self.router.clone().oneshot(request).await.unwrap();
+
+        if let Some(set_cookie) = response.headers().get(header::SET_COOKIE) {
+            let cookie = set_cookie.to_str().unwrap();
+            let cookie = cookie.split(';').next().unwrap_or(cookie);
+            *self.cookie.lock().unwrap() = Some(cookie.to_string());
+        }
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+
+        TestResponse {
+            status,
+            headers,
+            content_type,
+            body: String::from_utf8(body.to_vec()).unwrap(),
+        }
+    }
+}
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub content_type: Option<String>,
+    pub body: String,
+}
+
+impl TestResponse {
+    pub fn json(&self) -> Value {
+        serde_json::from_str(&self.body)
+            .unwrap_or_else(|_| panic!("response is not JSON: {}", self.body))
+    }
+
+    #[track_caller]
+    pub fn assert_status(&self, status: StatusCode) -> &Self {
+        assert_eq!(self.status, status, "unexpected response: {}", self.body);
+        self
+    }
+
+    /// Assert that the request failed with the given error key.
+    #[track_caller]
+    pub fn assert_error(&self, status: StatusCode, key: &str) {
+        self.assert_status(status);
+        assert_eq!(self.json()["key"], key);
+    }
+}
+
+/// Serve the mock Fénix API on an ephemeral port, returning its base URL.
+async fn spawn_mock_fenix(fixtures: Fixtures) -> String {
+    let server = axum::Server::bind(&SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+        .serve(mock_fenix::router(fixtures).into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    format!("http://{}", addr)
+}
+
+/// Connect to a fresh database: in-memory SQLite by default, or a new Postgres
+/// database if TEST_DATABASE_URL is set.
+async fn connect_database() -> (DatabaseConnection, Option<(String, String)>) {
+    let Ok(admin_url) = env::var("TEST_DATABASE_URL") else {
+        let conn = Database::connect("sqlite::memory:")
+            .await
+            .expect("Database connection failed");
+        return (conn, None);
+    };
+
+    let database = format!("election_test_{:016x}", rand::thread_rng().gen::<u64>());
+    let admin_conn = Database::connect(&admin_url)
+        .await
+        .expect("Database connection failed");
+    admin_conn
+        .execute(Statement::from_string(
+            admin_conn.get_database_backend(),
+            format!("CREATE DATABASE \"{}\"", database),
+        ))
+        .await
+        .expect("Failed to create test database");
+    admin_conn.close().await.unwrap();
+
+    let mut url = reqwest::Url::parse(&admin_url).expect("Invalid TEST_DATABASE_URL");
+    url.set_path(&database);
+    let conn = Database::connect(ConnectOptions::new(url.to_string()))
+        .await
+        .expect("Database connection failed");
+
+    (conn, Some((admin_url, database)))
+}
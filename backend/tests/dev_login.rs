@@ -0,0 +1,77 @@
+#![cfg(feature = "dev-login")]
+
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A};
+use serde_json::json;
+
+#[tokio::test]
+async fn dev_login_as_any_user() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    let now = Utc::now();
+    admin
+        .post(
+            "/api/elections/bulk",
+            json!({
+                "candidacyPeriod": { "start": now - Duration::hours(1), "end": now + Duration::days(1) },
+                "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+                "round": 1,
+                "degrees": [{ "degreeId": LEIC_A, "curricularYear": 2 }],
+            }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let client = app.anonymous();
+    let auth = client
+        .post(
+            "/api/dev/login",
+            json!({
+                "username": "ist1999999",
+                "name": "Demo Student",
+                "degreeEntries": [
+                    { "degreeId": LEIC_A, "curricularYear": 2, "nominationElegible": true },
+                ],
+            }),
+        )
+        .await
+        .json();
+    assert_eq!(auth["user"]["displayName"], "Demo Student");
+    assert_eq!(auth["isAdmin"], false);
+
+    assert_eq!(client.get("/api/whoami").await.json(), auth);
+    let sessions = client.get("/api/sessions").await.json();
+    assert_eq!(sessions[0]["username"], "ist1999999");
+
+    let elections = client.get("/api/elections/user").await.json();
+    let election_id = elections[0]["id"].as_i64().unwrap();
+    client
+        .post(
+            &format!("/api/election/{election_id}/self-nominate"),
+            json!(null),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let search_results = client
+        .post(
+            "/api/search-user",
+            json!({ "election": election_id, "query": "ana" }),
+        )
+        .await
+        .json();
+    assert_eq!(search_results[0]["username"], "ist1100002");
+
+    app.anonymous()
+        .post(
+            "/api/dev/login",
+            json!({ "username": " ", "degreeEntries": [] }),
+        )
+        .await
+        .assert_error(StatusCode::BAD_REQUEST, "error.username.empty");
+
+    app.teardown().await;
+}
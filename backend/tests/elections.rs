@@ -0,0 +1,297 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A, MEIC_A};
+use serde_json::{json, Value};
+
+/// Create the elections for the first round: one for the 1st year of LEIC-A and another
+/// for all years of MEIC-A, with the candidacy period happening now and voting in 2 days.
+fn first_round_elections() -> Value {
+    let now = Utc::now();
+
+    json!({
+        "candidacyPeriod": { "start": now - Duration::hours(1), "end": now + Duration::days(1) },
+        "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+        "round": 1,
+        "degrees": [
+            { "degreeId": LEIC_A, "curricularYear": 1 },
+            { "degreeId": MEIC_A, "curricularYear": null },
+        ],
+    })
+}
+
+#[tokio::test]
+async fn full_election() {
+    let app = TestApp::new().await;
+
+    let admin = app.setup_admin("ist1100005").await;
+    app.login("ist1100000")
+        .await
+        .post("/api/setup/admin", Value::Null)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    admin
+        .post("/api/elections/bulk", first_round_elections())
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    // candidacy period
+    let maria = app.login("ist1100000").await;
+    let joao = app.login("ist1100001").await;
+
+    let elections = maria.get("/api/elections/user").await.json();
+    let elections = elections.as_array().unwrap();
+    assert_eq!(elections.len(), 1);
+    assert_eq!(elections[0]["degree"]["acronym"], "LEIC-A");
+    assert_eq!(elections[0]["status"], "CANDIDACY");
+    let election_id = elections[0]["id"].as_i64().unwrap() as i32;
+
+    maria
+        .post(
+            &format!("/api/election/{election_id}/self-nominate"),
+            Value::Null,
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    maria
+        .post(
+            &format!("/api/election/{election_id}/self-nominate"),
+            Value::Null,
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.election.duplicate-nomination");
+
+    let search_results = joao
+        .post(
+            "/api/search-user",
+            json!({ "election": election_id, "query": "ana" }),
+        )
+        .await
+        .json();
+    let search_results = search_results.as_array().unwrap();
+    assert_eq!(search_results.len(), 1);
+    assert_eq!(search_results[0]["username"], "ist1100002");
+    joao.post(
+        &format!("/api/election/{election_id}/nominate"),
+        search_results[0].clone(),
+    )
+    .await
+    .assert_status(StatusCode::NO_CONTENT);
+
+    // the search result was signed for João, so it can't be reused by others
+    maria
+        .post(
+            &format!("/api/election/{election_id}/nominate"),
+            search_results[0].clone(),
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.person-signature.foreign");
+
+    // validation of nominations
+    let unverified_count = admin
+        .get("/api/elections/nominations/unverified-count")
+        .await
+        .json();
+    assert_eq!(unverified_count, json!({ election_id.to_string(): 1 }));
+
+    let unverified = admin
+        .get("/api/elections/nominations/unverified")
+        .await
+        .json();
+    assert_eq!(unverified[0]["nominations"][0]["username"], "ist1100002");
+
+    admin
+        .patch(
+            &format!("/api/election/{election_id}/nomination"),
+            json!({ "username": "ist1100002", "valid": true }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(
+        admin
+            .get("/api/elections/nominations/unverified-count")
+            .await
+            .json(),
+        json!({})
+    );
+
+    // voting period
+    maria
+        .post(
+            &format!("/api/election/{election_id}/vote"),
+            json!({ "username": "ist1100002" }),
+        )
+        .await
+        .assert_error(
+            StatusCode::FORBIDDEN,
+            "error.election.voting.outside-period",
+        );
+
+    app.time_travel(election_id, Duration::days(2) + Duration::hours(1))
+        .await;
+
+    let vote_options = maria
+        .get(&format!("/api/election/{election_id}/vote-options"))
+        .await
+        .json();
+    assert_eq!(
+        vote_options,
+        json!([
+            { "username": "ist1100002", "displayName": "Ana Ferreira" },
+            { "username": "ist1100000", "displayName": "Maria Silva" },
+        ])
+    );
+
+    maria
+        .post(
+            &format!("/api/election/{election_id}/vote"),
+            json!({ "username": "ist1100002" }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    maria
+        .post(
+            &format!("/api/election/{election_id}/vote"),
+            json!({ "username": "ist1100000" }),
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.election.duplicate-vote");
+    joao.post(
+        &format!("/api/election/{election_id}/vote"),
+        json!({ "username": null }),
+    )
+    .await
+    .assert_status(StatusCode::NO_CONTENT);
+    app.login("ist1100002")
+        .await
+        .post(
+            &format!("/api/election/{election_id}/vote"),
+            json!({ "username": "ist1100005" }),
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.election.unauthorized");
+
+    // turnout can be followed, but results are only available once the election ends
+    let details = admin
+        .get(&format!("/api/election/{election_id}/details"))
+        .await
+        .json();
+    assert_eq!(details["totalVotes"], 2);
+    assert_eq!(details["nominations"][0]["votes"], Value::Null);
+
+    app.time_travel(election_id, Duration::days(1)).await;
+
+    let details = admin
+        .get(&format!("/api/election/{election_id}/details"))
+        .await
+        .json();
+    assert_eq!(details["totalVotes"], 2);
+    assert_eq!(details["nominations"][0]["votes"], 1);
+    assert_eq!(details["nominations"][1]["votes"], 0);
+
+    let results = admin.get("/api/elections/results/download/csv").await;
+    results.assert_status(StatusCode::OK);
+    assert_eq!(
+        results.content_type.as_deref(),
+        Some("text/csv; charset=utf-8")
+    );
+    assert_eq!(
+        results.body,
+        format!(
+            "election_id,round,degree,curricular_year,username,display_name,vote_count\n\
+             {election_id},1,LEIC-A,1,ist1100002,Ana Ferreira,1\n\
+             {election_id},1,LEIC-A,1,blank,,1\n"
+        )
+    );
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn bulk_create_validation() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    let mut elections = first_round_elections();
+    elections["round"] = json!(0);
+    admin
+        .post("/api/elections/bulk", elections)
+        .await
+        .assert_error(StatusCode::CONFLICT, "error.round.invalid");
+
+    let mut elections = first_round_elections();
+    elections["candidacyPeriod"]["end"] = elections["votingPeriod"]["end"].clone();
+    admin
+        .post("/api/elections/bulk", elections)
+        .await
+        .assert_error(
+            StatusCode::BAD_REQUEST,
+            "error.election.candidacy-after-voting",
+        );
+
+    let mut elections = first_round_elections();
+    elections["degrees"][0]["degreeId"] = json!("123");
+    admin
+        .post("/api/elections/bulk", elections)
+        .await
+        .assert_error(StatusCode::CONFLICT, "error.degree.invalid");
+
+    admin
+        .post("/api/elections/bulk", first_round_elections())
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    admin
+        .post("/api/elections/bulk", first_round_elections())
+        .await
+        .assert_error(StatusCode::CONFLICT, "error.duplicate.election");
+
+    app.login("ist1100000")
+        .await
+        .post("/api/elections/bulk", first_round_elections())
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn nominations_by_ineligible_users() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+    admin
+        .post("/api/elections/bulk", first_round_elections())
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    // Inês is in mobility, so she can vote on MEIC-A elections, but can't be elected
+    let ines = app.login("ist1100004").await;
+    let elections = ines.get("/api/elections/user").await.json();
+    assert_eq!(elections[0]["degree"]["acronym"], "MEIC-A");
+    let election_id = elections[0]["id"].as_i64().unwrap();
+
+    ines.post(
+        &format!("/api/election/{election_id}/self-nominate"),
+        Value::Null,
+    )
+    .await
+    .assert_error(StatusCode::FORBIDDEN, "error.election.unauthorized");
+
+    // users can't nominate themselves on elections of other degrees
+    app.login("ist1100000")
+        .await
+        .post(
+            &format!("/api/election/{election_id}/self-nominate"),
+            Value::Null,
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.election.unauthorized");
+
+    app.anonymous()
+        .get("/api/elections/user")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+
+    app.teardown().await;
+}
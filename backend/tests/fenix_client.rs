@@ -0,0 +1,75 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use ist_delegate_election::services::fenix::FenixService;
+use serde_json::{json, Value};
+
+/// Serve Fénix's /about endpoint, failing the first `failures` requests.
+/// Returns the base URL and the number of requests received so far.
+async fn spawn_flaky_fenix(failures: usize) -> (String, Arc<AtomicUsize>) {
+    async fn about(
+        State((failures, requests)): State<(usize, Arc<AtomicUsize>)>,
+    ) -> Result<Json<Value>, StatusCode> {
+        if requests.fetch_add(1, Ordering::SeqCst) < failures {
+            return Err(StatusCode::SERVICE_UNAVAILABLE);
+        }
+
+        Ok(Json(json!({
+            "activeSemester": { "year": { "beginYear": 2023, "endYear": 2024 } },
+        })))
+    }
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    let router = Router::new()
+        .route("/tecnico-api/v2/about", get(about))
+        .with_state((failures, requests.clone()));
+    let server = axum::Server::bind(&SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+        .serve(router.into_make_service());
+    let addr = server.local_addr();
+    tokio::spawn(server);
+
+    (format!("http://{}", addr), requests)
+}
+
+fn fenix_service(base_url: String) -> FenixService {
+    FenixService::with_config(
+        base_url,
+        "client-id".to_string(),
+        "client-secret".to_string(),
+        "http://localhost/login".to_string(),
+    )
+}
+
+#[tokio::test]
+async fn get_requests_are_retried() {
+    let (base_url, requests) = spawn_flaky_fenix(2).await;
+    let fenix_service = fenix_service(base_url);
+
+    assert_eq!(
+        fenix_service.get_fenix_active_year().await.ok(),
+        Some("2023/2024".to_string())
+    );
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn circuit_opens_while_fenix_is_unhealthy() {
+    let (base_url, requests) = spawn_flaky_fenix(usize::MAX).await;
+    let fenix_service = fenix_service(base_url);
+
+    // each call makes 3 attempts, but only counts as one failure
+    for _ in 0..5 {
+        assert!(fenix_service.get_fenix_active_year().await.is_err());
+    }
+    assert_eq!(requests.load(Ordering::SeqCst), 15);
+
+    // further calls fail fast, without reaching Fénix
+    assert!(fenix_service.get_fenix_active_year().await.is_err());
+    assert_eq!(requests.load(Ordering::SeqCst), 15);
+}
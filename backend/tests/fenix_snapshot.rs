@@ -0,0 +1,107 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A};
+use entity::fenix_snapshot::{self, Entity as FenixSnapshot};
+use ist_delegate_election::{dtos::HealthStatusDto, services::fenix::FenixService};
+use sea_orm::{prelude::*, sea_query::Expr};
+use serde_json::Value;
+
+/// A Fénix service that can't reach Fénix, but shares the database with the given app.
+fn unreachable_fenix_service(app: &TestApp) -> FenixService {
+    FenixService::with_config(
+        "http://127.0.0.1:1".to_string(),
+        "client-id".to_string(),
+        "client-secret".to_string(),
+        "http://localhost/login".to_string(),
+    )
+    .with_database(app.conn.clone())
+}
+
+#[tokio::test]
+async fn snapshot_endpoints() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    app.login("ist1100000")
+        .await
+        .get("/api/fenix-snapshot")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    let snapshot = admin
+        .post("/api/fenix-snapshot/refresh", Value::Null)
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(snapshot["activeYear"], "2023/2024");
+    assert!(snapshot["activeYearTakenAt"].is_string());
+    assert!(snapshot["degreesTakenAt"].is_string());
+
+    let snapshot = admin
+        .get("/api/fenix-snapshot")
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(snapshot["activeYear"], "2023/2024");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn snapshot_fallback() {
+    let app = TestApp::new().await;
+
+    // without a snapshot, nothing can be served
+    let fenix_service = unreachable_fenix_service(&app);
+    assert!(fenix_service.get_active_year().await.is_err());
+    let (degrees_check, _) = fenix_service.check_readiness().await;
+    assert_eq!(degrees_check.status, HealthStatusDto::Down);
+
+    app.setup_admin("ist1100005")
+        .await
+        .post("/api/fenix-snapshot/refresh", Value::Null)
+        .await
+        .assert_status(StatusCode::OK);
+
+    // loaded at boot
+    let fenix_service = unreachable_fenix_service(&app);
+    fenix_service.load_snapshots().await.ok().unwrap();
+    assert_eq!(
+        fenix_service.get_active_year().await.ok().unwrap(),
+        "2023/2024"
+    );
+    assert_eq!(
+        fenix_service
+            .get_degree(LEIC_A)
+            .await
+            .ok()
+            .flatten()
+            .map(|degree| degree.acronym),
+        Some("LEIC-A".to_string())
+    );
+
+    // loaded when fetching from Fénix fails
+    let fenix_service = unreachable_fenix_service(&app);
+    assert_eq!(
+        fenix_service.get_active_year().await.ok().unwrap(),
+        "2023/2024"
+    );
+    assert_eq!(fenix_service.get_degrees().await.ok().unwrap().count(), 2);
+
+    // snapshots that haven't been refreshed in a while are reported as stale
+    FenixSnapshot::update_many()
+        .col_expr(
+            fenix_snapshot::Column::TakenAt,
+            Expr::value((Utc::now() - Duration::days(1)).naive_utc()),
+        )
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    let (degrees_check, active_year_check) = fenix_service.check_readiness().await;
+    assert_eq!(degrees_check.status, HealthStatusDto::Stale);
+    assert_eq!(active_year_check.status, HealthStatusDto::Stale);
+
+    app.teardown().await;
+}
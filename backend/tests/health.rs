@@ -0,0 +1,58 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::TestApp;
+use migration::{Migrator, MigratorTrait};
+
+#[tokio::test]
+async fn healthz() {
+    let app = TestApp::new().await;
+
+    let health = app
+        .anonymous()
+        .get("/healthz")
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(health["status"], "UP");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn readyz() {
+    let app = TestApp::new().await;
+    let client = app.anonymous();
+
+    let readiness = client
+        .get("/readyz")
+        .await
+        .assert_status(StatusCode::OK)
+        .json();
+    assert_eq!(readiness["ready"], true);
+    assert_eq!(readiness["database"]["status"], "UP");
+    assert!(readiness["database"]["latencyMs"].is_number());
+    assert_eq!(readiness["migrations"]["status"], "UP");
+    assert_eq!(readiness["migrations"]["pending"], serde_json::json!([]));
+    assert_eq!(readiness["fenixDegrees"]["status"], "UP");
+    assert!(readiness["fenixDegrees"]["fetchedAt"].is_string());
+    assert_eq!(readiness["fenixAcademicYear"]["status"], "UP");
+    assert_eq!(readiness["sessionStore"]["status"], "UP");
+    assert_eq!(readiness["sessionStore"]["kind"], "database");
+
+    // pretend a new version with a migration that hasn't run yet has been deployed
+    Migrator::down(&app.conn, Some(1)).await.unwrap();
+    let readiness = client
+        .get("/readyz")
+        .await
+        .assert_status(StatusCode::SERVICE_UNAVAILABLE)
+        .json();
+    assert_eq!(readiness["ready"], false);
+    assert_eq!(readiness["migrations"]["status"], "DOWN");
+    assert_eq!(
+        readiness["migrations"]["pending"].as_array().unwrap().len(),
+        1
+    );
+
+    app.teardown().await;
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use axum::http::StatusCode;
+use common::TestApp;
+use ist_delegate_election::logging::REQUEST_ID_HEADER;
+use serde_json::Value;
+use tracing::Level;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Collects everything that is logged, so that it can be inspected.
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for LogBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl LogBuffer {
+    fn lines(&self) -> Vec<Value> {
+        String::from_utf8(self.0.lock().unwrap().clone())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn requests_are_logged_with_context() {
+    let app = TestApp::new().await;
+    let maria = app.login("ist1100000").await;
+
+    let logs = LogBuffer::default();
+    let writer = logs.clone();
+    let _guard = tracing_subscriber::fmt()
+        .json()
+        .with_span_list(true)
+        .with_max_level(Level::INFO)
+        .with_writer(move || writer.clone())
+        .finish()
+        .set_default();
+
+    let response = maria.get("/api/whoami").await;
+    response.assert_status(StatusCode::OK);
+    let request_id = response.headers[REQUEST_ID_HEADER].to_str().unwrap();
+
+    let other_response = maria.get("/api/whoami").await;
+    assert_ne!(other_response.headers[REQUEST_ID_HEADER], request_id);
+
+    let lines = logs.lines();
+    // the username is only known once the session has been loaded, which can itself
+    // be logged (such as the queries made to load it), so look at the last line
+    let request_span = lines
+        .iter()
+        .rev()
+        .filter_map(|line| line["spans"].as_array()?.first())
+        .find(|span| span["request_id"] == request_id)
+        .expect("the request must be logged");
+    assert_eq!(request_span["route"], "/api/whoami");
+    assert_eq!(request_span["method"], "GET");
+    assert_eq!(request_span["username"], "ist1100000");
+
+    app.teardown().await;
+}
@@ -0,0 +1,124 @@
+mod common;
+
+use std::env;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A};
+use serde_json::json;
+
+/// Get the value of the given metric, if it has been reported.
+fn metric_value(metrics: &str, metric: &str) -> Option<f64> {
+    metrics.lines().find_map(|line| {
+        let value = line.strip_prefix(metric)?.strip_prefix(' ')?;
+        value.parse().ok()
+    })
+}
+
+#[tokio::test]
+async fn metrics() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+    let maria = app.login("ist1100000").await;
+
+    let now = Utc::now();
+    admin
+        .post(
+            "/api/elections/bulk",
+            json!({
+                "candidacyPeriod": { "start": now - Duration::hours(1), "end": now + Duration::days(1) },
+                "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+                "round": 1,
+                "degrees": [{ "degreeId": LEIC_A, "curricularYear": 1 }],
+            }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let elections = maria.get("/api/elections/user").await.json();
+    let election_id = elections[0]["id"].as_i64().unwrap() as i32;
+    // nominations of others have to be verified by an admin
+    let search_results = maria
+        .post(
+            "/api/search-user",
+            json!({ "election": election_id, "query": "ana" }),
+        )
+        .await
+        .json();
+    maria
+        .post(
+            &format!("/api/election/{election_id}/nominate"),
+            search_results[0].clone(),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let response = app.anonymous().get("/metrics").await;
+    response.assert_status(StatusCode::OK);
+    let metrics = &response.body;
+    assert!(metrics.contains(
+        r#"ist_delegate_election_http_requests_total{method="GET",route="/api/elections/user",status="200"}"#
+    ));
+    assert!(metrics.contains(
+        r#"ist_delegate_election_fenix_request_duration_seconds_count{method="get_user_details"}"#
+    ));
+    assert!(metrics.contains(
+        r#"ist_delegate_election_cache_requests_total{cache="fenix_degrees",result="hit"}"#
+    ));
+    assert!(metrics.contains(r#"ist_delegate_election_db_pool_connections{state="idle"}"#));
+    assert_eq!(
+        metric_value(metrics, "ist_delegate_election_unverified_nominations"),
+        Some(1.0)
+    );
+    assert_eq!(
+        metric_value(metrics, "ist_delegate_election_active_sessions"),
+        Some(2.0)
+    );
+    // votes are only reported during the voting period
+    assert!(!metrics.contains("ist_delegate_election_election_votes_cast{"));
+
+    admin
+        .patch(
+            &format!("/api/election/{election_id}/nomination"),
+            json!({ "username": "ist1100002", "valid": true }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    app.time_travel(election_id, Duration::days(2) + Duration::hours(1))
+        .await;
+    maria
+        .post(
+            &format!("/api/election/{election_id}/vote"),
+            json!({ "username": null }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let metrics = app.anonymous().get("/metrics").await.body;
+    assert_eq!(
+        metric_value(&metrics, "ist_delegate_election_unverified_nominations"),
+        Some(0.0)
+    );
+    // turnout is only exposed to scrapers that are authorized with a token
+    assert!(!metrics.contains("ist_delegate_election_election_votes_cast{"));
+
+    env::set_var("METRICS_TOKEN", "scraper-secret");
+    app.anonymous()
+        .get("/metrics")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+    app.with_token("wrong-secret")
+        .get("/metrics")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+    let metrics = app.with_token("scraper-secret").get("/metrics").await.body;
+    env::remove_var("METRICS_TOKEN");
+    assert_eq!(
+        metric_value(
+            &metrics,
+            &format!(r#"ist_delegate_election_election_votes_cast{{election="{election_id}"}}"#)
+        ),
+        Some(1.0)
+    );
+
+    app.teardown().await;
+}
@@ -0,0 +1,233 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, LEIC_A};
+use entity::observer::{self, Entity as Observer};
+use sea_orm::{prelude::*, sea_query::Expr};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn observers_follow_elections_without_seeing_votes() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    let now = Utc::now();
+    admin
+        .post(
+            "/api/elections/bulk",
+            json!({
+                "candidacyPeriod": { "start": now - Duration::hours(1), "end": now + Duration::days(1) },
+                "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+                "round": 1,
+                "degrees": [{ "degreeId": LEIC_A, "curricularYear": 1 }],
+            }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let maria = app.login("ist1100000").await;
+    let election_id = maria.get("/api/elections/user").await.json()[0]["id"]
+        .as_i64()
+        .unwrap() as i32;
+    let ana = maria
+        .post(
+            "/api/search-user",
+            json!({ "election": election_id, "query": "ana" }),
+        )
+        .await
+        .json()[0]
+        .clone();
+    maria
+        .post(&format!("/api/election/{election_id}/nominate"), ana)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let observer = app.login("ist1100004").await;
+    observer
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    admin
+        .post(
+            "/api/observer",
+            json!({ "username": "ist1100004", "expiresAt": now - Duration::hours(1) }),
+        )
+        .await
+        .assert_error(StatusCode::BAD_REQUEST, "error.observer.expiry.invalid");
+    admin
+        .post(
+            "/api/observer",
+            json!({ "username": "ist1100004", "expiresAt": now + Duration::days(7) }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let observers = admin.get("/api/observers").await.json();
+    assert_eq!(observers[0]["username"], "ist1100004");
+    assert_eq!(observers[0]["grantedBy"], "ist1100005");
+    assert!(observer.get("/api/whoami").await.json()["observerUntil"].is_string());
+
+    // read-only access
+    let degrees = observer.get("/api/degrees/elections").await.json();
+    assert!(degrees
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|degree| degree["elections"][0]["id"] == election_id));
+    assert_eq!(
+        observer
+            .get("/api/elections/nominations/unverified-count")
+            .await
+            .json(),
+        json!({ election_id.to_string(): 1 })
+    );
+    observer
+        .patch(
+            &format!("/api/election/{election_id}/nomination"),
+            json!({ "username": "ist1100002", "valid": true }),
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    observer
+        .get("/api/elections/nominations/unverified")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    observer
+        .get("/api/admins")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    observer
+        .post(
+            "/api/observer",
+            json!({ "username": "ist1100003", "expiresAt": now + Duration::days(7) }),
+        )
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    admin
+        .patch(
+            &format!("/api/election/{election_id}/nomination"),
+            json!({ "username": "ist1100002", "valid": true }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    // observers follow turnout, but not the votes of each nomination, while voting is open
+    app.time_travel(election_id, Duration::days(2) + Duration::hours(1))
+        .await;
+    maria
+        .post(
+            &format!("/api/election/{election_id}/vote"),
+            json!({ "username": "ist1100002" }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let details = observer
+        .get(&format!("/api/election/{election_id}/details"))
+        .await
+        .json();
+    assert_eq!(details["totalVotes"], 1);
+    assert_eq!(details["nominations"][0]["valid"], true);
+    assert_eq!(details["nominations"][0]["votes"], Value::Null);
+    // the same as admins
+    let admin_details = admin
+        .get(&format!("/api/election/{election_id}/details"))
+        .await
+        .json();
+    assert_eq!(admin_details["totalVotes"], 1);
+    assert_eq!(admin_details["nominations"][0]["votes"], Value::Null);
+
+    app.time_travel(election_id, Duration::days(1)).await;
+
+    let details = observer
+        .get(&format!("/api/election/{election_id}/details"))
+        .await
+        .json();
+    assert_eq!(details["nominations"][0]["votes"], 1);
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn observer_access_ends() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+    let observer = app.login("ist1100004").await;
+
+    admin
+        .post(
+            "/api/observer",
+            json!({ "username": "ist1100004", "expiresAt": Utc::now() + Duration::days(1) }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    observer
+        .get("/api/degrees/elections")
+        .await
+        .assert_status(StatusCode::OK);
+
+    // expired access
+    Observer::update_many()
+        .col_expr(
+            observer::Column::ExpiresAt,
+            Expr::value((Utc::now() - Duration::hours(1)).naive_utc()),
+        )
+        .filter(observer::Column::Username.eq("ist1100004"))
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    observer
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    assert!(observer.get("/api/whoami").await.json()["observerUntil"].is_null());
+
+    // granting access again extends it
+    admin
+        .post(
+            "/api/observer",
+            json!({ "username": "ist1100004", "expiresAt": Utc::now() + Duration::days(1) }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    observer
+        .get("/api/degrees/elections")
+        .await
+        .assert_status(StatusCode::OK);
+
+    // revoked access
+    admin
+        .delete("/api/observer/ist1100004", Value::Null)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    observer
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    admin
+        .delete("/api/observer/ist1100004", Value::Null)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "error.unknown.observer");
+
+    let entries = admin.get("/api/audit-log").await.json();
+    let actions: Vec<_> = entries
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        actions,
+        [
+            "observer.revoke",
+            "observer.grant",
+            "observer.grant",
+            "admin.setup"
+        ]
+    );
+
+    app.teardown().await;
+}
@@ -0,0 +1,290 @@
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, TestClient, LEIC_A};
+use entity::{
+    election::{self, Entity as Election},
+    nomination::{self, Entity as Nomination},
+    pending_action::{self, Entity as PendingAction},
+};
+use sea_orm::{prelude::*, sea_query::Expr, ActiveValue};
+use serde_json::{json, Value};
+
+async fn approve(client: &TestClient, pending_action: &Value) -> StatusCode {
+    client
+        .post(
+            &format!("/api/pending-action/{}/approve", pending_action["id"]),
+            Value::Null,
+        )
+        .await
+        .status
+}
+
+#[tokio::test]
+async fn removing_an_admin_needs_approval() {
+    let app = TestApp::new().await;
+    let proposer = app.setup_admin("ist1100005").await;
+    for admin in [
+        json!({ "username": "ist1100000" }),
+        json!({ "username": "ist1100001", "role": "AUDITOR" }),
+    ] {
+        proposer
+            .post("/api/admin", admin)
+            .await
+            .assert_status(StatusCode::NO_CONTENT);
+    }
+    let approver = app.login("ist1100000").await;
+    let auditor = app.login("ist1100001").await;
+
+    let response = proposer.delete("/api/admin/ist1100001", Value::Null).await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let removal = response.json();
+    assert_eq!(removal["kind"], "REMOVE_ADMIN");
+    assert_eq!(removal["target"], "ist1100001");
+    assert_eq!(removal["status"], "PENDING");
+    assert_eq!(removal["proposedBy"], "ist1100005");
+
+    proposer
+        .delete("/api/admin/ist1100001", Value::Null)
+        .await
+        .assert_error(StatusCode::CONFLICT, "error.duplicate.pending-action");
+    assert_eq!(approve(&proposer, &removal).await, StatusCode::FORBIDDEN);
+    assert_eq!(approve(&auditor, &removal).await, StatusCode::FORBIDDEN);
+    assert_eq!(
+        proposer
+            .get("/api/admins")
+            .await
+            .json()
+            .as_array()
+            .unwrap()
+            .len(),
+        3
+    );
+
+    assert_eq!(approve(&approver, &removal).await, StatusCode::NO_CONTENT);
+    assert_eq!(approve(&approver, &removal).await, StatusCode::CONFLICT);
+    assert_eq!(
+        proposer
+            .get("/api/admins")
+            .await
+            .json()
+            .as_array()
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let pending_actions = proposer.get("/api/pending-actions").await.json();
+    assert_eq!(pending_actions[0]["status"], "APPROVED");
+    assert_eq!(pending_actions[0]["decidedBy"], "ist1100000");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn expired_and_rejected_actions() {
+    let app = TestApp::new().await;
+    let proposer = app.setup_admin("ist1100005").await;
+    proposer
+        .post("/api/admin", json!({ "username": "ist1100000" }))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let approver = app.login("ist1100000").await;
+
+    let removal = proposer
+        .delete("/api/admin/ist1100000", Value::Null)
+        .await
+        .json();
+    PendingAction::update_many()
+        .col_expr(
+            pending_action::Column::ExpiresAt,
+            Expr::value(Utc::now().naive_utc() - Duration::minutes(1)),
+        )
+        .exec(&app.conn)
+        .await
+        .unwrap();
+    assert_eq!(
+        proposer.get("/api/pending-actions").await.json()[0]["status"],
+        "EXPIRED"
+    );
+    approver
+        .post(
+            &format!("/api/pending-action/{}/approve", removal["id"]),
+            Value::Null,
+        )
+        .await
+        .assert_error(StatusCode::CONFLICT, "error.pending-action.expired");
+
+    // once expired, the same action can be proposed again, and then withdrawn
+    let removal = proposer
+        .delete("/api/admin/ist1100000", Value::Null)
+        .await
+        .json();
+    proposer
+        .post(
+            &format!("/api/pending-action/{}/reject", removal["id"]),
+            Value::Null,
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert_eq!(
+        proposer.get("/api/pending-actions").await.json()[0]["status"],
+        "REJECTED"
+    );
+    approver
+        .post(
+            &format!("/api/pending-action/{}/approve", removal["id"]),
+            Value::Null,
+        )
+        .await
+        .assert_error(StatusCode::CONFLICT, "error.pending-action.decided");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn changing_elections_after_voting_started() {
+    let app = TestApp::new().await;
+    let proposer = app.setup_admin("ist1100005").await;
+    proposer
+        .post("/api/admin", json!({ "username": "ist1100000" }))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let approver = app.login("ist1100000").await;
+
+    let now = Utc::now();
+    let elections = json!({
+        "candidacyPeriod": { "start": now - Duration::hours(1), "end": now + Duration::days(1) },
+        "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+        "round": 1,
+        "degrees": [{ "degreeId": LEIC_A, "curricularYear": null }],
+    });
+    let find_election = || async {
+        Election::find()
+            .filter(election::Column::DegreeId.eq(LEIC_A))
+            .one(&app.conn)
+            .await
+            .unwrap()
+    };
+
+    // before voting starts, elections can be changed right away
+    proposer
+        .post("/api/elections/bulk", elections.clone())
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let election_id = find_election().await.unwrap().id;
+    let periods = json!({
+        "candidacyPeriod": null,
+        "votingPeriod": { "start": now + Duration::days(1), "end": now + Duration::days(4) },
+    });
+    proposer
+        .put(&format!("/api/election/{election_id}/periods"), periods)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert!(find_election()
+        .await
+        .unwrap()
+        .candidacy_period_start
+        .is_none());
+    proposer
+        .delete(&format!("/api/election/{election_id}"), Value::Null)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    assert!(find_election().await.is_none());
+
+    // but not once it has started
+    proposer
+        .post("/api/elections/bulk", elections)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let election_id = find_election().await.unwrap().id;
+    app.time_travel(election_id, Duration::days(2) + Duration::hours(1))
+        .await;
+    nomination::ActiveModel {
+        election: ActiveValue::Set(election_id),
+        username: ActiveValue::Set("ist1100001".to_string()),
+        display_name: ActiveValue::Set("João".to_string()),
+        valid: ActiveValue::Set(Some(true)),
+    }
+    .insert(&app.conn)
+    .await
+    .unwrap();
+
+    let nomination_uri = format!("/api/election/{election_id}/nomination");
+    proposer
+        .patch(
+            &nomination_uri,
+            json!({ "username": "ist1100001", "displayName": "João Silva" }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let response = proposer
+        .patch(
+            &nomination_uri,
+            json!({ "username": "ist1100001", "valid": false }),
+        )
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let invalidation = response.json();
+    assert_eq!(invalidation["kind"], "INVALIDATE_NOMINATION");
+    assert_eq!(invalidation["target"], format!("{election_id}/ist1100001"));
+
+    let voting_end = now + Duration::days(5);
+    let response = proposer
+        .put(
+            &format!("/api/election/{election_id}/periods"),
+            json!({ "votingPeriod": { "start": now - Duration::days(1), "end": voting_end } }),
+        )
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let rescheduling = response.json();
+
+    let response = proposer
+        .delete(&format!("/api/election/{election_id}"), Value::Null)
+        .await;
+    response.assert_status(StatusCode::ACCEPTED);
+    let deletion = response.json();
+
+    assert_eq!(
+        approve(&approver, &invalidation).await,
+        StatusCode::NO_CONTENT
+    );
+    let nomination = Nomination::find_by_id((election_id, "ist1100001".to_string()))
+        .one(&app.conn)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(nomination.valid, Some(false));
+    assert_eq!(nomination.display_name, "João Silva");
+
+    assert_eq!(
+        approve(&approver, &rescheduling).await,
+        StatusCode::NO_CONTENT
+    );
+    assert_eq!(
+        find_election()
+            .await
+            .unwrap()
+            .voting_period_end
+            .and_utc()
+            .timestamp(),
+        voting_end.timestamp()
+    );
+
+    assert_eq!(approve(&approver, &deletion).await, StatusCode::NO_CONTENT);
+    assert!(find_election().await.is_none());
+
+    let actions: Vec<_> = proposer
+        .get("/api/audit-log?limit=2")
+        .await
+        .json()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|entry| entry["action"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(actions, ["elections.delete", "pending-action.approve"]);
+
+    app.teardown().await;
+}
@@ -0,0 +1,144 @@
+mod common;
+
+use axum::http::{header, StatusCode};
+use common::TestApp;
+use entity::session::Entity as Session;
+use sea_orm::{EntityTrait, PaginatorTrait};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn users_manage_their_sessions() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+    let laptop = app.login("ist1100000").await;
+    let phone = app.login("ist1100000").await;
+    app.login("ist1100001").await;
+
+    let sessions = laptop.get("/api/sessions").await.json();
+    let sessions = sessions.as_array().unwrap();
+    assert_eq!(sessions.len(), 2);
+    assert!(sessions
+        .iter()
+        .all(|session| session["username"] == "ist1100000"));
+    assert_eq!(
+        sessions
+            .iter()
+            .filter(|session| session["current"] == true)
+            .count(),
+        1
+    );
+    assert_eq!(sessions[0]["userAgent"], "integration-tests");
+    // activity is only tracked once in a while
+    assert_eq!(sessions[0]["lastActivity"], sessions[0]["loginTime"]);
+
+    // only admins can see the sessions of other users, and only super admins revoke them
+    let phone_id = sessions
+        .iter()
+        .find(|session| session["current"] == false)
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    laptop
+        .get("/api/user/ist1100001/sessions")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    laptop
+        .delete(&format!("/api/session/{phone_id}"), Value::Null)
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    let sessions = admin.get("/api/user/ist1100000/sessions").await.json();
+    assert_eq!(sessions.as_array().unwrap().len(), 2);
+
+    admin
+        .delete(&format!("/api/session/{phone_id}"), Value::Null)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    phone
+        .get("/api/sessions")
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+    admin
+        .delete(&format!("/api/session/{phone_id}"), Value::Null)
+        .await
+        .assert_error(StatusCode::NOT_FOUND, "error.unknown.session");
+
+    let sessions = laptop.get("/api/sessions").await.json();
+    assert_eq!(sessions.as_array().unwrap().len(), 1);
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn logins_must_send_back_their_state() {
+    let app = TestApp::new().await;
+
+    let client = app.anonymous();
+    client
+        .post("/api/login", json!({ "code": "ist1100000" }))
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+
+    let start = client.get("/api/login/start").await;
+    start.assert_status(StatusCode::SEE_OTHER);
+    assert!(start.headers[header::LOCATION]
+        .to_str()
+        .unwrap()
+        .contains("state="));
+    client
+        .post(
+            "/api/login",
+            json!({ "code": "ist1100000", "state": "forged" }),
+        )
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn sessions_are_only_stored_when_they_change() {
+    let app = TestApp::new().await;
+    let user = app.login("ist1100000").await;
+
+    let response = user.get("/api/sessions").await;
+    response.assert_status(StatusCode::OK);
+    assert!(response.headers.get(header::SET_COOKIE).is_none());
+
+    // a cookie of a session that no longer exists doesn't create a new one
+    Session::delete_many().exec(&app.conn).await.unwrap();
+    let response = user.get("/api/sessions").await;
+    response.assert_error(StatusCode::UNAUTHORIZED, "error.unauthorized");
+    assert!(response.headers.get(header::SET_COOKIE).is_none());
+    assert_eq!(Session::find().count(&app.conn).await.unwrap(), 0);
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn sessions_cant_be_managed_in_memory() {
+    let app = TestApp::with_memory_sessions().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    admin.get("/api/sessions").await.assert_error(
+        StatusCode::NOT_IMPLEMENTED,
+        "error.session-store.unsupported",
+    );
+    admin
+        .get("/api/user/ist1100005/sessions")
+        .await
+        .assert_error(
+            StatusCode::NOT_IMPLEMENTED,
+            "error.session-store.unsupported",
+        );
+    admin
+        .delete("/api/session/abc", Value::Null)
+        .await
+        .assert_error(
+            StatusCode::NOT_IMPLEMENTED,
+            "error.session-store.unsupported",
+        );
+
+    app.teardown().await;
+}
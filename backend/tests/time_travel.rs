@@ -0,0 +1,243 @@
+#![cfg(feature = "time-travel")]
+
+mod common;
+
+use axum::http::StatusCode;
+use chrono::{Duration, Utc};
+use common::{TestApp, TestClient, LEIC_A};
+use serde_json::{json, Value};
+
+#[tokio::test]
+async fn time_travel_to_voting_period() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    let now = Utc::now();
+    admin
+        .post(
+            "/api/elections/bulk",
+            json!({
+                "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+                "round": 1,
+                "degrees": [{ "degreeId": LEIC_A, "curricularYear": 1 }],
+            }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let maria = app.login("ist1100000").await;
+    assert_eq!(
+        maria.get("/api/elections/user").await.json()[0]["status"],
+        "NOT_STARTED"
+    );
+
+    maria
+        .put("/api/time-travel", json!({ "offsetSeconds": 0 }))
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+
+    let time_travel = admin
+        .put(
+            "/api/time-travel",
+            json!({ "offsetSeconds": Duration::days(2).num_seconds() + 60 }),
+        )
+        .await
+        .json();
+    assert_eq!(time_travel["offsetSeconds"], 2 * 24 * 60 * 60 + 60);
+
+    assert_eq!(
+        maria.get("/api/elections/user").await.json()[0]["status"],
+        "VOTING"
+    );
+
+    admin
+        .put("/api/time-travel", json!({ "offsetSeconds": 0 }))
+        .await
+        .assert_status(StatusCode::OK);
+    assert_eq!(
+        admin.get("/api/time-travel").await.json()["offsetSeconds"],
+        0
+    );
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn person_signatures_follow_time_travel() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+
+    let now = Utc::now();
+    admin
+        .post(
+            "/api/elections/bulk",
+            json!({
+                "candidacyPeriod": { "start": now - Duration::hours(1), "end": now + Duration::days(1) },
+                "votingPeriod": { "start": now + Duration::days(2), "end": now + Duration::days(3) },
+                "round": 1,
+                "degrees": [{ "degreeId": LEIC_A, "curricularYear": 1 }],
+            }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    let joao = app.login("ist1100001").await;
+    let election_id = joao.get("/api/elections/user").await.json()[0]["id"]
+        .as_i64()
+        .unwrap();
+    let stale_result = search(&joao, election_id).await[0].clone();
+    let stale_admin_result = search(&admin, election_id).await[0].clone();
+
+    admin
+        .put(
+            "/api/time-travel",
+            json!({ "offsetSeconds": Duration::hours(2).num_seconds() }),
+        )
+        .await
+        .assert_status(StatusCode::OK);
+
+    // signatures issued before travelling are now too old
+    joao.post(
+        &format!("/api/election/{election_id}/nominate"),
+        stale_result,
+    )
+    .await
+    .assert_error(StatusCode::UNAUTHORIZED, "error.person-signature.expired");
+    admin
+        .post(
+            &format!("/api/election/{election_id}/nomination"),
+            stale_admin_result,
+        )
+        .await
+        .assert_error(StatusCode::UNAUTHORIZED, "error.person-signature.expired");
+
+    // while new ones are issued at the shifted time
+    let result = search(&joao, election_id).await[0].clone();
+    joao.post(&format!("/api/election/{election_id}/nominate"), result)
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let admin_result = search(&admin, election_id).await[0].clone();
+    admin
+        .post(
+            &format!("/api/election/{election_id}/nomination"),
+            admin_result,
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn pending_actions_expire_with_time_travel() {
+    let app = TestApp::new().await;
+    let proposer = app.setup_admin("ist1100005").await;
+    proposer
+        .post("/api/admin", json!({ "username": "ist1100000" }))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let approver = app.login("ist1100000").await;
+
+    let removal = proposer
+        .delete("/api/admin/ist1100000", Value::Null)
+        .await
+        .json();
+    proposer
+        .put(
+            "/api/time-travel",
+            json!({ "offsetSeconds": Duration::hours(49).num_seconds() }),
+        )
+        .await
+        .assert_status(StatusCode::OK);
+
+    assert_eq!(
+        proposer.get("/api/pending-actions").await.json()[0]["status"],
+        "EXPIRED"
+    );
+    approver
+        .post(
+            &format!("/api/pending-action/{}/approve", removal["id"]),
+            Value::Null,
+        )
+        .await
+        .assert_error(StatusCode::CONFLICT, "error.pending-action.expired");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn pending_actions_are_proposed_on_the_shifted_clock() {
+    let app = TestApp::new().await;
+    let proposer = app.setup_admin("ist1100005").await;
+    proposer
+        .post("/api/admin", json!({ "username": "ist1100000" }))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    proposer
+        .put(
+            "/api/time-travel",
+            json!({ "offsetSeconds": -Duration::hours(49).num_seconds() }),
+        )
+        .await
+        .assert_status(StatusCode::OK);
+
+    let removal = proposer.delete("/api/admin/ist1100000", Value::Null).await;
+    removal.assert_status(StatusCode::ACCEPTED);
+    assert_eq!(removal.json()["status"], "PENDING");
+
+    app.teardown().await;
+}
+
+#[tokio::test]
+async fn observer_access_expires_with_time_travel() {
+    let app = TestApp::new().await;
+    let admin = app.setup_admin("ist1100005").await;
+    admin
+        .post(
+            "/api/observer",
+            json!({ "username": "ist1100004", "expiresAt": Utc::now() + Duration::days(1) }),
+        )
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    let observer = app.login("ist1100004").await;
+    observer
+        .get("/api/degrees/elections")
+        .await
+        .assert_status(StatusCode::OK);
+
+    admin
+        .put(
+            "/api/time-travel",
+            json!({ "offsetSeconds": Duration::days(2).num_seconds() }),
+        )
+        .await
+        .assert_status(StatusCode::OK);
+
+    observer
+        .get("/api/degrees/elections")
+        .await
+        .assert_error(StatusCode::FORBIDDEN, "error.forbidden");
+    assert_eq!(
+        observer.get("/api/whoami").await.json()["observerUntil"],
+        Value::Null
+    );
+    admin
+        .post(
+            "/api/observer",
+            json!({ "username": "ist1100004", "expiresAt": Utc::now() + Duration::days(1) }),
+        )
+        .await
+        .assert_error(StatusCode::BAD_REQUEST, "error.observer.expiry.invalid");
+
+    app.teardown().await;
+}
+
+async fn search(client: &TestClient, election_id: i64) -> Value {
+    client
+        .post(
+            "/api/search-user",
+            json!({ "election": election_id, "query": "ana" }),
+        )
+        .await
+        .json()
+}
@@ -14,13 +14,108 @@ export interface FenixConfigDto {
   redirectUrl: string;
 }
 
+export interface FenixSnapshotDto {
+  activeYear?: string;
+  activeYearTakenAt?: string;
+  degreesTakenAt?: string;
+}
+
+export enum HealthStatusDto {
+  Up = 'UP',
+  /** Working, but serving data that couldn't be refreshed. */
+  Stale = 'STALE',
+  Down = 'DOWN',
+}
+
+export interface DatabaseCheckDto {
+  status: HealthStatusDto;
+  latencyMs?: number;
+}
+
+export interface MigrationsCheckDto {
+  status: HealthStatusDto;
+  pending: string[];
+}
+
+export interface FenixDataCheckDto {
+  status: HealthStatusDto;
+  /** When the data being served was fetched from Fénix, if known. */
+  fetchedAt?: string;
+}
+
+export interface SessionStoreCheckDto {
+  status: HealthStatusDto;
+  /** Either "database" or "memory". */
+  kind: string;
+}
+
+export interface ReadinessDto {
+  /** Whether no check is down. */
+  ready: boolean;
+  database: DatabaseCheckDto;
+  migrations: MigrationsCheckDto;
+  fenixDegrees: FenixDataCheckDto;
+  fenixAcademicYear: FenixDataCheckDto;
+  sessionStore: SessionStoreCheckDto;
+}
+
+export interface AuditLogEntryDto {
+  id: number;
+  actor: string;
+  action: string;
+  target: string;
+  before?: unknown;
+  after?: unknown;
+  createdAt: string;
+  hash: string;
+}
+
+export interface AuditLogVerificationDto {
+  valid: boolean;
+  /** Number of entries that were verified, until an issue was found, if any. */
+  entries: number;
+  /**
+   * Hash of the last verified entry, which can be compared with a previously
+   * known hash to detect entries removed from the end of the log.
+   */
+  lastHash?: string;
+  issue?: AuditLogIssueDto;
+}
+
+export interface AuditLogIssueDto {
+  id: number;
+  kind: AuditLogIssueKindDto;
+}
+
+export enum AuditLogIssueKindDto {
+  /** Entries before this one are missing. */
+  Gap = 'GAP',
+  /** This entry doesn't follow the previous one, which has been edited. */
+  BrokenChain = 'BROKEN_CHAIN',
+  /** This entry has been edited. */
+  HashMismatch = 'HASH_MISMATCH',
+}
+
 export interface AppConfigDto {
   fenix: FenixConfigDto;
+  loginUrl: string;
+  devLogin: boolean;
+  /** Whether the first admin has been set up, or can only be added from the management CLI. */
   isSetup: boolean;
+  /** Academic year pinned by an admin, which is used instead of Fénix's active year. */
+  pinnedAcademicYear?: string;
 }
 
 export interface LoginDto {
   code: string;
+  /** State sent back by the identity provider, if it supports one. */
+  state?: string;
+}
+
+export interface DevLoginDto {
+  username: string;
+  name?: string;
+  degreeEntries: DegreeEntryDto[];
 }
 
 export interface DegreeEntryDto {
@@ -39,15 +134,117 @@ export interface UserDto {
 export interface AuthDto {
   user: UserDto;
   isAdmin: boolean;
+  adminRole?: AdminRoleDto;
+  /** Until when the user can follow elections as an observer, if they have been granted access. */
+  observerUntil?: string;
 }
 
 export interface AdminDto {
   username: string;
   dateAdded: string;
+  role: AdminRoleDto;
+  /** Degree id or degree type the role is restricted to, for coordinators. */
+  scope?: string;
+}
+
+export enum AdminRoleDto {
+  /** Can do everything, including managing other admins and global settings. */
+  SuperAdmin = 'SUPER_ADMIN',
+  /**
+   * Can manage elections, nominations and overrides of all degrees of a type,
+   * such as all Masters, which is identified by any of its localized names.
+   */
+  DegreeTypeCoordinator = 'DEGREE_TYPE_COORDINATOR',
+  /** Can manage elections, nominations and overrides of a single degree. */
+  DegreeCoordinator = 'DEGREE_COORDINATOR',
+  /** Can see everything other admins can, but can't change anything. */
+  Auditor = 'AUDITOR',
 }
 
 export interface AddAdminDto {
   username: string;
+  /** Defaults to super admin. */
+  role?: AdminRoleDto;
+  /** Degree id, for degree coordinators, or degree type, for degree type coordinators. */
+  scope?: string;
+}
+
+export interface ApiTokenDto {
+  id: number;
+  /** Admin the token acts as, with their current role. */
+  username: string;
+  name: string;
+  scopes: ApiTokenScopeDto[];
+  createdAt: string;
+  expiresAt: string;
+  lastUsedAt?: string;
+}
+
+export enum ApiTokenScopeDto {
+  /** Can make GET requests, such as downloading results. */
+  Read = 'READ',
+  /** Can make any other request, such as importing overrides. */
+  Write = 'WRITE',
+}
+
+export interface CreateApiTokenDto {
+  name: string;
+  scopes: ApiTokenScopeDto[];
+  expiresAt: string;
+}
+
+export interface CreatedApiTokenDto {
+  token: ApiTokenDto;
+  /**
+   * Sent as a Bearer token to authenticate requests. It is only shown once, since
+   * only its hash is stored.
+   */
+  secret: string;
+}
+
+export interface ObserverDto {
+  username: string;
+  grantedBy: string;
+  grantedAt: string;
+  expiresAt: string;
+}
+
+export interface AddObserverDto {
+  username: string;
+  /** When the observer loses access, which can be changed by granting access again. */
+  expiresAt: string;
+}
+
+export interface SessionDto {
+  id: string;
+  username?: string;
+  userAgent?: string;
+  loginTime?: string;
+  lastActivity?: string;
+  expiresAt?: string;
+  current: boolean;
+}
+
+export interface TimeTravelDto {
+  now: string;
+  offsetSeconds: number;
+}
+
+export interface SetTimeTravelDto {
+  offsetSeconds: number;
+}
+
+export interface AcademicYearDto {
+  /** Academic year used by elections, overrides and results. */
+  academicYear: string;
+  /** Active academic year in Fénix, if it could be fetched. */
+  fenixAcademicYear?: string;
+  pinned: boolean;
+}
+
+export interface PinAcademicYearDto {
+  /** Academic year to pin, in the format "2023/2024", or null to follow Fénix again. */
+  academicYear?: string;
 }
 
 export interface DegreeDto {
@@ -97,6 +294,28 @@ export interface DegreeElectionsDto {
   elections: ElectionDto[];
 }
 
+/**
+ * An election as shown to observers, which never includes the votes of each nomination
+ * before the election ends, so that it can't be used to tell who is winning.
+ */
+export interface ObserverElectionDto {
+  id: number;
+  academicYear: string;
+  degree?: DegreeDto;
+  curricularYear?: number;
+  candidacyPeriod?: DateRangeDto;
+  votingPeriod: DateRangeDto;
+  round: number;
+  status: ElectionStatusDto;
+  nominations?: NominationDto[];
+  totalVotes?: number;
+}
+
+export interface ObserverDegreeElectionsDto {
+  degree: DegreeDto;
+  elections: ObserverElectionDto[];
+}
+
 export interface SearchPersonDto {
   election: number;
   query: string;
@@ -105,6 +324,8 @@ export interface SearchPersonDto {
 export interface SignedPersonSearchResultDto {
   username: string;
   displayName: string;
+  requestedBy: string;
+  issuedAt: string;
   signature: string;
 }
 
@@ -143,6 +364,46 @@ export interface EditNominationDto {
   valid?: boolean;
 }
 
+export interface RescheduleElectionDto {
+  candidacyPeriod?: DateRangeDto;
+  votingPeriod: DateRangeDto;
+}
+
+export interface PendingActionDto {
+  id: number;
+  kind: PendingActionKindDto;
+  /** Username of the admin, id of the election, or "{election}/{username}" of the nomination. */
+  target: string;
+  /** Everything needed to execute the action, such as the new periods of an election. */
+  action: unknown;
+  proposedBy: string;
+  proposedAt: string;
+  /** After this, the action can no longer be approved. */
+  expiresAt: string;
+  status: PendingActionStatusDto;
+  decidedBy?: string;
+  decidedAt?: string;
+}
+
+export enum PendingActionKindDto {
+  RemoveAdmin = 'REMOVE_ADMIN',
+  /** Invalidate a nomination that had already been validated. */
+  InvalidateNomination = 'INVALIDATE_NOMINATION',
+  /** Delete an election whose voting period has already started. */
+  DeleteElection = 'DELETE_ELECTION',
+  /** Change the periods of an election whose voting period has already started. */
+  RescheduleElection = 'RESCHEDULE_ELECTION',
+}
+
+export enum PendingActionStatusDto {
+  Pending = 'PENDING',
+  /** Approved by a second admin, and executed. */
+  Approved = 'APPROVED',
+  Rejected = 'REJECTED',
+  /** Not approved before its deadline. */
+  Expired = 'EXPIRED',
+}
+
 export interface BulkAddUserDegreeOverrideDto {
   degreeId: string;
   curricularYear: number;
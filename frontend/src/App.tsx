@@ -12,7 +12,11 @@ import './i18n';
 import { createBrowserRouter, RouterProvider } from 'react-router-dom';
 import Root, { loader as rootLoader } from './routes/root';
 import LoginCallback, { loader as loginCallbackLoader } from './routes/login';
-import AdminRoot from './routes/admin/root';
+import DevLogin, { action as devLoginAction } from './routes/dev-login';
+import AdminRoot, {
+  action as adminRootAction,
+  loader as adminRootLoader,
+} from './routes/admin/root';
 import Admins, {
   addAction as addAdminAction,
   AdminsAdd,
@@ -62,6 +66,10 @@ import BulkAddUserDegreeOverrides, {
   action as bulkAddUserDegreeOverridesAction,
   loader as bulkAddUserDegreeOverridesLoader,
 } from './routes/admin/user-degree-override/bulk-add';
+import PendingActions, {
+  action as pendingActionsAction,
+  loader as pendingActionsLoader,
+} from './routes/admin/pending-actions';
 
 function getThemeOptions(dark: boolean): ThemeOptions {
   return {
@@ -109,7 +117,12 @@ const router = createBrowserRouter([
           { path: 'vote/success', element: <ElectionVoteSuccess /> },
         ],
       },
-      { path: 'admin', element: <AdminRoot /> },
+      {
+        path: 'admin',
+        loader: adminRootLoader,
+        action: adminRootAction,
+        element: <AdminRoot />,
+      },
       {
         path: 'admin/admins',
         loader: adminsLoader,
@@ -155,6 +168,12 @@ const router = createBrowserRouter([
         action: bulkAddUserDegreeOverridesAction,
         element: <BulkAddUserDegreeOverrides />,
       },
+      {
+        path: 'admin/pending-actions',
+        loader: pendingActionsLoader,
+        action: pendingActionsAction,
+        element: <PendingActions />,
+      },
     ],
   },
   {
@@ -163,6 +182,12 @@ const router = createBrowserRouter([
     element: <LoginCallback />,
     errorElement: <RootErrorPage />,
   },
+  {
+    path: '/dev-login',
+    action: devLoginAction,
+    element: <DevLogin />,
+    errorElement: <RootErrorPage />,
+  },
   {
     path: '/logout',
     loader: logoutLoader,
@@ -1,20 +1,32 @@
 import {
+  AcademicYearDto,
   AddAdminDto,
+  AddObserverDto,
   AdminDto,
+  ApiTokenDto,
   AppConfigDto,
   AppErrorDto,
+  AuditLogEntryDto,
+  AuditLogVerificationDto,
   AuthDto,
   BulkAddUserDegreeOverrideDto,
   BulkCreateElectionsDto,
   BulkDeleteUserDegreeOverrideDto,
   CastVoteDto,
+  CreateApiTokenDto,
+  CreatedApiTokenDto,
   DegreeDto,
   DegreeElectionsDto,
   DegreeWithUserOverridesDto,
+  DevLoginDto,
   EditNominationDto,
   ElectionDto,
   ElectionWithUnverifiedNominationsDto,
   LoginDto,
+  ObserverDto,
+  PendingActionDto,
+  PinAcademicYearDto,
+  RescheduleElectionDto,
   SearchPersonDto,
   SignedPersonSearchResultDto,
   VoteOptionDto,
@@ -64,6 +76,11 @@ function buildJsonBody<T>(method: string, body?: T): RequestInit {
   return { method, headers: { 'Content-Type': 'application/json' }, body: JSON.stringify(body) };
 }
 
+// admin listings default to the active academic year
+function buildAcademicYearQuery(academicYear?: string): string {
+  return academicYear ? `?academic_year=${encodeURIComponent(academicYear)}` : '';
+}
+
 export function getAppConfig(): Promise<AppConfigDto> {
   return wrapFetch(fetch(`${BASE_URL}/config`));
 }
@@ -76,10 +93,35 @@ export function login(payload: LoginDto): Promise<AuthDto> {
   return wrapFetch(fetch(`${BASE_URL}/login`, buildJsonBody('POST', payload)));
 }
 
+export function devLogin(payload: DevLoginDto): Promise<AuthDto> {
+  return wrapFetch(fetch(`${BASE_URL}/dev/login`, buildJsonBody('POST', payload)));
+}
+
 export function logout(): Promise<AuthDto> {
   return wrapFetch(fetch(`${BASE_URL}/logout`, buildJsonBody('POST')));
 }
 
+export function getAcademicYears(): Promise<string[]> {
+  return wrapFetch(fetch(`${BASE_URL}/academic-years`));
+}
+
+export function getAcademicYear(): Promise<AcademicYearDto> {
+  return wrapFetch(fetch(`${BASE_URL}/academic-year`));
+}
+
+export function pinAcademicYear(payload: PinAcademicYearDto): Promise<AcademicYearDto> {
+  return wrapFetch(fetch(`${BASE_URL}/academic-year`, buildJsonBody('PUT', payload)));
+}
+
+export function getAuditLog(before?: number): Promise<AuditLogEntryDto[]> {
+  const query = before !== undefined ? `?before=${before}` : '';
+  return wrapFetch(fetch(`${BASE_URL}/audit-log${query}`));
+}
+
+export function verifyAuditLog(): Promise<AuditLogVerificationDto> {
+  return wrapFetch(fetch(`${BASE_URL}/audit-log/verify`));
+}
+
 export function getAdmins(): Promise<AdminDto[]> {
   return wrapFetch(fetch(`${BASE_URL}/admins`));
 }
@@ -88,16 +130,40 @@ export function addAdmin(payload: AddAdminDto): Promise<void> {
   return wrapFetch(fetch(`${BASE_URL}/admin`, buildJsonBody('POST', payload)));
 }
 
-export function removeAdmin(username: string): Promise<void> {
+export function removeAdmin(username: string): Promise<PendingActionDto> {
   return wrapFetch(fetch(`${BASE_URL}/admin/${username}`, buildJsonBody('DELETE')));
 }
 
+export function getApiTokens(): Promise<ApiTokenDto[]> {
+  return wrapFetch(fetch(`${BASE_URL}/api-tokens`));
+}
+
+export function createApiToken(payload: CreateApiTokenDto): Promise<CreatedApiTokenDto> {
+  return wrapFetch(fetch(`${BASE_URL}/api-token`, buildJsonBody('POST', payload)));
+}
+
+export function revokeApiToken(id: number): Promise<void> {
+  return wrapFetch(fetch(`${BASE_URL}/api-token/${id}`, buildJsonBody('DELETE')));
+}
+
+export function getObservers(): Promise<ObserverDto[]> {
+  return wrapFetch(fetch(`${BASE_URL}/observers`));
+}
+
+export function grantObserver(payload: AddObserverDto): Promise<void> {
+  return wrapFetch(fetch(`${BASE_URL}/observer`, buildJsonBody('POST', payload)));
+}
+
+export function revokeObserver(username: string): Promise<void> {
+  return wrapFetch(fetch(`${BASE_URL}/observer/${username}`, buildJsonBody('DELETE')));
+}
+
 export function setupFirstAdmin(): Promise<void> {
   return wrapFetch(fetch(`${BASE_URL}/setup/admin`, buildJsonBody('POST')));
 }
 
-export function getDegreeElections(): Promise<DegreeElectionsDto[]> {
-  return wrapFetch(fetch(`${BASE_URL}/degrees/elections`));
+export function getDegreeElections(academicYear?: string): Promise<DegreeElectionsDto[]> {
+  return wrapFetch(fetch(`${BASE_URL}/degrees/elections${buildAcademicYearQuery(academicYear)}`));
 }
 
 export function getDegrees(): Promise<DegreeDto[]> {
@@ -158,22 +224,67 @@ export function electionVote(electionId: number, payload: CastVoteDto): Promise<
   );
 }
 
-export function countUnverifiedNominations(): Promise<Record<number, number>> {
-  return wrapFetch(fetch(`${BASE_URL}/elections/nominations/unverified-count`));
+export function countUnverifiedNominations(
+  academicYear?: string
+): Promise<Record<number, number>> {
+  return wrapFetch(
+    fetch(
+      `${BASE_URL}/elections/nominations/unverified-count${buildAcademicYearQuery(academicYear)}`
+    )
+  );
 }
 
-export function getUnverifiedNominations(): Promise<ElectionWithUnverifiedNominationsDto[]> {
-  return wrapFetch(fetch(`${BASE_URL}/elections/nominations/unverified`));
+export function getUnverifiedNominations(
+  academicYear?: string
+): Promise<ElectionWithUnverifiedNominationsDto[]> {
+  return wrapFetch(
+    fetch(`${BASE_URL}/elections/nominations/unverified${buildAcademicYearQuery(academicYear)}`)
+  );
 }
 
-export function editNomination(electionId: number, payload: EditNominationDto): Promise<void> {
+/** Resolves to the pending action, if the edit must be approved by another admin. */
+export function editNomination(
+  electionId: number,
+  payload: EditNominationDto
+): Promise<PendingActionDto | undefined> {
   return wrapFetch(
     fetch(`${BASE_URL}/election/${electionId}/nomination`, buildJsonBody('PATCH', payload))
   );
 }
 
-export function getUserDegreeOverrides(): Promise<DegreeWithUserOverridesDto[]> {
-  return wrapFetch(fetch(`${BASE_URL}/user-degree-overrides`));
+/** Resolves to the pending action, if the deletion must be approved by another admin. */
+export function deleteElection(electionId: number): Promise<PendingActionDto | undefined> {
+  return wrapFetch(fetch(`${BASE_URL}/election/${electionId}`, buildJsonBody('DELETE')));
+}
+
+/** Resolves to the pending action, if the change must be approved by another admin. */
+export function rescheduleElection(
+  electionId: number,
+  payload: RescheduleElectionDto
+): Promise<PendingActionDto | undefined> {
+  return wrapFetch(
+    fetch(`${BASE_URL}/election/${electionId}/periods`, buildJsonBody('PUT', payload))
+  );
+}
+
+export function getPendingActions(): Promise<PendingActionDto[]> {
+  return wrapFetch(fetch(`${BASE_URL}/pending-actions`));
+}
+
+export function approvePendingAction(id: number): Promise<void> {
+  return wrapFetch(fetch(`${BASE_URL}/pending-action/${id}/approve`, buildJsonBody('POST')));
+}
+
+export function rejectPendingAction(id: number): Promise<void> {
+  return wrapFetch(fetch(`${BASE_URL}/pending-action/${id}/reject`, buildJsonBody('POST')));
+}
+
+export function getUserDegreeOverrides(
+  academicYear?: string
+): Promise<DegreeWithUserOverridesDto[]> {
+  return wrapFetch(
+    fetch(`${BASE_URL}/user-degree-overrides${buildAcademicYearQuery(academicYear)}`)
+  );
 }
 
 export function addUserDegreeOverrides(payload: BulkAddUserDegreeOverrideDto): Promise<void> {
@@ -184,6 +295,6 @@ export function deleteUserDegreeOverrides(payload: BulkDeleteUserDegreeOverrideD
   return wrapFetch(fetch(`${BASE_URL}/user-degree-overrides`, buildJsonBody('DELETE', payload)));
 }
 
-export function getElectionsResultsDownloadCsvUrl(): string {
-  return `${BASE_URL}/elections/results/download/csv`;
+export function getElectionsResultsDownloadCsvUrl(academicYear?: string): string {
+  return `${BASE_URL}/elections/results/download/csv${buildAcademicYearQuery(academicYear)}`;
 }
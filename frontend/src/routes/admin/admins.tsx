@@ -11,6 +11,7 @@ import {
   DialogContent,
   DialogContentText,
   DialogTitle,
+  MenuItem,
   TextField,
   Tooltip,
   Typography,
@@ -29,7 +30,7 @@ import {
   useParams,
   useRouteLoaderData,
 } from 'react-router-dom';
-import { AdminDto } from '../../@types/api';
+import { AddAdminDto, AdminDto, AdminRoleDto } from '../../@types/api';
 import { addAdmin, getAdmins, removeAdmin } from '../../api';
 import FenixAvatar from '../../components/fenix/FenixAvatar';
 import { RootData } from '../root';
@@ -77,6 +78,10 @@ function Admins() {
                     />
                   )}
                 </Typography>
+                <Typography variant='subtitle1' component='span'>
+                  {t(`admin.subpages.admin-management.roles.${admin.role}`)}
+                  {admin.scope && ` (${admin.scope})`}
+                </Typography>
                 <Typography variant='subtitle1' component='span'>
                   <Trans
                     i18nKey='admin.subpages.admin-management.since'
@@ -139,8 +144,10 @@ function Admins() {
 export async function addAction({ request }: ActionFunctionArgs) {
   const formData = await request.formData();
   const username = formData.get('username')?.toString() ?? '';
+  const role = formData.get('role')?.toString() as AddAdminDto['role'];
+  const scope = formData.get('scope')?.toString() || undefined;
 
-  await addAdmin({ username });
+  await addAdmin({ username, role, scope });
 
   return redirect('..');
 }
@@ -169,6 +176,32 @@ export function AdminsAdd() {
             fullWidth
             placeholder={t('admin.subpages.admin-management.add-dialog.username-placeholder')}
           />
+          <TextField
+            select
+            variant='outlined'
+            id='role'
+            name='role'
+            label={t('admin.subpages.admin-management.add-dialog.role-label')}
+            defaultValue={AdminRoleDto.SuperAdmin}
+            fullWidth
+            sx={{ mt: 2 }}
+          >
+            {Object.values(AdminRoleDto).map((role) => (
+              <MenuItem key={role} value={role}>
+                {t(`admin.subpages.admin-management.roles.${role}`)}
+              </MenuItem>
+            ))}
+          </TextField>
+          <TextField
+            variant='outlined'
+            id='scope'
+            name='scope'
+            label={t('admin.subpages.admin-management.add-dialog.scope-label')}
+            helperText={t('admin.subpages.admin-management.add-dialog.scope-help')}
+            type='text'
+            fullWidth
+            sx={{ mt: 2 }}
+          />
         </DialogContent>
         <DialogActions>
           <Button component={Link} to='..'>
@@ -186,9 +219,10 @@ export function AdminsAdd() {
 export async function removeAction({ params }: ActionFunctionArgs) {
   const username = params.username ?? '';
 
+  // removals must be approved by another admin
   await removeAdmin(username);
 
-  return redirect('..');
+  return redirect('/admin/pending-actions');
 }
 
 export function AdminsRemove() {
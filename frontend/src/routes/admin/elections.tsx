@@ -8,10 +8,12 @@ import {
   DialogContent,
   DialogTitle,
   IconButton,
+  MenuItem,
   Step,
   StepContent,
   StepLabel,
   Stepper,
+  TextField,
   Toolbar,
   Typography,
   useTheme,
@@ -22,16 +24,19 @@ import { useTranslation } from 'react-i18next';
 import {
   ActionFunctionArgs,
   Link,
+  LoaderFunctionArgs,
   Outlet,
   redirect,
   useLoaderData,
   useNavigate,
   useOutletContext,
+  useSearchParams,
 } from 'react-router-dom';
 import { DegreeElectionsDto } from '../../@types/api';
 import {
   bulkCreateElections,
   countUnverifiedNominations,
+  getAcademicYears,
   getDegreeElections,
   getElectionsResultsDownloadCsvUrl,
 } from '../../api';
@@ -54,26 +59,42 @@ const DATE_FORMAT = {
 };
 
 interface ElectionsData {
+  academicYear?: string;
+  academicYears: string[];
   degrees: DegreeElectionsDto[];
   unverifiedNominationsCountByElection: Record<number, number>;
   unverifiedNominationsCount: number;
 }
 
-export async function loader(): Promise<ElectionsData> {
-  const degrees = await getDegreeElections();
-  const unverifiedNominationsCountByElection = await countUnverifiedNominations();
+export async function loader({ request }: LoaderFunctionArgs): Promise<ElectionsData> {
+  const academicYear = new URL(request.url).searchParams.get('academicYear') ?? undefined;
+  const academicYears = await getAcademicYears();
+  const degrees = await getDegreeElections(academicYear);
+  const unverifiedNominationsCountByElection = await countUnverifiedNominations(academicYear);
 
   const unverifiedNominationsCount = Object.values(unverifiedNominationsCountByElection).reduce(
     (acc, v) => acc + v,
     0
   );
 
-  return { degrees, unverifiedNominationsCountByElection, unverifiedNominationsCount };
+  return {
+    academicYear,
+    academicYears,
+    degrees,
+    unverifiedNominationsCountByElection,
+    unverifiedNominationsCount,
+  };
 }
 
 function Elections() {
-  const { degrees, unverifiedNominationsCountByElection, unverifiedNominationsCount } =
-    useLoaderData() as ElectionsData;
+  const {
+    academicYear,
+    academicYears,
+    degrees,
+    unverifiedNominationsCountByElection,
+    unverifiedNominationsCount,
+  } = useLoaderData() as ElectionsData;
+  const [, setSearchParams] = useSearchParams();
   const { t } = useTranslation();
   const sortedDegrees = useSortAndGroupDegrees(degrees);
 
@@ -83,12 +104,31 @@ function Elections() {
         {t('admin.subpages.election-management.title')}
       </Typography>
       <Box my={4} display='flex' flexDirection='row-reverse' gap={1} flexWrap='wrap'>
+        <TextField
+          select
+          size='small'
+          label={t('admin.subpages.election-management.academic-year')}
+          value={academicYear ?? ''}
+          onChange={(event) =>
+            setSearchParams(event.target.value ? { academicYear: event.target.value } : {})
+          }
+          sx={{ minWidth: 200 }}
+        >
+          <MenuItem value=''>
+            {t('admin.subpages.election-management.active-academic-year')}
+          </MenuItem>
+          {academicYears.map((year) => (
+            <MenuItem key={year} value={year}>
+              {year}
+            </MenuItem>
+          ))}
+        </TextField>
         <Button component={Link} to='bulk-add' variant='contained' startIcon={<AddRounded />}>
           {t('admin.subpages.election-management.create-elections')}
         </Button>
         <Button
           component='a'
-          href={getElectionsResultsDownloadCsvUrl()}
+          href={getElectionsResultsDownloadCsvUrl(academicYear)}
           target='_blank'
           variant='outlined'
           startIcon={<DownloadRounded />}
@@ -0,0 +1,123 @@
+import { Button, Card, CardActions, CardContent, Chip, Typography } from '@mui/material';
+import Grid from '@mui/material/Unstable_Grid2';
+import { useTranslation } from 'react-i18next';
+import { ActionFunctionArgs, Form, useLoaderData, useRouteLoaderData } from 'react-router-dom';
+import { PendingActionDto, PendingActionStatusDto } from '../../@types/api';
+import { approvePendingAction, getPendingActions, rejectPendingAction } from '../../api';
+import { RootData } from '../root';
+
+interface PendingActionsData {
+  pendingActions: PendingActionDto[];
+}
+
+export async function loader(): Promise<PendingActionsData> {
+  const pendingActions = await getPendingActions();
+
+  return { pendingActions };
+}
+
+export async function action({ request }: ActionFunctionArgs) {
+  const formData = await request.formData();
+  const id = parseInt(formData.get('id')?.toString() ?? '', 10);
+
+  if (formData.get('intent') === 'approve') {
+    await approvePendingAction(id);
+  } else {
+    await rejectPendingAction(id);
+  }
+
+  return null;
+}
+
+const statusColors = {
+  [PendingActionStatusDto.Pending]: 'warning',
+  [PendingActionStatusDto.Approved]: 'success',
+  [PendingActionStatusDto.Rejected]: 'error',
+  [PendingActionStatusDto.Expired]: 'default',
+} as const;
+
+function PendingActions() {
+  const { auth } = useRouteLoaderData('root') as RootData;
+  const { pendingActions } = useLoaderData() as PendingActionsData;
+  const { t } = useTranslation();
+
+  return (
+    <>
+      <Typography variant='h2' gutterBottom>
+        {t('admin.subpages.pending-actions.title')}
+      </Typography>
+      <Typography variant='body1' sx={{ mb: 2 }}>
+        {t('admin.subpages.pending-actions.description')}
+      </Typography>
+      {pendingActions.length === 0 && (
+        <Typography variant='body1' color='text.secondary'>
+          {t('admin.subpages.pending-actions.empty')}
+        </Typography>
+      )}
+      <Grid container spacing={2}>
+        {pendingActions.map((pendingAction) => (
+          <Grid key={pendingAction.id} xs={12} md={6}>
+            <Card sx={{ height: '100%' }}>
+              <CardContent>
+                <Typography variant='h6' component='span'>
+                  {t(`admin.subpages.pending-actions.kinds.${pendingAction.kind}`, {
+                    target: pendingAction.target,
+                  })}
+                </Typography>
+                <Chip
+                  sx={{ ml: 1 }}
+                  size='small'
+                  color={statusColors[pendingAction.status]}
+                  label={t(`admin.subpages.pending-actions.statuses.${pendingAction.status}`)}
+                />
+                <Typography variant='body2' sx={{ mt: 1 }}>
+                  {t('admin.subpages.pending-actions.proposed', {
+                    username: pendingAction.proposedBy,
+                    date: new Date(pendingAction.proposedAt),
+                  })}
+                </Typography>
+                {pendingAction.status === PendingActionStatusDto.Pending && (
+                  <Typography variant='body2'>
+                    {t('admin.subpages.pending-actions.expires', {
+                      date: new Date(pendingAction.expiresAt),
+                    })}
+                  </Typography>
+                )}
+                {pendingAction.decidedBy && pendingAction.decidedAt && (
+                  <Typography variant='body2'>
+                    {t('admin.subpages.pending-actions.decided', {
+                      username: pendingAction.decidedBy,
+                      date: new Date(pendingAction.decidedAt),
+                    })}
+                  </Typography>
+                )}
+              </CardContent>
+              {pendingAction.status === PendingActionStatusDto.Pending && (
+                <CardActions>
+                  <Form method='post'>
+                    <input type='hidden' name='id' value={pendingAction.id} />
+                    <Button
+                      type='submit'
+                      name='intent'
+                      value='approve'
+                      disabled={pendingAction.proposedBy === auth.user.username}
+                    >
+                      {t('admin.subpages.pending-actions.approve-button')}
+                    </Button>
+                    <Button type='submit' name='intent' value='reject' color='error'>
+                      {pendingAction.proposedBy === auth.user.username
+                        ? t('admin.subpages.pending-actions.withdraw-button')
+                        : t('admin.subpages.pending-actions.reject-button')}
+                    </Button>
+                  </Form>
+                </CardActions>
+              )}
+            </Card>
+          </Grid>
+        ))}
+      </Grid>
+    </>
+  );
+}
+
+export default PendingActions;
@@ -3,11 +3,23 @@ import {
   BallotRounded,
   ChevronRight,
   GroupRounded,
+  PendingActionsRounded,
 } from '@mui/icons-material';
-import { Card, CardContent, Typography } from '@mui/material';
+import {
+  Alert,
+  Box,
+  Button,
+  Card,
+  CardActions,
+  CardContent,
+  TextField,
+  Typography,
+} from '@mui/material';
 import Grid from '@mui/material/Unstable_Grid2';
 import { useTranslation } from 'react-i18next';
-import { Link } from 'react-router-dom';
+import { ActionFunctionArgs, Form, Link, useLoaderData } from 'react-router-dom';
+import { AcademicYearDto } from '../../@types/api';
+import { getAcademicYear, pinAcademicYear } from '../../api';
 
 const subpages = [
   {
@@ -28,9 +40,77 @@ const subpages = [
     icon: <GroupRounded fontSize='inherit' />,
     path: '/admin/user-degree-overrides',
   },
+  {
+    title: 'admin.subpages.pending-actions.title',
+    subtitle: 'admin.subpages.pending-actions.subtitle',
+    icon: <PendingActionsRounded fontSize='inherit' />,
+    path: '/admin/pending-actions',
+  },
 ];
 
+interface AdminRootData {
+  academicYear: AcademicYearDto;
+}
+
+export async function loader(): Promise<AdminRootData> {
+  const academicYear = await getAcademicYear();
+
+  return { academicYear };
+}
+
+export async function action({ request }: ActionFunctionArgs) {
+  const formData = await request.formData();
+  const academicYear = formData.get('academicYear')?.toString().trim() || undefined;
+
+  await pinAcademicYear({ academicYear });
+
+  return null;
+}
+
+function AcademicYearCard({ academicYear }: AdminRootData) {
+  const { t } = useTranslation();
+
+  return (
+    <Card variant='outlined' sx={{ mb: 2 }}>
+      <Form method='put'>
+        <CardContent>
+          <Typography variant='h5' gutterBottom>
+            {t('admin.academic-year.title', { academicYear: academicYear.academicYear })}
+          </Typography>
+          {academicYear.pinned ? (
+            <Alert severity='warning'>
+              {t('admin.academic-year.pinned', {
+                fenixAcademicYear: academicYear.fenixAcademicYear ?? '?',
+              })}
+            </Alert>
+          ) : (
+            <Box>
+              <Typography variant='body2' sx={{ mb: 2 }}>
+                {t('admin.academic-year.description')}
+              </Typography>
+              <TextField
+                variant='outlined'
+                size='small'
+                name='academicYear'
+                label={t('admin.academic-year.label')}
+                placeholder={academicYear.academicYear}
+                required
+              />
+            </Box>
+          )}
+        </CardContent>
+        <CardActions>
+          <Button type='submit' color={academicYear.pinned ? 'warning' : 'primary'}>
+            {t(academicYear.pinned ? 'admin.academic-year.unpin' : 'admin.academic-year.pin')}
+          </Button>
+        </CardActions>
+      </Form>
+    </Card>
+  );
+}
+
 function AdminRoot() {
+  const { academicYear } = useLoaderData() as AdminRootData;
   const { t } = useTranslation();
 
   return (
@@ -38,6 +118,7 @@ function AdminRoot() {
       <Typography variant='h2' gutterBottom>
         {t('admin.page.title')}
       </Typography>
+      <AcademicYearCard academicYear={academicYear} />
       <Grid container spacing={2}>
         {subpages.map((subpage) => (
           <Grid xs={12} md={6} key={subpage.path}>
@@ -0,0 +1,78 @@
+import { Box, Button, Container, TextField, Typography } from '@mui/material';
+import { useTranslation } from 'react-i18next';
+import { ActionFunctionArgs, Form, redirect } from 'react-router-dom';
+import { DegreeEntryDto } from '../@types/api';
+import { devLogin } from '../api';
+
+/**
+ * Parse degree entries, one per line, in the format "<degree id> <curricular year>".
+ * Appending "mobility" to a line makes the user ineligible for nominations in that degree.
+ */
+function parseDegreeEntries(text: string): DegreeEntryDto[] {
+  return text
+    .split('\n')
+    .map((line) => line.trim().split(/\s+/))
+    .filter(([degreeId, curricularYear]) => degreeId && curricularYear)
+    .map(([degreeId, curricularYear, state]) => ({
+      degreeId,
+      curricularYear: parseInt(curricularYear, 10),
+      nominationElegible: state !== 'mobility',
+    }));
+}
+
+export async function action({ request }: ActionFunctionArgs) {
+  const formData = await request.formData();
+  const username = formData.get('username')?.toString() ?? '';
+  const name = formData.get('name')?.toString() || undefined;
+  const degreeEntries = parseDegreeEntries(formData.get('degreeEntries')?.toString() ?? '');
+
+  await devLogin({ username, name, degreeEntries });
+
+  return redirect('/');
+}
+
+function DevLogin() {
+  const { t } = useTranslation();
+
+  return (
+    <Container maxWidth='sm'>
+      <Box
+        sx={{
+          minHeight: '100vh',
+          display: 'flex',
+          flexDirection: 'column',
+          justifyContent: 'center',
+        }}
+      >
+        <Typography variant='h4' gutterBottom>
+          {t('login.dev.title')}
+        </Typography>
+        <Typography gutterBottom>{t('login.dev.description')}</Typography>
+        <Form method='post'>
+          <TextField
+            name='username'
+            label={t('login.dev.username')}
+            required
+            fullWidth
+            margin='normal'
+          />
+          <TextField name='name' label={t('login.dev.name')} fullWidth margin='normal' />
+          <TextField
+            name='degreeEntries'
+            label={t('login.dev.degree-entries')}
+            helperText={t('login.dev.degree-entries-help')}
+            multiline
+            minRows={3}
+            fullWidth
+            margin='normal'
+          />
+          <Button type='submit' variant='contained' sx={{ mt: 2 }}>
+            {t('login.dev.submit')}
+          </Button>
+        </Form>
+      </Box>
+    </Container>
+  );
+}
+
+export default DevLogin;
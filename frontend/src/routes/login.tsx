@@ -22,7 +22,7 @@ export async function loader({ request }: LoaderFunctionArgs) {
     return redirect('/');
   }
 
-  return defer({ user: login({ code }) });
+  return defer({ user: login({ code, state: url.searchParams.get('state') ?? undefined }) });
 }
 
 function LoginCallback() {
@@ -18,12 +18,7 @@ export async function loader() {
     return { appConfig, auth };
   } catch (e) {
     if (e instanceof ApiError && e.getError().key === 'error.unauthorized') {
-      const { baseUrl, clientId, redirectUrl } = appConfig.fenix;
-      return redirect(
-        `${baseUrl}/oauth/userdialog?client_id=${encodeURIComponent(
-          clientId
-        )}&redirect_uri=${encodeURIComponent(redirectUrl)}`
-      );
+      return redirect(appConfig.devLogin ? '/dev-login' : appConfig.loginUrl);
     }
     throw e;
   }